@@ -1,124 +1,260 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 //! API client for communicating with the SOCP control plane
+//!
+//! Paths are relative to the context's `api_url`. Every request carries the
+//! operator in `X-SOCP-Operator`; the mTLS identity authenticates it.
 
-use anyhow::Result;
-use reqwest::Client;
+use anyhow::{anyhow, bail, Context as _, Result};
+use async_trait::async_trait;
+use reqwest::{Certificate, Client, Identity, Method, RequestBuilder, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
 
-use crate::app::{Alert, Deployment, Site};
+use crate::app::{Alert, Approval, Deployment, Site};
+use crate::composer::{DeploymentRequest, FieldError, SiteGroup};
+use crate::config::Context;
+use crate::history::DeploymentRecord;
+use crate::maintenance::ChangeCalendar;
+use crate::plane::ControlPlane;
+use crate::rbac::Permissions;
+use crate::wordpress::WordPressInventory;
+
+/// Errors the control plane reports in a structured form
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("validation failed: {}", format_field_errors(.0))]
+    Validation(Vec<FieldError>),
+    /// HTTP 403 with the server's explanation
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+}
+
+fn format_field_errors(errors: &[FieldError]) -> String {
+    errors.iter()
+        .map(|e| format!("{}: {}", e.field, e.message))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Body of a 422 response
+#[derive(Deserialize)]
+struct ValidationErrors {
+    errors: Vec<FieldError>,
+}
+
+/// Body of other error responses
+#[derive(Deserialize)]
+struct ErrorMessage {
+    error: String,
+}
+
+#[derive(Deserialize)]
+struct Silence {
+    until: chrono::DateTime<chrono::Utc>,
+}
+
+/// A control plane that accepts but never answers gives up after this
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
 
 pub struct ApiClient {
     client: Client,
     base_url: String,
+    /// Identity the control plane attributes our actions to
+    operator: String,
 }
 
 impl ApiClient {
-    pub fn new(base_url: &str) -> Result<Self> {
-        let client = Client::builder()
-            .danger_accept_invalid_certs(false) // Always verify certs
-            .build()?;
+    pub fn new(context: &Context, operator: &str) -> Result<Self> {
+        let mut builder = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .connect_timeout(CONNECT_TIMEOUT)
+            .danger_accept_invalid_certs(false); // Always verify certs
+        if let Some(path) = &context.identity {
+            let pem = std::fs::read(path)
+                .with_context(|| format!("reading TLS identity {}", path.display()))?;
+            builder = builder.identity(Identity::from_pem(&pem)?);
+        }
+        if let Some(path) = &context.ca_cert {
+            let pem = std::fs::read(path)
+                .with_context(|| format!("reading CA certificate {}", path.display()))?;
+            builder = builder.add_root_certificate(Certificate::from_pem(&pem)?);
+        }
+        let client = builder.build()?;
 
         Ok(Self {
             client,
-            base_url: base_url.trim_end_matches('/').to_string(),
+            base_url: context.api_url.trim_end_matches('/').to_string(),
+            operator: operator.to_string(),
         })
     }
 
-    pub async fn get_sites(&self) -> Result<Vec<Site>> {
-        // TODO: Implement actual API call
-        // For now, return mock data for testing the TUI
-        Ok(vec![
-            Site {
-                id: "site-1".to_string(),
-                domain: "example.com".to_string(),
-                status: crate::app::SiteStatus::Healthy,
-                last_sync: Some(chrono::Utc::now()),
-                config_hash: Some("sha256:abc123".to_string()),
-                response_time_ms: Some(145),
-                ssl_expires: Some(chrono::Utc::now() + chrono::Duration::days(60)),
-                tags: vec!["production".to_string(), "wordpress".to_string()],
-                environment: "production".to_string(),
-            },
-            Site {
-                id: "site-2".to_string(),
-                domain: "blog.example.com".to_string(),
-                status: crate::app::SiteStatus::Warning,
-                last_sync: Some(chrono::Utc::now() - chrono::Duration::hours(2)),
-                config_hash: Some("sha256:def456".to_string()),
-                response_time_ms: Some(523),
-                ssl_expires: Some(chrono::Utc::now() + chrono::Duration::days(15)),
-                tags: vec!["production".to_string(), "wordpress".to_string()],
-                environment: "production".to_string(),
-            },
-            Site {
-                id: "site-3".to_string(),
-                domain: "staging.example.com".to_string(),
-                status: crate::app::SiteStatus::Drifted,
-                last_sync: Some(chrono::Utc::now() - chrono::Duration::days(1)),
-                config_hash: Some("sha256:ghi789".to_string()),
-                response_time_ms: Some(89),
-                ssl_expires: Some(chrono::Utc::now() + chrono::Duration::days(90)),
-                tags: vec!["staging".to_string()],
-                environment: "staging".to_string(),
-            },
-        ])
-    }
-
-    pub async fn get_alerts(&self) -> Result<Vec<Alert>> {
-        // TODO: Implement actual API call
-        Ok(vec![
-            Alert {
-                id: "alert-1".to_string(),
-                site_id: "site-2".to_string(),
-                severity: crate::app::AlertSeverity::Warning,
-                message: "SSL certificate expires in 15 days".to_string(),
-                timestamp: chrono::Utc::now(),
-                acknowledged: false,
-            },
-            Alert {
-                id: "alert-2".to_string(),
-                site_id: "site-2".to_string(),
-                severity: crate::app::AlertSeverity::Warning,
-                message: "Response time > 500ms".to_string(),
-                timestamp: chrono::Utc::now() - chrono::Duration::hours(1),
-                acknowledged: false,
-            },
-        ])
-    }
-
-    pub async fn get_pending_deployments(&self) -> Result<Vec<Deployment>> {
-        // TODO: Implement actual API call
-        Ok(vec![
-            Deployment {
-                id: "deploy-1".to_string(),
-                sites: vec!["site-1".to_string(), "site-2".to_string()],
-                change_type: "security-headers-update".to_string(),
-                scheduled: Some(chrono::Utc::now() + chrono::Duration::hours(6)),
-                status: crate::app::DeploymentStatus::Pending,
-            },
-        ])
-    }
-
-    pub async fn sync_site(&self, site_id: &str) -> Result<()> {
-        // TODO: Implement actual API call
-        tracing::info!("Syncing site: {}", site_id);
+    fn url(&self, segments: &[&str]) -> Result<Url> {
+        let mut url = Url::parse(&self.base_url)?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("{} cannot take a path", self.base_url))?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url)
+    }
+
+    fn request(&self, method: Method, segments: &[&str]) -> Result<RequestBuilder> {
+        Ok(self.client.request(method, self.url(segments)?).header("X-SOCP-Operator", &self.operator))
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        check(request.send().await?).await
+    }
+
+    async fn get<T: DeserializeOwned>(&self, segments: &[&str]) -> Result<T> {
+        let response = self.send(self.request(Method::GET, segments)?).await?;
+        Ok(response.json().await?)
+    }
+
+    async fn post<T: DeserializeOwned>(&self, segments: &[&str], body: serde_json::Value) -> Result<T> {
+        let response = self.send(self.request(Method::POST, segments)?.json(&body)).await?;
+        Ok(response.json().await?)
+    }
+
+    /// POST whose response carries nothing we need
+    async fn act(&self, segments: &[&str], body: serde_json::Value) -> Result<()> {
+        self.send(self.request(Method::POST, segments)?.json(&body)).await?;
+        Ok(())
+    }
+}
+
+/// Pass successful responses through; turn the rest into errors carrying the server's explanation
+async fn check(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let path = response.url().path().to_string();
+    let body = response.text().await.unwrap_or_default();
+    if status == StatusCode::UNPROCESSABLE_ENTITY {
+        if let Ok(validation) = serde_json::from_str::<ValidationErrors>(&body) {
+            return Err(ApiError::Validation(validation.errors).into());
+        }
+    }
+    let message = serde_json::from_str::<ErrorMessage>(&body)
+        .map(|e| e.error)
+        .unwrap_or_else(|_| body.trim().to_string());
+    if status == StatusCode::FORBIDDEN {
+        return Err(ApiError::PermissionDenied(message).into());
+    }
+    bail!("{} {}: {}", status, path, message)
+}
+
+#[async_trait]
+impl ControlPlane for ApiClient {
+    async fn get_permissions(&self) -> Result<Permissions> {
+        self.get(&["me", "permissions"]).await
+    }
+
+    async fn get_sites(&self) -> Result<Vec<Site>> {
+        self.get(&["sites"]).await
+    }
+
+    async fn get_alerts(&self) -> Result<Vec<Alert>> {
+        self.get(&["alerts"]).await
+    }
+
+    async fn get_pending_deployments(&self) -> Result<Vec<Deployment>> {
+        self.get(&["deployments", "pending"]).await
+    }
+
+    async fn get_groups(&self) -> Result<Vec<SiteGroup>> {
+        self.get(&["groups"]).await
+    }
+
+    async fn get_change_calendar(&self) -> Result<ChangeCalendar> {
+        self.get(&["calendar"]).await
+    }
+
+    async fn get_deployment_history(&self, limit: usize) -> Result<Vec<DeploymentRecord>> {
+        let request = self.request(Method::GET, &["history"])?.query(&[("limit", limit)]);
+        Ok(self.send(request).await?.json().await?)
+    }
+
+    async fn get_config_diff(&self, site_id: &str) -> Result<String> {
+        let response = self.send(self.request(Method::GET, &["sites", site_id, "diff"])?).await?;
+        Ok(response.text().await?)
+    }
+
+    async fn get_wordpress_inventory(&self, site_id: &str) -> Result<Option<WordPressInventory>> {
+        let response = self.request(Method::GET, &["sites", site_id, "wordpress"])?.send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(check(response).await?.json().await?))
+    }
+
+    async fn sync_site(&self, site_id: &str, override_reason: Option<&str>) -> Result<()> {
+        tracing::info!("Syncing site: {} (override: {:?})", site_id, override_reason);
+        self.act(&["sites", site_id, "sync"], json!({ "override_reason": override_reason })).await
+    }
+
+    async fn silence_site(&self, site_id: &str, duration_secs: u64) -> Result<chrono::DateTime<chrono::Utc>> {
+        tracing::info!("Silencing site {} for {}s", site_id, duration_secs);
+        let silence: Silence = self.post(&["sites", site_id, "silence"], json!({ "duration_secs": duration_secs })).await?;
+        Ok(silence.until)
+    }
+
+    async fn acknowledge_alert(&self, alert_id: &str) -> Result<()> {
+        tracing::info!("Acknowledging alert: {}", alert_id);
+        self.act(&["alerts", alert_id, "acknowledge"], json!({})).await
+    }
+
+    async fn dismiss_alert(&self, alert_id: &str) -> Result<()> {
+        tracing::info!("Dismissing alert: {}", alert_id);
+        self.send(self.request(Method::DELETE, &["alerts", alert_id])?).await?;
         Ok(())
     }
 
-    pub async fn get_config_diff(&self, site_id: &str) -> Result<String> {
-        // TODO: Implement actual API call
-        Ok(format!(
-            r#"--- a/sites/{}/config.ncl
-+++ b/sites/{}/config.ncl
-@@ -15,7 +15,7 @@
-   security = {{
-     headers = {{
--      x_frame_options = 'SAMEORIGIN,
-+      x_frame_options = 'DENY,
-       content_security_policy = "default-src 'self'",
-     }},
-   }},
-"#,
-            site_id, site_id
-        ))
+    async fn create_deployment(&self, request: &DeploymentRequest) -> Result<Deployment> {
+        tracing::info!("Creating deployment {} for {:?}", request.change_type, request.sites);
+        self.post(&["deployments"], serde_json::to_value(request)?).await
+    }
+
+    async fn preview_deployment(&self, request: &DeploymentRequest) -> Result<String> {
+        let request = self.request(Method::POST, &["deployments", "preview"])?.json(request);
+        Ok(self.send(request).await?.text().await?)
+    }
+
+    async fn approve_deployment(&self, deployment_id: &str, override_reason: Option<&str>) -> Result<Approval> {
+        tracing::info!("Approving deployment: {} (override: {:?})", deployment_id, override_reason);
+        self.post(&["deployments", deployment_id, "approve"], json!({ "override_reason": override_reason })).await
+    }
+
+    async fn reject_deployment(&self, deployment_id: &str) -> Result<()> {
+        tracing::info!("Rejecting deployment: {}", deployment_id);
+        self.act(&["deployments", deployment_id, "reject"], json!({})).await
+    }
+
+    async fn pause_deployment(&self, deployment_id: &str) -> Result<()> {
+        tracing::info!("Pausing deployment: {}", deployment_id);
+        self.act(&["deployments", deployment_id, "pause"], json!({})).await
+    }
+
+    async fn resume_deployment(&self, deployment_id: &str) -> Result<()> {
+        tracing::info!("Resuming deployment: {}", deployment_id);
+        self.act(&["deployments", deployment_id, "resume"], json!({})).await
+    }
+
+    async fn abort_deployment(&self, deployment_id: &str) -> Result<()> {
+        tracing::info!("Aborting deployment: {}", deployment_id);
+        self.act(&["deployments", deployment_id, "abort"], json!({})).await
+    }
+
+    async fn rollback_deployment(&self, deployment_id: &str) -> Result<()> {
+        tracing::info!("Rolling back deployment: {}", deployment_id);
+        self.act(&["deployments", deployment_id, "rollback"], json!({})).await
+    }
+
+    async fn create_rollback(&self, record: &DeploymentRecord, config_hash: &str) -> Result<Deployment> {
+        tracing::info!("Rolling back {} to {}", record.id, config_hash);
+        self.post(&["history", &record.id, "rollback"], json!({ "config_hash": config_hash })).await
     }
 }
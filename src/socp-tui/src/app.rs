@@ -2,11 +2,35 @@
 //! Application state and logic
 
 use anyhow::Result;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use ratatui::layout::{Position, Rect};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
 
-use crate::api::ApiClient;
+use crate::api::ApiError;
+use crate::audit::{AuditEntry, AuditLog, Verification};
+use crate::composer::{Composer, ComposerField, DeploymentRequest, SiteGroup, TargetMode};
+use crate::config::{Config, ConfigWatcher, Context};
+use crate::dnssec::{DnssecValidator, ZoneReport};
+use crate::federation::Fleet;
+use crate::history::{DeploymentRecord, HISTORY_LIMIT};
+use crate::keymap::{Command, Key, Keymap, Lookup, Mode};
+use crate::layout::LayoutConfig;
+use crate::maintenance::ChangeCalendar;
+use crate::notify::{Level, Notifications, MAX_TOASTS};
+use crate::palette::{History, Invocation, Palette};
+use crate::plane::{self, ControlPlane};
+use crate::policy::{Action, ConfirmMode, ConfirmPolicy};
+use crate::rbac::Permissions;
+use crate::refresh::{self, Fetched, Refresh, RefreshJob, Routes};
+use crate::rollout::{format_duration, Batch, BatchStatus, RolloutPolicy, SiteRolloutStatus};
+use crate::theme::{Theme, ThemeSpec};
+use crate::vulndb::{Finding, VulnDatabase};
+use crate::wordpress::WordPressInventory;
 
 /// Site health status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -32,6 +56,18 @@ pub struct Site {
     pub ssl_expires: Option<chrono::DateTime<chrono::Utc>>,
     pub tags: Vec<String>,
     pub environment: String,
+    /// DNS zone serving this site, when it differs from the domain
+    #[serde(default)]
+    pub dns_zone: Option<String>,
+    /// Control plane this site came from in the federated view
+    #[serde(skip)]
+    pub origin: Option<String>,
+}
+
+impl Site {
+    pub fn zone(&self) -> &str {
+        self.dns_zone.as_deref().unwrap_or(&self.domain)
+    }
 }
 
 /// Active view in the TUI
@@ -40,13 +76,218 @@ pub enum View {
     Dashboard,
     SiteList,
     SiteDetail,
+    WordPress,
     Deployments,
+    Composer,
+    History,
+    #[allow(dead_code)] // Secret rotation view not implemented yet
     Secrets,
     Alerts,
+    Vulnerabilities,
     Logs,
     Help,
 }
 
+impl View {
+    /// Tab a view is reached from
+    pub fn tab(self) -> View {
+        match self {
+            View::SiteDetail | View::WordPress | View::Secrets => View::SiteList,
+            View::Composer => View::Deployments,
+            view => view,
+        }
+    }
+
+    /// Where Back goes when there is no history
+    fn parent(self) -> View {
+        match self {
+            View::WordPress => View::SiteDetail,
+            View::SiteDetail | View::Secrets => View::SiteList,
+            View::Composer => View::Deployments,
+            _ => View::Dashboard,
+        }
+    }
+
+    /// Label in tabs and breadcrumbs
+    pub fn title(self) -> &'static str {
+        match self {
+            View::Dashboard => "Dashboard",
+            View::SiteList => "Sites",
+            View::SiteDetail => "Site",
+            View::WordPress => "WordPress",
+            View::Deployments => "Deployments",
+            View::Composer => "New deployment",
+            View::History => "History",
+            View::Secrets => "Secrets",
+            View::Alerts => "Alerts",
+            View::Vulnerabilities => "Vulns",
+            View::Logs => "Logs",
+            View::Help => "Help",
+        }
+    }
+
+    /// Name used by view permissions
+    pub fn name(self) -> &'static str {
+        match self.tab() {
+            View::Dashboard => "dashboard",
+            View::SiteList | View::SiteDetail | View::WordPress | View::Secrets => "sites",
+            View::Deployments | View::Composer => "deployments",
+            View::History => "history",
+            View::Alerts => "alerts",
+            View::Vulnerabilities => "vulns",
+            View::Logs => "logs",
+            View::Help => "help",
+        }
+    }
+}
+
+/// Header tabs, in order
+const TABS: [View; 8] = [
+    View::Dashboard,
+    View::SiteList,
+    View::Deployments,
+    View::History,
+    View::Alerts,
+    View::Logs,
+    View::Vulnerabilities,
+    View::Help,
+];
+
+/// Back/forward history is trimmed to this many entries
+const HISTORY_DEPTH: usize = 50;
+
+/// A view together with its selection and scroll, for back/forward
+///
+/// Selections are kept by ID, since a refresh in between may add or remove rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Location {
+    view: View,
+    site: Option<String>,
+    alert: Option<String>,
+    deployment: Option<String>,
+    history: Option<String>,
+    scroll_offset: usize,
+}
+
+/// How long `s` in the alerts view silences a site
+const SILENCE_SECS: u64 = 3600;
+
+/// Second click on the same cell within this counts as a double click
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Lines one scroll-wheel step moves a popup
+const SCROLL_LINES: u16 = 3;
+
+/// Where the last frame drew clickable things, for mouse hit-testing
+#[derive(Debug, Default)]
+pub struct HitAreas {
+    pub tabs: Vec<(Rect, View)>,
+    /// Rows of the current view's selectable list, one item per line
+    pub rows: Option<Rect>,
+    /// Collapsed tabs, which open the tab menu
+    pub menu: Option<Rect>,
+}
+
+/// A mutating action waiting for the operator to confirm
+#[derive(Debug, Clone)]
+pub enum PendingAction {
+    RollbackDeployment { record_id: String },
+    SyncSite { site_id: String },
+    ApproveDeployment { deployment_id: String },
+    /// Pause, resume, abort or reject
+    TransitionDeployment { deployment_id: String, to: DeploymentStatus },
+    CreateDeployment { request: DeploymentRequest },
+    AcknowledgeAlert { alert_id: String },
+    DismissAlert { alert_id: String },
+    SilenceSite { site_id: String, duration_secs: u64 },
+}
+
+#[derive(Debug, Clone)]
+pub struct Confirmation {
+    pub prompt: String,
+    pub action: PendingAction,
+    pub input: ConfirmInput,
+}
+
+#[derive(Debug, Clone)]
+pub enum ConfirmInput {
+    YesNo,
+    /// The operator must type `expected` exactly
+    Typed { expected: String, entered: String },
+    /// Free-text justification, required when overriding a window or freeze
+    Reason(String),
+}
+
+/// What a pending action touches, for policies and prompts
+struct ActionScope {
+    action: Action,
+    sites: Vec<String>,
+    /// Text to type for typed confirmation
+    target: String,
+    summary: String,
+    environments: Vec<String>,
+}
+
+/// What the app shows from one control plane, fetched at startup
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PlaneData {
+    pub sites: Vec<Site>,
+    pub alerts: Vec<Alert>,
+    pub pending_deployments: Vec<Deployment>,
+    pub groups: Vec<SiteGroup>,
+    pub calendar: ChangeCalendar,
+    pub deployment_history: Vec<DeploymentRecord>,
+    pub permissions: Permissions,
+}
+
+impl PlaneData {
+    /// Everything the control plane will give us; what fails starts out empty
+    /// and is added to `failures` with what it was
+    pub async fn fetch(client: &dyn ControlPlane, failures: &mut Vec<(&'static str, anyhow::Error)>) -> Self {
+        fn or_default<T: Default>(what: &'static str, result: Result<T>, failures: &mut Vec<(&'static str, anyhow::Error)>) -> T {
+            result.unwrap_or_else(|e| {
+                failures.push((what, e));
+                T::default()
+            })
+        }
+        Self {
+            sites: or_default("sites", client.get_sites().await, failures),
+            alerts: or_default("alerts", client.get_alerts().await, failures),
+            pending_deployments: or_default("deployments", client.get_pending_deployments().await, failures),
+            groups: or_default("groups", client.get_groups().await, failures),
+            calendar: or_default("change calendar", client.get_change_calendar().await, failures),
+            deployment_history: or_default("history", client.get_deployment_history(HISTORY_LIMIT).await, failures),
+            permissions: client.get_permissions().await.unwrap_or_else(|e| {
+                tracing::warn!("Could not fetch permissions, disabling actions: {:#}", e);
+                failures.push(("permissions", e));
+                Permissions::unverified()
+            }),
+        }
+    }
+
+    /// Everything again, failing if any of it fails so a refresh keeps what it had
+    pub async fn refresh(client: &dyn ControlPlane, warnings: &mut Vec<String>) -> Result<Self> {
+        let sites = client.get_sites().await?;
+        let permissions = match client.get_permissions().await {
+            Ok(permissions) => permissions,
+            Err(e) => {
+                warnings.push(format!("Could not fetch permissions, actions disabled: {:#}", e));
+                Permissions::unverified()
+            }
+        };
+        Ok(Self {
+            sites,
+            alerts: client.get_alerts().await?,
+            pending_deployments: client.get_pending_deployments().await?,
+            groups: client.get_groups().await?,
+            calendar: client.get_change_calendar().await?,
+            deployment_history: client.get_deployment_history(HISTORY_LIMIT).await?,
+            permissions,
+        })
+    }
+}
+
 /// Application state
 pub struct App {
     pub running: bool,
@@ -54,12 +295,86 @@ pub struct App {
     pub sites: Vec<Site>,
     pub selected_site: usize,
     pub alerts: Vec<Alert>,
+    pub selected_alert: usize,
+    /// Local alerts the operator acknowledged; those alerts are rebuilt on every refresh
+    pub acknowledged_local: HashSet<String>,
+    /// Deployments this session has asked the control plane to roll back
+    rollbacks_requested: HashSet<String>,
+    /// When each vulnerability alert was first raised, so rebuilding it keeps its age
+    vuln_first_seen: HashMap<String, chrono::DateTime<chrono::Utc>>,
+    /// When each unhealthy-rollout alert was first raised
+    rollout_first_seen: HashMap<String, chrono::DateTime<chrono::Utc>>,
+    /// Site ID to end of silence
+    pub silences: HashMap<String, chrono::DateTime<chrono::Utc>>,
     pub pending_deployments: Vec<Deployment>,
-    pub api_client: ApiClient,
-    pub status_message: Option<String>,
+    pub selected_deployment: usize,
+    pub deployment_history: Vec<DeploymentRecord>,
+    pub selected_history: usize,
+    pub confirmation: Option<Confirmation>,
+    pub confirm_policy: ConfirmPolicy,
+    /// Every mutating action is refused
+    pub read_only: bool,
+    pub composer: Option<Composer>,
+    pub groups: Vec<SiteGroup>,
+    pub change_calendar: ChangeCalendar,
+    pub local_calendar: ChangeCalendar,
+    pub plane: Arc<dyn ControlPlane>,
+    /// Control plane currently connected to
+    pub context: Context,
+    pub contexts: Vec<Context>,
+    /// Highlighted row while the context switcher is open; one past the
+    /// last context selects the federated view
+    pub context_picker: Option<usize>,
+    /// Selected entry while the tab menu is open
+    pub tab_menu: Option<usize>,
+    /// Aggregate of several planes; `plane` is unused while set
+    pub fleet: Option<Fleet>,
+    /// `--read-only` or `read_only` in config; overrides per-context defaults
+    pub force_read_only: bool,
+    pub operator: String,
+    pub permissions: Permissions,
+    pub audit: Option<AuditLog>,
+    pub audit_entries: Vec<AuditEntry>,
+    pub audit_verification: Option<Verification>,
+    pub dnssec: Option<DnssecValidator>,
+    pub dnssec_reports: HashMap<String, ZoneReport>,
+    pub wp_inventory: HashMap<String, WordPressInventory>,
+    pub vuln_db: Option<VulnDatabase>,
+    pub vuln_findings: HashMap<String, Vec<Finding>>,
+    /// Fleet-wide "which sites have CVE-X" query
+    pub vuln_query: String,
+    pub editing_vuln_query: bool,
+    /// Reloads the config files when they change
+    pub config_watcher: Option<ConfigWatcher>,
+    pub refresh_interval: Option<Duration>,
+    last_refresh: Instant,
+    /// Periodic refresh running in the background
+    refresh_task: Option<JoinHandle<Refresh>>,
+    pub keymap: Keymap,
+    /// Keys of a chord typed so far
+    pub pending_keys: Vec<Key>,
+    pub palette: Option<Palette>,
+    pub palette_history: History,
+    pub theme: Theme,
+    /// Top-level `theme`, used unless the context sets its own
+    default_theme: Option<String>,
+    user_themes: BTreeMap<String, ThemeSpec>,
+    pub notifications: Notifications,
+    /// Scroll position while the notification center is open
+    pub notification_center: Option<usize>,
     pub show_popup: bool,
     pub popup_content: String,
+    pub popup_scroll: u16,
+    /// Capture the mouse; follows `mouse` in the config
+    pub mouse: bool,
+    pub layout: LayoutConfig,
+    pub hit_areas: RefCell<HitAreas>,
+    last_click: Option<(Instant, Position)>,
     pub scroll_offset: usize,
+    /// Fixed time for rendering; `None` follows the system clock
+    pub clock: Option<chrono::DateTime<chrono::Utc>>,
+    back: Vec<Location>,
+    forward: Vec<Location>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,6 +387,13 @@ pub struct Alert {
     pub acknowledged: bool,
 }
 
+impl Alert {
+    /// Raised by this client's own DNSSEC, vulnerability and rollout checks, not the control plane
+    pub fn is_local(&self) -> bool {
+        ["dnssec-", "vuln-", "rollout-"].iter().any(|prefix| self.id.starts_with(prefix))
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum AlertSeverity {
@@ -87,6 +409,48 @@ pub struct Deployment {
     pub change_type: String,
     pub scheduled: Option<chrono::DateTime<chrono::Utc>>,
     pub status: DeploymentStatus,
+    #[serde(default)]
+    pub created_by: String,
+    /// Approvals the server requires beyond the creator's own
+    #[serde(default)]
+    pub required_approvals: u32,
+    #[serde(default)]
+    pub approvals: Vec<Approval>,
+    #[serde(default)]
+    pub rollout: RolloutPolicy,
+    #[serde(default)]
+    pub batches: Vec<Batch>,
+    /// Control plane this deployment came from in the federated view
+    #[serde(skip)]
+    pub origin: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Approval {
+    pub operator: String,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Deployment {
+    pub fn approvers(&self) -> Vec<&str> {
+        self.approvals.iter().map(|a| a.operator.as_str()).collect()
+    }
+
+    /// Index of the first batch that has not completed
+    pub fn current_batch(&self) -> Option<usize> {
+        self.batches.iter().position(|b| b.status != BatchStatus::Completed)
+    }
+
+    /// (finished, total) site count across all batches
+    pub fn progress(&self) -> (usize, usize) {
+        self.batches.iter().fold((0, 0), |(done, total), b| {
+            (done + b.finished_sites(), total + b.sites.len())
+        })
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(self.status, DeploymentStatus::InProgress | DeploymentStatus::Paused)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -94,222 +458,2249 @@ pub struct Deployment {
 pub enum DeploymentStatus {
     Pending,
     InProgress,
+    Paused,
     Completed,
     Failed,
+    Aborted,
+    Rejected,
     RolledBack,
 }
 
 impl App {
-    pub async fn new(api_url: &str) -> Result<Self> {
-        let api_client = ApiClient::new(api_url)?;
+    pub async fn new(context: Context, config: &Config) -> Result<Self> {
+        let plane = plane::connect(&context, &config.operator())?;
+        let mut failures = Vec::new();
+        let data = PlaneData::fetch(plane.as_ref(), &mut failures).await;
+        let mut app = Self::from_data(context, config, plane, data)?;
+        // Without sites there is nothing to show; don't claim otherwise
+        if !failures.iter().any(|(what, _)| *what == "sites") {
+            app.announce_connection();
+        }
+        if !failures.is_empty() {
+            let message = format!("Could not fetch from {}: {}", app.context.name, describe_failures(&failures));
+            app.notifications.push(Level::Error, message);
+        }
+        app.enforce_rollbacks().await;
+        app.check_dnssec().await;
+        app.scan_vulnerabilities().await;
+        Ok(app)
+    }
 
-        // Fetch initial data
-        let sites = api_client.get_sites().await.unwrap_or_default();
-        let alerts = api_client.get_alerts().await.unwrap_or_default();
-        let pending_deployments = api_client.get_pending_deployments().await.unwrap_or_default();
+    /// Build the app around data already fetched (or fixtures), without touching the network
+    pub fn from_data(context: Context, config: &Config, plane: Arc<dyn ControlPlane>, data: PlaneData) -> Result<Self> {
+        let PlaneData { sites, alerts, mut pending_deployments, groups, calendar, deployment_history, permissions } = data;
+        plan_missing_batches(&mut pending_deployments);
+        let mut change_calendar = config.maintenance.clone();
+        change_calendar.merge(calendar);
+        let audit = config.audit_log.clone()
+            .or_else(AuditLog::default_path)
+            .map(|path| AuditLog::open(&path))
+            .transpose()?;
+        let vuln_db = config.vuln_db.as_deref().map(VulnDatabase::load).transpose()?;
 
-        Ok(Self {
+        let mut app = Self {
             running: true,
             view: View::Dashboard,
             sites,
             selected_site: 0,
             alerts,
+            selected_alert: 0,
+            acknowledged_local: HashSet::new(),
+            vuln_first_seen: HashMap::new(),
+            rollout_first_seen: HashMap::new(),
+            rollbacks_requested: HashSet::new(),
+            silences: HashMap::new(),
             pending_deployments,
-            api_client,
-            status_message: Some("Connected to control plane".to_string()),
+            selected_deployment: 0,
+            deployment_history,
+            selected_history: 0,
+            confirmation: None,
+            confirm_policy: config.confirm.clone(),
+            read_only: config.read_only || context.read_only || plane.read_only(),
+            composer: None,
+            groups,
+            change_calendar,
+            local_calendar: config.maintenance.clone(),
+            plane,
+            contexts: config.all_contexts(&context),
+            context,
+            context_picker: None,
+            tab_menu: None,
+            fleet: None,
+            force_read_only: config.read_only,
+            operator: config.operator(),
+            permissions,
+            audit,
+            audit_entries: Vec::new(),
+            audit_verification: None,
+            dnssec: DnssecValidator::from_config(&config.dnssec),
+            dnssec_reports: HashMap::new(),
+            wp_inventory: HashMap::new(),
+            vuln_db,
+            vuln_findings: HashMap::new(),
+            vuln_query: String::new(),
+            editing_vuln_query: false,
+            config_watcher: None,
+            refresh_interval: config.refresh_interval_secs.map(Duration::from_secs),
+            last_refresh: Instant::now(),
+            refresh_task: None,
+            keymap: Keymap::new(&config.keys).unwrap_or_default(),
+            pending_keys: Vec::new(),
+            palette: None,
+            palette_history: History::load(History::default_path()),
+            theme: Theme::default(),
+            default_theme: config.theme.clone(),
+            user_themes: config.themes.clone(),
+            notifications: Notifications::default(),
+            notification_center: None,
             show_popup: false,
             popup_content: String::new(),
+            popup_scroll: 0,
+            mouse: config.mouse.unwrap_or(true),
+            layout: config.layout.clone(),
+            hit_areas: RefCell::new(HitAreas::default()),
+            last_click: None,
             scroll_offset: 0,
-        })
+            clock: None,
+            back: Vec::new(),
+            forward: Vec::new(),
+        };
+        app.select_theme();
+        app.verify_audit_log()?;
+        app.flag_unhealthy_rollouts();
+        Ok(app)
+    }
+
+    /// Tell the operator which plane they are on, once its data is in
+    pub fn announce_connection(&mut self) {
+        self.notifications.push(Level::Info, if self.read_only {
+            format!("Connected to {} (read-only)", self.context.name)
+        } else {
+            format!("Connected to {}", self.context.name)
+        });
     }
 
     /// Handle a key event, returns true if app should exit
     pub async fn handle_key(&mut self, key: KeyEvent) -> Result<bool> {
-        // Global shortcuts
-        match key.code {
-            KeyCode::Char('q') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                return Ok(true);
-            }
-            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                return Ok(true);
-            }
-            KeyCode::Esc => {
-                if self.show_popup {
-                    self.show_popup = false;
-                } else if self.view != View::Dashboard {
-                    self.view = View::Dashboard;
-                } else {
-                    return Ok(true);
-                }
-            }
-            _ => {}
+        // Text entry swallows everything, including Esc
+        if self.editing_vuln_query {
+            self.handle_vuln_query_input(key);
+            return Ok(false);
         }
 
-        // View-specific handling
-        match self.view {
-            View::Dashboard => self.handle_dashboard_key(key).await?,
-            View::SiteList => self.handle_site_list_key(key).await?,
-            View::SiteDetail => self.handle_site_detail_key(key).await?,
-            View::Deployments => self.handle_deployments_key(key).await?,
-            View::Alerts => self.handle_alerts_key(key).await?,
-            View::Help => self.handle_help_key(key)?,
-            _ => {}
+        if self.confirmation.is_some() {
+            self.handle_confirmation_key(key).await?;
+            return Ok(false);
+        }
+        if self.context_picker.is_some() {
+            self.handle_context_picker_key(key).await?;
+            return Ok(false);
+        }
+        if self.tab_menu.is_some() {
+            self.handle_tab_menu_key(key);
+            return Ok(false);
+        }
+        if self.notification_center.is_some() {
+            self.handle_notification_center_key(key);
+            return Ok(false);
+        }
+        if self.palette.is_some() {
+            self.handle_palette_key(key).await?;
+            return Ok(!self.running);
+        }
+        if self.view == View::Composer && !self.show_popup {
+            self.handle_composer_key(key).await?;
+            return Ok(false);
         }
 
-        Ok(false)
+        // Esc abandons a half-typed chord
+        if key.code == KeyCode::Esc && !self.pending_keys.is_empty() {
+            self.pending_keys.clear();
+            return Ok(false);
+        }
+        let mode = Mode::of(self.view);
+        let mut keys = std::mem::take(&mut self.pending_keys);
+        keys.push(Key::from_event(key));
+        let mut lookup = self.keymap.lookup(mode, &keys);
+        if matches!(lookup, Lookup::Unbound) && keys.len() > 1 {
+            // The chord went nowhere; start over from the last key
+            keys.drain(..keys.len() - 1);
+            lookup = self.keymap.lookup(mode, &keys);
+        }
+        match lookup {
+            Lookup::Command(command) => self.run_command(command).await?,
+            Lookup::Pending => self.pending_keys = keys,
+            Lookup::Unbound => {}
+        }
+        Ok(!self.running)
     }
 
-    async fn handle_dashboard_key(&mut self, key: KeyEvent) -> Result<()> {
-        match key.code {
-            KeyCode::Char('s') | KeyCode::Char('1') => self.view = View::SiteList,
-            KeyCode::Char('d') | KeyCode::Char('2') => self.view = View::Deployments,
-            KeyCode::Char('a') | KeyCode::Char('3') => self.view = View::Alerts,
-            KeyCode::Char('l') | KeyCode::Char('4') => self.view = View::Logs,
-            KeyCode::Char('?') | KeyCode::F(1) => self.view = View::Help,
-            KeyCode::Char('r') => {
-                self.status_message = Some("Refreshing...".to_string());
-                self.refresh_data().await?;
-                self.status_message = Some("Data refreshed".to_string());
+    async fn run_command(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Quit => self.running = false,
+            Command::Close => {
+                if self.show_popup {
+                    self.show_popup = false;
+                } else if self.view != View::Dashboard {
+                    self.go_back();
+                } else {
+                    self.running = false;
+                }
             }
-            _ => {}
+            Command::Back => self.go_back(),
+            Command::Forward => self.go_forward(),
+            Command::NextTab => self.cycle_tab(1),
+            Command::PrevTab => self.cycle_tab(-1),
+            Command::TabMenu => self.open_tab_menu(),
+            Command::Notifications => {
+                self.notification_center = Some(0);
+                self.notifications.mark_seen();
+                self.notifications.dismiss_toasts();
+            }
+            Command::ContextPicker => {
+                self.context_picker = self.contexts.iter().position(|c| c.name == self.context.name).or(Some(0));
+            }
+            Command::Palette => self.palette = Some(Palette::default()),
+            Command::Help => self.navigate(View::Help),
+            Command::GotoSites => self.goto(View::SiteList),
+            Command::GotoDeployments => self.goto(View::Deployments),
+            Command::GotoHistory => self.goto(View::History),
+            Command::GotoAlerts => self.goto(View::Alerts),
+            Command::GotoLogs => self.goto(View::Logs),
+            Command::GotoVulns => self.goto(View::Vulnerabilities),
+            command => match self.view {
+                View::Dashboard => self.handle_dashboard_command(command).await?,
+                View::SiteList => self.handle_site_list_command(command).await?,
+                View::SiteDetail => self.handle_site_detail_command(command).await?,
+                View::WordPress => self.handle_wordpress_command(command).await?,
+                View::Deployments => self.handle_deployments_command(command).await?,
+                View::History => self.handle_history_command(command).await?,
+                View::Alerts => self.handle_alerts_command(command).await?,
+                View::Logs => self.handle_logs_command(command)?,
+                View::Vulnerabilities => self.handle_vulnerabilities_command(command).await?,
+                _ => {}
+            },
         }
         Ok(())
     }
 
-    async fn handle_site_list_key(&mut self, key: KeyEvent) -> Result<()> {
+    async fn handle_context_picker_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(selected) = self.context_picker else {
+            return Ok(());
+        };
         match key.code {
             KeyCode::Up | KeyCode::Char('k') => {
-                if self.selected_site > 0 {
-                    self.selected_site -= 1;
-                }
+                self.context_picker = Some(selected.saturating_sub(1));
             }
             KeyCode::Down | KeyCode::Char('j') => {
-                if self.selected_site < self.sites.len().saturating_sub(1) {
-                    self.selected_site += 1;
-                }
+                let last = if self.contexts.len() > 1 { self.contexts.len() } else { 0 };
+                self.context_picker = Some((selected + 1).min(last));
             }
             KeyCode::Enter => {
-                self.view = View::SiteDetail;
-            }
-            KeyCode::Char('s') => {
-                // Sync selected site
-                if let Some(site) = self.sites.get(self.selected_site) {
-                    self.status_message = Some(format!("Syncing {}...", site.domain));
-                    self.api_client.sync_site(&site.id).await?;
-                    self.status_message = Some(format!("Sync initiated for {}", site.domain));
+                self.context_picker = None;
+                match self.contexts.get(selected).cloned() {
+                    Some(context) if context.name != self.context.name || self.fleet.is_some() => {
+                        self.switch_context(context).await;
+                    }
+                    Some(_) => {}
+                    None => {
+                        if let Err(e) = self.federate(&[]).await {
+                            self.notifications.push(Level::Error, format!("Could not federate: {:#}", e));
+                        }
+                    }
                 }
             }
-            KeyCode::Char('/') => {
-                // TODO: Search/filter
-            }
+            KeyCode::Esc | KeyCode::Char('q') => self.context_picker = None,
             _ => {}
         }
         Ok(())
     }
 
-    async fn handle_site_detail_key(&mut self, key: KeyEvent) -> Result<()> {
+    fn open_tab_menu(&mut self) {
+        self.tab_menu = Some(self.tabs().iter().position(|view| *view == self.view.tab()).unwrap_or(0));
+    }
+
+    fn handle_tab_menu_key(&mut self, key: KeyEvent) {
+        let Some(selected) = self.tab_menu else {
+            return;
+        };
         match key.code {
-            KeyCode::Backspace | KeyCode::Char('b') => {
-                self.view = View::SiteList;
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.tab_menu = Some(selected.saturating_sub(1));
             }
-            KeyCode::Char('s') => {
-                // Sync this site
-                if let Some(site) = self.sites.get(self.selected_site) {
-                    self.status_message = Some(format!("Syncing {}...", site.domain));
-                    self.api_client.sync_site(&site.id).await?;
-                }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.tab_menu = Some((selected + 1).min(self.tabs().len().saturating_sub(1)));
             }
-            KeyCode::Char('c') => {
-                // Show config diff
-                if let Some(site) = self.sites.get(self.selected_site) {
-                    let diff = self.api_client.get_config_diff(&site.id).await?;
-                    self.popup_content = diff;
-                    self.show_popup = true;
+            KeyCode::Enter => {
+                self.tab_menu = None;
+                if let Some(view) = self.tabs().get(selected) {
+                    self.goto(*view);
                 }
             }
+            KeyCode::Esc | KeyCode::Char('q') => self.tab_menu = None,
+            _ => {}
+        }
+    }
+
+    fn handle_notification_center_key(&mut self, key: KeyEvent) {
+        let Some(offset) = self.notification_center else {
+            return;
+        };
+        match key.code {
             KeyCode::Up | KeyCode::Char('k') => {
-                self.scroll_offset = self.scroll_offset.saturating_sub(1);
+                self.notification_center = Some(offset.saturating_sub(1));
             }
             KeyCode::Down | KeyCode::Char('j') => {
-                self.scroll_offset += 1;
+                self.notification_center = Some((offset + 1).min(self.notifications.len().saturating_sub(1)));
+            }
+            KeyCode::Char('c') => {
+                self.notifications.clear();
+                self.notification_center = Some(0);
             }
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('N') => self.notification_center = None,
             _ => {}
         }
-        Ok(())
     }
 
-    async fn handle_deployments_key(&mut self, key: KeyEvent) -> Result<()> {
-        match key.code {
-            KeyCode::Char('a') => {
-                // Approve selected deployment
+    /// Reconnect to another control plane, staying on the current one if it is unreachable
+    async fn switch_context(&mut self, context: Context) {
+        let client = match plane::connect(&context, &self.operator) {
+            Ok(client) => client,
+            Err(e) => {
+                self.notifications.push(Level::Error, format!("Cannot use context {}: {:#}", context.name, e));
+                return;
             }
-            KeyCode::Char('r') => {
-                // Reject/cancel selected deployment
+        };
+        let previous_client = std::mem::replace(&mut self.plane, client);
+        let previous_context = std::mem::replace(&mut self.context, context);
+        let previous_read_only = self.read_only;
+        self.read_only = self.force_read_only || self.context.read_only || self.plane.read_only();
+        self.fleet = None;
+        self.reset_plane_state();
+
+        match self.refresh_data().await {
+            Ok(()) => {
+                self.notifications.push(Level::Success, format!("Switched to {} ({})", self.context.name, self.context.api_url));
+            }
+            Err(e) => {
+                let failed = std::mem::replace(&mut self.context, previous_context);
+                self.plane = previous_client;
+                self.read_only = previous_read_only;
+                self.notifications.push(Level::Error, format!("Could not switch to {}: {:#}", failed.name, e));
+                if let Err(e) = self.refresh_data().await {
+                    self.notifications.push(Level::Error, format!("Reloading {} failed: {:#}", self.context.name, e));
+                }
             }
-            _ => {}
         }
-        Ok(())
+        self.select_theme();
+    }
+
+    /// The context's theme, or the default one; the federated view always uses the default
+    fn select_theme(&mut self) {
+        let name = match &self.fleet {
+            Some(_) => self.default_theme.as_deref(),
+            None => self.context.theme.as_deref().or(self.default_theme.as_deref()),
+        };
+        self.theme = name
+            .and_then(|name| Theme::load(name, &self.user_themes).ok())
+            .unwrap_or_default();
     }
 
-    async fn handle_alerts_key(&mut self, key: KeyEvent) -> Result<()> {
+    async fn handle_palette_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(mut palette) = self.palette.take() else {
+            return Ok(());
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
         match key.code {
-            KeyCode::Char('a') => {
-                // Acknowledge selected alert
+            KeyCode::Esc => return Ok(()),
+            KeyCode::Enter => {
+                if palette.input.trim().is_empty() {
+                    palette.complete(self);
+                }
+                match palette.parse(self) {
+                    Ok(invocation) => {
+                        self.palette_history.record(palette.input.trim());
+                        return self.invoke(invocation).await;
+                    }
+                    Err(e) => palette.error = Some(format!("{:#}", e)),
+                }
             }
-            KeyCode::Char('d') => {
-                // Dismiss selected alert
+            KeyCode::Tab => palette.complete(self),
+            KeyCode::Up => palette.selected = palette.selected.saturating_sub(1),
+            KeyCode::Char('p') if ctrl => palette.selected = palette.selected.saturating_sub(1),
+            KeyCode::Down => palette.selected += 1,
+            KeyCode::Char('n') if ctrl => palette.selected += 1,
+            KeyCode::Backspace => {
+                palette.input.pop();
+                palette.selected = 0;
+                palette.error = None;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                palette.input.push(c);
+                palette.selected = 0;
+                palette.error = None;
             }
             _ => {}
         }
+        palette.selected = palette.selected.min(palette.suggestions(self).len().saturating_sub(1));
+        self.palette = Some(palette);
         Ok(())
     }
 
-    fn handle_help_key(&mut self, key: KeyEvent) -> Result<()> {
-        match key.code {
-            KeyCode::Char('q') | KeyCode::Esc => {
-                self.view = View::Dashboard;
+    /// Run a palette command through the same paths as its key bindings
+    async fn invoke(&mut self, invocation: Invocation) -> Result<()> {
+        let select_site = |app: &mut Self, id: &str| {
+            if let Some(i) = app.sites.iter().position(|s| s.id == id) {
+                app.selected_site = i;
             }
-            _ => {}
+        };
+        let select_deployment = |app: &mut Self, id: &str| {
+            if let Some(i) = app.pending_deployments.iter().position(|d| d.id == id) {
+                app.selected_deployment = i;
+            }
+        };
+        match invocation {
+            Invocation::Goto(view) => self.goto(view),
+            Invocation::Sync { site_id } => {
+                select_site(self, &site_id);
+                self.request_sync().await;
+            }
+            Invocation::Diff { site_id } => {
+                select_site(self, &site_id);
+                self.handle_site_detail_command(Command::Diff).await?;
+            }
+            Invocation::Silence { site_id, duration_secs } => {
+                self.confirm(PendingAction::SilenceSite { site_id, duration_secs }).await;
+            }
+            Invocation::Acknowledge { alert_id } => self.confirm(PendingAction::AcknowledgeAlert { alert_id }).await,
+            Invocation::Dismiss { alert_id } => self.confirm(PendingAction::DismissAlert { alert_id }).await,
+            Invocation::Approve { deployment_id } => {
+                select_deployment(self, &deployment_id);
+                self.request_approval().await;
+            }
+            Invocation::Reject { deployment_id } => {
+                select_deployment(self, &deployment_id);
+                self.handle_deployments_command(Command::Reject).await?;
+            }
+            Invocation::Pause { deployment_id } => {
+                select_deployment(self, &deployment_id);
+                self.handle_deployments_command(Command::Pause).await?;
+            }
+            Invocation::Abort { deployment_id } => {
+                select_deployment(self, &deployment_id);
+                self.handle_deployments_command(Command::Abort).await?;
+            }
+            Invocation::Context(name) => {
+                if let Some(context) = self.contexts.iter().find(|c| c.name == name).cloned() {
+                    if context.name != self.context.name || self.fleet.is_some() {
+                        self.switch_context(context).await;
+                    }
+                }
+            }
+            Invocation::Refresh => self.handle_dashboard_command(Command::Refresh).await?,
+            Invocation::Help => self.navigate(View::Help),
+            Invocation::Quit => self.running = false,
         }
         Ok(())
     }
 
-    pub fn handle_mouse(&mut self, _mouse: MouseEvent) -> Result<()> {
-        // TODO: Mouse support
+    /// Aggregate the named contexts (all configured ones when empty) into one read-only view
+    pub async fn federate(&mut self, names: &[String]) -> Result<()> {
+        let contexts: Vec<Context> = if names.is_empty() {
+            self.contexts.clone()
+        } else {
+            names.iter()
+                .map(|name| {
+                    self.contexts.iter()
+                        .find(|c| &c.name == name)
+                        .cloned()
+                        .ok_or_else(|| anyhow::anyhow!("unknown context '{}'", name))
+                })
+                .collect::<Result<_>>()?
+        };
+        self.fleet = Some(Fleet::new(contexts, &self.operator)?);
+        // Actions must go to one plane; switch context to act
+        self.read_only = true;
+        self.reset_plane_state();
+        self.select_theme();
+        self.refresh_data().await?;
+
+        if let Some(fleet) = &self.fleet {
+            let down = fleet.planes.iter().filter(|p| p.error.is_some()).count();
+            self.notifications.push(Level::Info, format!(
+                "Federated view of {} planes ({} unavailable); press C to pick one to act on",
+                fleet.planes.len(),
+                down
+            ));
+        }
         Ok(())
     }
 
-    pub fn handle_resize(&mut self, _width: u16, _height: u16) -> Result<()> {
-        // Terminal resize handled by ratatui automatically
-        Ok(())
+    /// Forget everything keyed by site or deployment when the plane changes
+    fn reset_plane_state(&mut self) {
+        if let Some(task) = self.refresh_task.take() {
+            task.abort();
+        }
+        self.view = View::Dashboard;
+        self.back.clear();
+        self.forward.clear();
+        self.composer = None;
+        self.selected_site = 0;
+        self.selected_alert = 0;
+        self.selected_deployment = 0;
+        self.selected_history = 0;
+        self.silences.clear();
+        self.acknowledged_local.clear();
+        self.wp_inventory.clear();
+        self.vuln_findings.clear();
+        self.dnssec_reports.clear();
+        self.rollbacks_requested.clear();
+        self.rollout_first_seen.clear();
     }
 
-    pub async fn tick(&mut self) -> Result<()> {
-        // Periodic data refresh (every 30 ticks = ~7.5 seconds at 250ms interval)
-        // TODO: Implement efficient polling or WebSocket updates
+    async fn handle_dashboard_command(&mut self, command: Command) -> Result<()> {
+        if command == Command::Refresh {
+            match self.refresh_data().await {
+                Ok(()) => self.notifications.push(Level::Success, "Data refreshed".to_string()),
+                Err(e) => self.notifications.push(Level::Error, format!("Refresh failed: {:#}", e)),
+            }
+        }
         Ok(())
     }
 
-    async fn refresh_data(&mut self) -> Result<()> {
-        self.sites = self.api_client.get_sites().await?;
-        self.alerts = self.api_client.get_alerts().await?;
-        self.pending_deployments = self.api_client.get_pending_deployments().await?;
+    /// Switch views unless the operator's role hides the target
+    pub fn goto(&mut self, view: View) {
+        if self.can_view(view) {
+            self.navigate(view);
+        } else {
+            let role = self.permissions.role.as_deref().unwrap_or("your role");
+            self.notifications.push(Level::Warning, format!("Permission denied: {} may not open {}", role, view.name()));
+        }
+    }
+
+    /// Open `view`, remembering where we were for Back
+    fn navigate(&mut self, view: View) {
+        if view == self.view {
+            return;
+        }
+        self.back.push(self.location());
+        if self.back.len() > HISTORY_DEPTH {
+            self.back.remove(0);
+        }
+        self.forward.clear();
+        self.view = view;
+        self.scroll_offset = 0;
+    }
+
+    /// Whether history may lead back to `view`; a closed composer has nothing to show
+    fn can_return_to(&self, view: View) -> bool {
+        self.can_view(view) && (view != View::Composer || self.composer.is_some())
+    }
+
+    /// Return to the previous view, or the current one's parent when there is no history
+    fn go_back(&mut self) {
+        while let Some(location) = self.back.pop() {
+            if self.can_return_to(location.view) {
+                self.forward.push(self.location());
+                self.restore(location);
+                return;
+            }
+        }
+        let parent = self.view.parent();
+        if parent != self.view {
+            self.forward.clear();
+            self.view = parent;
+        }
+    }
+
+    fn go_forward(&mut self) {
+        while let Some(location) = self.forward.pop() {
+            if self.can_return_to(location.view) {
+                self.back.push(self.location());
+                self.restore(location);
+                return;
+            }
+        }
+    }
+
+    fn location(&self) -> Location {
+        Location {
+            view: self.view,
+            site: self.sites.get(self.selected_site).map(|s| s.id.clone()),
+            alert: self.alerts.get(self.selected_alert).map(|a| a.id.clone()),
+            deployment: self.pending_deployments.get(self.selected_deployment).map(|d| d.id.clone()),
+            history: self.deployment_history.get(self.selected_history).map(|r| r.id.clone()),
+            scroll_offset: self.scroll_offset,
+        }
+    }
+
+    /// Go to `location`, reselecting its rows wherever a refresh has since moved them
+    fn restore(&mut self, location: Location) {
+        self.view = location.view;
+        self.scroll_offset = location.scroll_offset;
+        self.reselect_rows(location);
+    }
+
+    /// Select the rows `location` had selected, after the lists were replaced
+    fn reselect_rows(&mut self, location: Location) {
+        self.selected_site = reselect(&self.sites, |s| &s.id, location.site, self.selected_site);
+        self.selected_alert = reselect(&self.alerts, |a| &a.id, location.alert, self.selected_alert);
+        self.selected_deployment =
+            reselect(&self.pending_deployments, |d| &d.id, location.deployment, self.selected_deployment);
+        self.selected_history =
+            reselect(&self.deployment_history, |r| &r.id, location.history, self.selected_history);
+    }
+
+    /// Move `step` tabs along, wrapping, skipping tabs the role hides
+    fn cycle_tab(&mut self, step: isize) {
+        let tabs = self.tabs();
+        let current = tabs.iter().position(|view| *view == self.view.tab()).unwrap_or(0);
+        let next = (current as isize + step).rem_euclid(tabs.len() as isize) as usize;
+        self.navigate(tabs[next]);
+    }
+
+    /// Header tabs the operator may open
+    pub fn tabs(&self) -> Vec<View> {
+        TABS.into_iter().filter(|view| self.can_view(*view)).collect()
+    }
+
+    /// Path to the current view, for the header
+    pub fn breadcrumbs(&self) -> Vec<String> {
+        let site = || self.sites.get(self.selected_site).map(|s| s.domain.clone()).unwrap_or_default();
+        match self.view {
+            View::SiteDetail => vec![View::SiteList.title().to_string(), site()],
+            View::WordPress | View::Secrets => vec![View::SiteList.title().to_string(), site(), self.view.title().to_string()],
+            View::Composer => vec![View::Deployments.title().to_string(), self.view.title().to_string()],
+            view => vec![view.title().to_string()],
+        }
+    }
+
+    pub fn can_view(&self, view: View) -> bool {
+        matches!(view, View::Dashboard | View::Help) || self.permissions.can_view(view.name())
+    }
+
+    /// Whether `action` is granted on all of `site_ids`
+    pub fn can(&self, action: Action, site_ids: &[String]) -> bool {
+        let sites: Vec<&Site> = self.sites.iter().filter(|s| site_ids.contains(&s.id)).collect();
+        self.permissions.allows(action, &sites)
+    }
+
+    async fn handle_site_list_command(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Up => {
+                self.selected_site = self.selected_site.saturating_sub(1);
+            }
+            Command::Down => {
+                self.selected_site = (self.selected_site + 1).min(self.sites.len().saturating_sub(1));
+            }
+            Command::Open => {
+                self.navigate(View::SiteDetail);
+            }
+            Command::Sync => {
+                // Sync selected site
+                self.request_sync().await;
+            }
+            _ => {}
+        }
         Ok(())
     }
 
-    pub fn healthy_sites(&self) -> usize {
-        self.sites.iter().filter(|s| s.status == SiteStatus::Healthy).count()
+    async fn handle_site_detail_command(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Sync => {
+                // Sync this site
+                self.request_sync().await;
+            }
+            Command::Diff => {
+                // Show config diff
+                if let Some(site) = self.sites.get(self.selected_site) {
+                    let (client, site_id) = client_for(&self.fleet, self.plane.as_ref(), &site.id);
+                    match client.get_config_diff(site_id).await {
+                        Ok(diff) => self.open_popup(diff),
+                        Err(e) => self.notifications.push(Level::Error, format!("Diff for {} failed: {:#}", site.domain, e)),
+                    }
+                }
+            }
+            Command::WordPress => {
+                // WordPress inventory
+                self.load_wordpress_inventory().await;
+                self.navigate(View::WordPress);
+            }
+            Command::Dnssec => {
+                // Re-validate DNSSEC for this site's zone
+                if let (Some(site), Some(validator)) = (self.sites.get(self.selected_site), &self.dnssec) {
+                    let report = validator.check_zone(site.zone()).await;
+                    self.notifications.push(Level::Info, format!("DNSSEC {}: {}", report.zone, report.summary()));
+                    self.dnssec_reports.insert(report.zone.clone(), report);
+                    self.rebuild_dnssec_alerts();
+                } else {
+                    self.notifications.push(Level::Warning, "DNSSEC checks disabled (no [dnssec] resolver configured)".to_string());
+                }
+            }
+            Command::Up => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(1);
+            }
+            Command::Down => {
+                self.scroll_offset += 1;
+            }
+            _ => {}
+        }
+        Ok(())
     }
 
-    pub fn warning_sites(&self) -> usize {
-        self.sites.iter().filter(|s| s.status == SiteStatus::Warning).count()
+    async fn handle_wordpress_command(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Refresh => {
+                self.load_wordpress_inventory().await;
+            }
+            Command::Up => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(1);
+            }
+            Command::Down => {
+                self.scroll_offset += 1;
+            }
+            _ => {}
+        }
+        Ok(())
     }
 
-    pub fn critical_sites(&self) -> usize {
-        self.sites.iter().filter(|s| s.status == SiteStatus::Critical).count()
+    async fn load_wordpress_inventory(&mut self) {
+        let Some(site) = self.sites.get(self.selected_site) else {
+            return;
+        };
+        let (client, site_id) = client_for(&self.fleet, self.plane.as_ref(), &site.id);
+        let inventory = match client.get_wordpress_inventory(site_id).await {
+            Ok(inventory) => inventory,
+            Err(e) => {
+                self.notifications.push(Level::Error, format!("WordPress inventory for {} failed: {:#}", site.domain, e));
+                return;
+            }
+        };
+        match inventory {
+            Some(inventory) => {
+                let drift = inventory.divergences().len();
+                self.notifications.push(Level::Info, format!(
+                    "{}: {} updates available, {} plugin divergences",
+                    site.domain,
+                    inventory.updates_available(),
+                    drift
+                ));
+                self.wp_inventory.insert(site.id.clone(), inventory);
+            }
+            None => {
+                self.notifications.push(Level::Warning, format!("{} is not a WordPress site", site.domain));
+                self.wp_inventory.remove(&site.id);
+            }
+        }
     }
 
-    pub fn unacknowledged_alerts(&self) -> usize {
-        self.alerts.iter().filter(|a| !a.acknowledged).count()
+    async fn handle_deployments_command(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Up => {
+                self.selected_deployment = self.selected_deployment.saturating_sub(1);
+            }
+            Command::Down => {
+                self.selected_deployment =
+                    (self.selected_deployment + 1).min(self.pending_deployments.len().saturating_sub(1));
+            }
+            Command::NewDeployment => {
+                if self.read_only {
+                    self.notifications.push(Level::Warning, "Read-only mode: create-deployment disabled".to_string());
+                } else if !self.permissions.allows_somewhere(Action::CreateDeployment) {
+                    let role = self.permissions.role.as_deref().unwrap_or("your role");
+                    self.notifications.push(Level::Warning, format!("Permission denied: {} may not create-deployment", role));
+                } else {
+                    self.composer = Some(Composer::new(self.sites.get(self.selected_site)));
+                    self.navigate(View::Composer);
+                }
+            }
+            Command::Pause => {
+                // Pause or resume the selected rollout
+                if let Some(deployment) = self.pending_deployments.get(self.selected_deployment) {
+                    match deployment.status {
+                        DeploymentStatus::InProgress => self.transition_deployment(DeploymentStatus::Paused).await,
+                        DeploymentStatus::Paused => self.transition_deployment(DeploymentStatus::InProgress).await,
+                        status => {
+                            self.notifications.push(Level::Warning, format!("Cannot pause a {:?} deployment", status));
+                        }
+                    }
+                }
+            }
+            Command::Abort => {
+                // Abort the selected rollout; completed batches stay applied
+                if let Some(deployment) = self.pending_deployments.get(self.selected_deployment) {
+                    if deployment.is_active() || deployment.status == DeploymentStatus::Pending {
+                        self.transition_deployment(DeploymentStatus::Aborted).await;
+                    }
+                }
+            }
+            Command::Approve => {
+                // Approve selected deployment
+                self.request_approval().await;
+            }
+            Command::Reject => {
+                // Reject/cancel selected deployment
+                if let Some(deployment) = self.pending_deployments.get(self.selected_deployment) {
+                    if deployment.status == DeploymentStatus::Pending {
+                        self.transition_deployment(DeploymentStatus::Rejected).await;
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Pause, resume, abort or reject the selected deployment
+    async fn transition_deployment(&mut self, to: DeploymentStatus) {
+        let Some(id) = self.pending_deployments.get(self.selected_deployment).map(|d| d.id.clone()) else {
+            return;
+        };
+        self.confirm(PendingAction::TransitionDeployment { deployment_id: id, to }).await
+    }
+
+    async fn handle_composer_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(composer) = self.composer.as_mut() else {
+            self.go_back();
+            return Ok(());
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Esc => self.close_composer(),
+            KeyCode::Tab => composer.focus = composer.focus.next(),
+            KeyCode::BackTab => composer.focus = composer.focus.prev(),
+            KeyCode::Char('d') if ctrl => self.preview_composed_diff().await?,
+            KeyCode::Char('s') if ctrl => self.submit_composer().await,
+            KeyCode::Left if composer.focus == ComposerField::Targets => {
+                composer.target_mode = composer.target_mode.prev();
+            }
+            KeyCode::Right if composer.focus == ComposerField::Targets => {
+                composer.target_mode = composer.target_mode.next();
+            }
+            KeyCode::Left | KeyCode::Right if composer.focus == ComposerField::ChangeSet => {
+                composer.change_mode = composer.change_mode.toggle();
+            }
+            KeyCode::Left if composer.focus == ComposerField::Rollout => {
+                composer.strategy = composer.strategy.prev();
+            }
+            KeyCode::Right if composer.focus == ComposerField::Rollout => {
+                composer.strategy = composer.strategy.next();
+            }
+            KeyCode::Up if composer.target_mode == TargetMode::Selection => {
+                composer.cursor = composer.cursor.saturating_sub(1);
+            }
+            KeyCode::Down if composer.target_mode == TargetMode::Selection => {
+                composer.cursor = (composer.cursor + 1).min(self.sites.len().saturating_sub(1));
+            }
+            KeyCode::Char(' ')
+                if composer.focus == ComposerField::Targets && composer.target_mode == TargetMode::Selection =>
+            {
+                if let Some(site) = self.sites.get(composer.cursor) {
+                    if !composer.selected_sites.remove(&site.id) {
+                        composer.selected_sites.insert(site.id.clone());
+                    }
+                }
+            }
+            KeyCode::Backspace => {
+                if let Some(text) = composer.focused_text() {
+                    text.pop();
+                }
+            }
+            KeyCode::Char(c) if !ctrl => {
+                if let Some(text) = composer.focused_text() {
+                    text.push(c);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Diff the composed deployment would apply, as rendered by the control plane
+    async fn preview_composed_diff(&mut self) -> Result<()> {
+        let Some(composer) = self.composer.as_mut() else {
+            return Ok(());
+        };
+        let request = match composer.build_request(&self.sites, &self.groups) {
+            Ok(request) => request,
+            Err(error) => {
+                composer.errors = vec![error];
+                return Ok(());
+            }
+        };
+        if request.sites.is_empty() {
+            self.notifications.push(Level::Warning, "No sites match the selected targets".to_string());
+            return Ok(());
+        }
+
+        match self.plane.preview_deployment(&request).await {
+            Ok(diff) => {
+                composer.errors.clear();
+                self.open_popup(diff);
+            }
+            Err(e) => match e.downcast::<ApiError>() {
+                Ok(ApiError::Validation(errors)) => composer.errors = errors,
+                Ok(e) => self.notifications.push(Level::Error, format!("Preview failed: {}", e)),
+                Err(e) => self.notifications.push(Level::Error, format!("Preview failed: {:#}", e)),
+            },
+        }
+        Ok(())
+    }
+
+    /// Drop the draft and go back to where the composer was opened from
+    fn close_composer(&mut self) {
+        self.composer = None;
+        self.go_back();
+    }
+
+    async fn submit_composer(&mut self) {
+        let Some(composer) = self.composer.as_mut() else {
+            return;
+        };
+        match composer.build_request(&self.sites, &self.groups) {
+            Ok(request) => self.confirm(PendingAction::CreateDeployment { request }).await,
+            Err(error) => composer.errors = vec![error],
+        }
+    }
+
+    async fn create_deployment(&mut self, request: DeploymentRequest) -> Result<()> {
+        let result = self.plane.create_deployment(&request).await;
+        let target = request.sites.join(",");
+        let args = serde_json::to_value(&request).unwrap_or_default();
+        self.record("create-deployment", &target, args, &result);
+
+        match result {
+            Ok(mut deployment) => {
+                if deployment.batches.is_empty() {
+                    deployment.batches = deployment.rollout.plan(&deployment.sites);
+                }
+                self.notifications.push(Level::Success, format!(
+                    "Created {} for {} site(s)",
+                    deployment.id,
+                    deployment.sites.len()
+                ));
+                self.pending_deployments.push(deployment);
+                self.close_composer();
+                self.selected_deployment = self.pending_deployments.len() - 1;
+            }
+            Err(e) => match e.downcast_ref::<ApiError>() {
+                Some(ApiError::Validation(errors)) => {
+                    if let Some(composer) = self.composer.as_mut() {
+                        composer.errors = errors.clone();
+                    }
+                    self.notifications.push(Level::Error, "Deployment rejected by control plane".to_string());
+                }
+                // Reported by execute; the composer stays open with its input
+                _ => return Err(e),
+            },
+        }
+        Ok(())
+    }
+
+    async fn handle_history_command(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Up => {
+                self.selected_history = self.selected_history.saturating_sub(1);
+            }
+            Command::Down => {
+                self.selected_history =
+                    (self.selected_history + 1).min(self.deployment_history.len().saturating_sub(1));
+            }
+            Command::Diff => {
+                // Show the diff this deployment applied
+                if let Some(record) = self.deployment_history.get(self.selected_history) {
+                    let diff = record.diff.clone();
+                    self.open_popup(diff);
+                }
+            }
+            Command::Rollback => {
+                if let Some(record) = self.deployment_history.get(self.selected_history) {
+                    if record.can_roll_back() {
+                        let record_id = record.id.clone();
+                        self.confirm(PendingAction::RollbackDeployment { record_id }).await;
+                    } else {
+                        self.notifications.push(Level::Warning, format!("{} cannot be rolled back", record.id));
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn handle_confirmation_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(confirmation) = self.confirmation.as_mut() else {
+            return Ok(());
+        };
+
+        // Some(true) to go ahead, Some(false) to cancel
+        let decision = match &mut confirmation.input {
+            ConfirmInput::YesNo => match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => Some(true),
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => Some(false),
+                _ => None,
+            },
+            ConfirmInput::Typed { expected, entered } => match key.code {
+                KeyCode::Enter if entered.trim() == expected => Some(true),
+                KeyCode::Esc => Some(false),
+                KeyCode::Backspace => {
+                    entered.pop();
+                    None
+                }
+                KeyCode::Char(c) => {
+                    entered.push(c);
+                    None
+                }
+                _ => None,
+            },
+            ConfirmInput::Reason(reason) => match key.code {
+                KeyCode::Enter if !reason.trim().is_empty() => Some(true),
+                KeyCode::Esc => Some(false),
+                KeyCode::Backspace => {
+                    reason.pop();
+                    None
+                }
+                KeyCode::Char(c) => {
+                    reason.push(c);
+                    None
+                }
+                _ => None,
+            },
+        };
+
+        match decision {
+            Some(true) => {
+                if let Some(confirmation) = self.confirmation.take() {
+                    match confirmation.input {
+                        ConfirmInput::Reason(reason) => {
+                            self.execute(confirmation.action, Some(reason.trim().to_string())).await;
+                        }
+                        _ => self.proceed(confirmation.action).await,
+                    }
+                }
+            }
+            Some(false) => {
+                self.confirmation = None;
+                self.notifications.push(Level::Info, "Cancelled".to_string());
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    async fn request_sync(&mut self) {
+        let Some(site_id) = self.sites.get(self.selected_site).map(|s| s.id.clone()) else {
+            return;
+        };
+        self.confirm(PendingAction::SyncSite { site_id }).await
+    }
+
+    /// Approve the selected deployment unless it is the operator's own or already approved
+    async fn request_approval(&mut self) {
+        let Some(deployment) = self.pending_deployments.get(self.selected_deployment) else {
+            return;
+        };
+        if deployment.status != DeploymentStatus::Pending || self.approvals_outstanding(deployment) == 0 {
+            self.notifications.push(Level::Warning, format!("{} is not awaiting approval", deployment.id));
+            return;
+        }
+        if deployment.created_by == self.operator {
+            self.notifications.push(Level::Warning, format!("You created {}; another operator must approve it", deployment.id));
+            return;
+        }
+        if deployment.approvals.iter().any(|a| a.operator == self.operator) {
+            self.notifications.push(Level::Warning, format!("You have already approved {}", deployment.id));
+            return;
+        }
+        let deployment_id = deployment.id.clone();
+        self.confirm(PendingAction::ApproveDeployment { deployment_id }).await
+    }
+
+    /// Gate an action on read-only mode and its confirmation policy
+    async fn confirm(&mut self, action: PendingAction) {
+        let scope = self.scope(&action);
+        if self.read_only {
+            self.notifications.push(Level::Warning, format!("Read-only mode: {} disabled", scope.action.name()));
+            return;
+        }
+
+        let sites: Vec<&Site> = self.sites.iter().filter(|s| scope.sites.contains(&s.id)).collect();
+        if let Some(reason) = self.permissions.denied(scope.action, &sites) {
+            self.notifications.push(Level::Warning, format!("Permission denied: {}", reason));
+            return;
+        }
+
+        let environments: Vec<&str> = scope.environments.iter().map(String::as_str).collect();
+        match self.confirm_policy.mode(scope.action, &environments) {
+            ConfirmMode::None => self.proceed(action).await,
+            ConfirmMode::Prompt => {
+                self.confirmation = Some(Confirmation {
+                    prompt: format!("{}?", scope.summary),
+                    action,
+                    input: ConfirmInput::YesNo,
+                });
+            }
+            ConfirmMode::Typed => {
+                self.confirmation = Some(Confirmation {
+                    prompt: format!("{}. Type {} to confirm.", scope.summary, scope.target),
+                    action,
+                    input: ConfirmInput::Typed { expected: scope.target, entered: String::new() },
+                });
+            }
+        }
+    }
+
+    /// Run a confirmed action, asking for an override reason outside change windows
+    async fn proceed(&mut self, action: PendingAction) {
+        let blocked = match &action {
+            PendingAction::SyncSite { site_id } => self.sites.iter()
+                .find(|s| &s.id == site_id)
+                .and_then(|site| self.change_calendar.blocked(site, chrono::Utc::now())),
+            // Checked against windows at the scheduled time
+            PendingAction::ApproveDeployment { deployment_id } => {
+                self.pending_deployments.iter().find(|d| &d.id == deployment_id).and_then(|deployment| {
+                    let at = deployment.scheduled.unwrap_or_else(chrono::Utc::now);
+                    let targets = self.sites.iter().filter(|s| deployment.sites.contains(&s.id));
+                    self.change_calendar.blocked_any(targets, at)
+                })
+            }
+            _ => None,
+        };
+        match blocked {
+            Some(reason) => {
+                self.confirmation = Some(Confirmation {
+                    prompt: format!("{}. Override and {} anyway?", reason, self.scope(&action).action.name()),
+                    action,
+                    input: ConfirmInput::Reason(String::new()),
+                });
+            }
+            None => self.execute(action, None).await,
+        }
+    }
+
+    fn scope(&self, action: &PendingAction) -> ActionScope {
+        let site_environments = |ids: &[String]| -> Vec<String> {
+            let mut environments: Vec<String> = self.sites.iter()
+                .filter(|s| ids.contains(&s.id))
+                .map(|s| s.environment.clone())
+                .collect();
+            environments.sort();
+            environments.dedup();
+            environments
+        };
+        let domain = |site_id: &str| {
+            self.sites.iter()
+                .find(|s| s.id == site_id)
+                .map(|s| s.domain.clone())
+                .unwrap_or_else(|| site_id.to_string())
+        };
+        let deployment_sites = |id: &str| {
+            self.pending_deployments.iter()
+                .find(|d| d.id == id)
+                .map(|d| d.sites.clone())
+                .unwrap_or_default()
+        };
+        let alert_site = |id: &str| {
+            self.alerts.iter()
+                .find(|a| a.id == id)
+                .map(|a| a.site_id.clone())
+                .unwrap_or_default()
+        };
+
+        match action {
+            PendingAction::SyncSite { site_id } => ActionScope {
+                action: Action::Sync,
+                target: domain(site_id),
+                summary: format!("Sync {}", domain(site_id)),
+                sites: vec![site_id.clone()],
+                environments: site_environments(std::slice::from_ref(site_id)),
+            },
+            PendingAction::ApproveDeployment { deployment_id } => ActionScope {
+                action: Action::Approve,
+                target: deployment_id.clone(),
+                summary: format!("Approve {}", deployment_id),
+                sites: deployment_sites(deployment_id),
+                environments: site_environments(&deployment_sites(deployment_id)),
+            },
+            PendingAction::TransitionDeployment { deployment_id, to } => {
+                let action = match to {
+                    DeploymentStatus::Paused => Action::Pause,
+                    DeploymentStatus::InProgress => Action::Resume,
+                    DeploymentStatus::Rejected => Action::Reject,
+                    _ => Action::Abort,
+                };
+                let verb = match action {
+                    Action::Pause => "Pause",
+                    Action::Resume => "Resume",
+                    Action::Reject => "Reject",
+                    _ => "Abort",
+                };
+                ActionScope {
+                    action,
+                    target: deployment_id.clone(),
+                    summary: format!("{} {}", verb, deployment_id),
+                    sites: deployment_sites(deployment_id),
+                    environments: site_environments(&deployment_sites(deployment_id)),
+                }
+            }
+            PendingAction::RollbackDeployment { record_id } => {
+                let record = self.deployment_history.iter().find(|r| &r.id == record_id);
+                ActionScope {
+                    action: Action::Rollback,
+                    target: record_id.clone(),
+                    summary: match record {
+                        Some(r) => format!(
+                            "Roll back {} ({}) on {} site(s) to {}",
+                            r.id,
+                            r.change_type,
+                            r.sites.len(),
+                            r.previous_config_hash.as_deref().unwrap_or("?")
+                        ),
+                        None => format!("Roll back {}", record_id),
+                    },
+                    sites: record.map(|r| r.sites.clone()).unwrap_or_default(),
+                    environments: site_environments(&record.map(|r| r.sites.clone()).unwrap_or_default()),
+                }
+            }
+            PendingAction::CreateDeployment { request } => ActionScope {
+                action: Action::CreateDeployment,
+                target: request.change_type.clone(),
+                summary: format!("Deploy {} to {} site(s)", request.change_type, request.sites.len()),
+                sites: request.sites.clone(),
+                environments: site_environments(&request.sites),
+            },
+            PendingAction::AcknowledgeAlert { alert_id } => ActionScope {
+                action: Action::Acknowledge,
+                target: alert_id.clone(),
+                summary: format!("Acknowledge {}", alert_id),
+                sites: vec![alert_site(alert_id)],
+                environments: site_environments(&[alert_site(alert_id)]),
+            },
+            PendingAction::DismissAlert { alert_id } => ActionScope {
+                action: Action::Dismiss,
+                target: alert_id.clone(),
+                summary: format!("Dismiss {}", alert_id),
+                sites: vec![alert_site(alert_id)],
+                environments: site_environments(&[alert_site(alert_id)]),
+            },
+            PendingAction::SilenceSite { site_id, duration_secs } => ActionScope {
+                action: Action::Silence,
+                target: domain(site_id),
+                summary: format!("Silence {} for {}", domain(site_id), format_duration(*duration_secs)),
+                sites: vec![site_id.clone()],
+                environments: site_environments(std::slice::from_ref(site_id)),
+            },
+        }
+    }
+
+    /// Perform an action. A failure has already been audited by the time it
+    /// gets here; it is reported and the session carries on.
+    async fn execute(&mut self, action: PendingAction, reason: Option<String>) {
+        let name = self.scope(&action).action.name();
+        if let Err(e) = self.perform(action, reason).await {
+            match e.downcast_ref::<ApiError>() {
+                Some(ApiError::PermissionDenied(reason)) => {
+                    self.notifications.push(Level::Warning, format!("Permission denied: {}", reason));
+                }
+                _ => self.notifications.push(Level::Error, format!("{} failed: {:#}", name, e)),
+            }
+        }
+    }
+
+    async fn perform(&mut self, action: PendingAction, reason: Option<String>) -> Result<()> {
+        match action {
+            PendingAction::SyncSite { site_id } => {
+                let Some(site) = self.sites.iter().find(|s| s.id == site_id) else {
+                    return Ok(());
+                };
+                let site = site.clone();
+                let result = self.plane.sync_site(&site.id, reason.as_deref()).await;
+                self.record("sync", &site.id, serde_json::json!({ "override_reason": reason }), &result);
+                result?;
+                self.notifications.push(Level::Success, match &reason {
+                    Some(reason) => format!("Sync initiated for {} (override: {})", site.domain, reason),
+                    None => format!("Sync initiated for {}", site.domain),
+                });
+            }
+            PendingAction::ApproveDeployment { deployment_id } => {
+                let Some(deployment) = self.pending_deployments.iter().find(|d| d.id == deployment_id) else {
+                    return Ok(());
+                };
+                let mut deployment = deployment.clone();
+                let result = self.plane.approve_deployment(&deployment.id, reason.as_deref()).await;
+                self.record("approve", &deployment.id, serde_json::json!({ "override_reason": reason }), &result);
+                let approval = result?;
+                deployment.approvals.push(approval);
+                let outstanding = self.approvals_outstanding(&deployment);
+                if outstanding == 0 && deployment.scheduled.is_none_or(|t| t <= chrono::Utc::now()) {
+                    deployment.status = DeploymentStatus::InProgress;
+                }
+                self.notifications.push(Level::Success, if outstanding == 0 {
+                    format!("Approved {}", deployment.id)
+                } else {
+                    format!("Approved {}; awaiting {} more approval(s)", deployment.id, outstanding)
+                });
+                if let Some(slot) = self.pending_deployments.iter_mut().find(|d| d.id == deployment.id) {
+                    *slot = deployment;
+                }
+            }
+            PendingAction::RollbackDeployment { record_id } => {
+                let Some(record) = self.deployment_history.iter().find(|r| r.id == record_id) else {
+                    return Ok(());
+                };
+                let Some(hash) = record.previous_config_hash.as_deref() else {
+                    return Ok(());
+                };
+                let hash = hash.to_string();
+                let record = record.clone();
+                let result = self.plane.create_rollback(&record, &hash).await;
+                self.record("rollback", &record.id, serde_json::json!({ "config_hash": hash }), &result);
+                let mut deployment = result?;
+                if deployment.batches.is_empty() {
+                    deployment.batches = deployment.rollout.plan(&deployment.sites);
+                }
+                self.notifications.push(Level::Success, format!("Created {} reverting to {}", deployment.id, hash));
+                self.pending_deployments.push(deployment);
+            }
+            PendingAction::TransitionDeployment { deployment_id: id, to } => {
+                let (action, result) = match to {
+                    DeploymentStatus::Paused => ("pause", self.plane.pause_deployment(&id).await),
+                    DeploymentStatus::InProgress => ("resume", self.plane.resume_deployment(&id).await),
+                    DeploymentStatus::Aborted => ("abort", self.plane.abort_deployment(&id).await),
+                    DeploymentStatus::Rejected => ("reject", self.plane.reject_deployment(&id).await),
+                    _ => return Ok(()),
+                };
+                self.record(action, &id, serde_json::json!({}), &result);
+                result?;
+
+                if let Some(deployment) = self.pending_deployments.iter_mut().find(|d| d.id == id) {
+                    deployment.status = to;
+                }
+                self.notifications.push(Level::Success, format!("{:?} {}", to, id));
+            }
+            PendingAction::CreateDeployment { request } => self.create_deployment(request).await?,
+            PendingAction::AcknowledgeAlert { alert_id } => {
+                let alert = self.alerts.iter().find(|a| a.id == alert_id);
+                let site_id = alert.map(|a| a.site_id.clone());
+                // The control plane has never heard of alerts we raised ourselves
+                let local = alert.is_some_and(Alert::is_local);
+                let result = if local { Ok(()) } else { self.plane.acknowledge_alert(&alert_id).await };
+                self.record("acknowledge", &alert_id, serde_json::json!({ "site_id": site_id }), &result);
+                result?;
+                if local {
+                    self.acknowledged_local.insert(alert_id.clone());
+                }
+                if let Some(alert) = self.alerts.iter_mut().find(|a| a.id == alert_id) {
+                    alert.acknowledged = true;
+                }
+                self.notifications.push(Level::Success, format!("Acknowledged {}", alert_id));
+            }
+            PendingAction::DismissAlert { alert_id } => {
+                let site_id = self.alerts.iter().find(|a| a.id == alert_id).map(|a| a.site_id.clone());
+                let result = self.plane.dismiss_alert(&alert_id).await;
+                self.record("dismiss", &alert_id, serde_json::json!({ "site_id": site_id }), &result);
+                result?;
+                self.alerts.retain(|a| a.id != alert_id);
+                self.selected_alert = self.selected_alert.min(self.alerts.len().saturating_sub(1));
+                self.notifications.push(Level::Success, format!("Dismissed {}", alert_id));
+            }
+            PendingAction::SilenceSite { site_id, duration_secs } => {
+                let result = self.plane.silence_site(&site_id, duration_secs).await;
+                self.record("silence", &site_id, serde_json::json!({ "duration_secs": duration_secs }), &result);
+                let until = result?;
+                self.silences.insert(site_id.clone(), until);
+                self.notifications.push(Level::Success, format!("Silenced {} until {}", site_id, until.format("%H:%M UTC")));
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_alerts_command(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Up => {
+                self.selected_alert = self.selected_alert.saturating_sub(1);
+            }
+            Command::Down => {
+                self.selected_alert = (self.selected_alert + 1).min(self.alerts.len().saturating_sub(1));
+            }
+            Command::Acknowledge => {
+                // Acknowledge selected alert
+                if let Some(alert_id) = self.alerts.get(self.selected_alert).map(|a| a.id.clone()) {
+                    self.confirm(PendingAction::AcknowledgeAlert { alert_id }).await;
+                }
+            }
+            Command::Dismiss => {
+                // Dismiss selected alert
+                if let Some(alert_id) = self.alerts.get(self.selected_alert).map(|a| a.id.clone()) {
+                    self.confirm(PendingAction::DismissAlert { alert_id }).await;
+                }
+            }
+            Command::Silence => {
+                // Silence the selected alert's site for an hour
+                if let Some(site_id) = self.alerts.get(self.selected_alert).map(|a| a.site_id.clone()) {
+                    self.confirm(PendingAction::SilenceSite { site_id, duration_secs: SILENCE_SECS }).await;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub fn is_silenced(&self, site_id: &str) -> bool {
+        self.silences.get(site_id).is_some_and(|until| *until > chrono::Utc::now())
+    }
+
+    fn handle_logs_command(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Up => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(1);
+            }
+            Command::Down => {
+                self.scroll_offset += 1;
+            }
+            Command::VerifyAudit => {
+                if let Err(e) = self.verify_audit_log() {
+                    self.notifications.push(Level::Error, format!("Could not verify the audit log: {:#}", e));
+                } else if let Some(verification) = &self.audit_verification {
+                    let level = if verification.is_intact() { Level::Success } else { Level::Error };
+                    let summary = verification.summary();
+                    self.notifications.push(level, summary);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn verify_audit_log(&mut self) -> Result<()> {
+        if let Some(log) = &self.audit {
+            self.audit_entries = log.entries()?;
+            self.audit_verification = Some(log.verify()?);
+        }
+        Ok(())
+    }
+
+    /// Append an action and its API outcome to the local audit log
+    fn record<T>(&mut self, action: &str, target: &str, args: serde_json::Value, result: &Result<T>) {
+        let Some(log) = self.audit.as_ref() else {
+            return;
+        };
+        let outcome = match result {
+            Ok(_) => "ok".to_string(),
+            Err(e) => format!("error: {:#}", e),
+        };
+        match log.append(&self.operator, &self.context.name, action, target, args, outcome) {
+            Ok(entry) => self.audit_entries.push(entry),
+            Err(e) => {
+                tracing::error!("Audit log write failed: {:#}", e);
+                self.notifications.push(Level::Error, format!("Audit log write failed: {:#}", e));
+            }
+        }
+    }
+
+    async fn handle_vulnerabilities_command(&mut self, command: Command) -> Result<()> {
+        match command {
+            Command::Search => {
+                self.editing_vuln_query = true;
+            }
+            Command::ClearSearch => {
+                self.vuln_query.clear();
+                self.scroll_offset = 0;
+            }
+            Command::Refresh => {
+                self.scan_vulnerabilities().await;
+            }
+            Command::Up => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(1);
+            }
+            Command::Down => {
+                self.scroll_offset += 1;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_vuln_query_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => {
+                self.editing_vuln_query = false;
+                self.scroll_offset = 0;
+                if !self.vuln_query.is_empty() {
+                    let count = self.sites_affected_by(&self.vuln_query).len();
+                    self.notifications.push(Level::Info, format!("{} site(s) affected by {}", count, self.vuln_query));
+                }
+            }
+            KeyCode::Esc => {
+                self.editing_vuln_query = false;
+                self.vuln_query.clear();
+            }
+            KeyCode::Backspace => {
+                self.vuln_query.pop();
+            }
+            KeyCode::Char(c) => self.vuln_query.push(c),
+            _ => {}
+        }
+    }
+
+    pub async fn handle_mouse(&mut self, mouse: MouseEvent) -> Result<()> {
+        if self.confirmation.is_some()
+            || self.context_picker.is_some()
+            || self.tab_menu.is_some()
+            || self.notification_center.is_some()
+            || self.palette.is_some()
+        {
+            return Ok(());
+        }
+        match mouse.kind {
+            MouseEventKind::ScrollUp if self.show_popup => self.popup_scroll = self.popup_scroll.saturating_sub(SCROLL_LINES),
+            MouseEventKind::ScrollDown if self.show_popup => self.popup_scroll = self.popup_scroll.saturating_add(SCROLL_LINES),
+            MouseEventKind::ScrollUp => self.run_command(Command::Up).await?,
+            MouseEventKind::ScrollDown => self.run_command(Command::Down).await?,
+            MouseEventKind::Down(MouseButton::Left) if !self.show_popup => {
+                let click = Position::new(mouse.column, mouse.row);
+                let (tab, rows, menu) = {
+                    let hits = self.hit_areas.borrow();
+                    let tab = hits.tabs.iter().find(|(area, _)| area.contains(click)).map(|(_, view)| *view);
+                    (tab, hits.rows, hits.menu)
+                };
+                if let Some(view) = tab {
+                    self.goto(view);
+                } else if menu.is_some_and(|area| area.contains(click)) {
+                    self.open_tab_menu();
+                } else if let Some(rows) = rows.filter(|area| area.contains(click)) {
+                    let index = usize::from(click.y - rows.y);
+                    let double = self.last_click.is_some_and(|(at, previous)| {
+                        previous == click && at.elapsed() < DOUBLE_CLICK
+                    });
+                    self.select_row(index);
+                    self.last_click = Some((Instant::now(), click));
+                    if double {
+                        self.last_click = None;
+                        self.run_command(Command::Open).await?;
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Select row `index` of the current view's list, if it exists
+    fn select_row(&mut self, index: usize) {
+        let (selected, len) = match self.view {
+            View::SiteList | View::SiteDetail => (&mut self.selected_site, self.sites.len()),
+            View::Deployments => (&mut self.selected_deployment, self.pending_deployments.len()),
+            View::History => (&mut self.selected_history, self.deployment_history.len()),
+            View::Alerts => (&mut self.selected_alert, self.alerts.len()),
+            _ => return,
+        };
+        if index < len {
+            *selected = index;
+        }
+    }
+
+    fn open_popup(&mut self, content: String) {
+        self.popup_content = content;
+        self.popup_scroll = 0;
+        self.show_popup = true;
+    }
+
+    pub fn handle_resize(&mut self, _width: u16, _height: u16) -> Result<()> {
+        // Terminal resize handled by ratatui automatically
+        Ok(())
+    }
+
+    pub async fn tick(&mut self) -> Result<()> {
+        if let Some(reloaded) = self.config_watcher.as_mut().and_then(ConfigWatcher::poll) {
+            match reloaded.and_then(|loaded| self.apply_config(loaded.config)) {
+                Ok(notice) => match self.refresh_data().await {
+                    Ok(()) => self.notifications.push(Level::Success, notice),
+                    Err(e) => self.notifications.push(Level::Error, format!("{}; refresh failed: {:#}", notice, e)),
+                },
+                Err(e) => {
+                    tracing::warn!("Config reload failed: {:#}", e);
+                    let reason = format!("{:#}", e);
+                    let reason: Vec<&str> = reason.lines().map(str::trim).collect();
+                    self.notifications.push(Level::Error, format!(
+                        "Config reload failed, keeping previous configuration: {}",
+                        reason.join(" ")
+                    ));
+                }
+            }
+        }
+
+        // TODO: Replace polling with WebSocket updates
+        // Row indices shift on refresh; hold the result while the operator is mid-action
+        let idle = self.confirmation.is_none() && self.composer.is_none();
+        if idle && self.refresh_task.as_ref().is_some_and(JoinHandle::is_finished) {
+            if let Some(task) = self.refresh_task.take() {
+                match task.await {
+                    Ok(refresh) => match self.take_refresh(refresh) {
+                        Ok(()) => self.enforce_rollbacks().await,
+                        Err(e) => self.notifications.push(Level::Error, format!("Refresh failed: {:#}", e)),
+                    },
+                    Err(e) => tracing::warn!("Background refresh failed: {}", e),
+                }
+            }
+        }
+        let due = self.refresh_interval.is_some_and(|every| self.last_refresh.elapsed() >= every);
+        if due && idle && self.refresh_task.is_none() {
+            self.last_refresh = Instant::now();
+            self.refresh_task = Some(tokio::spawn(self.refresh_job().run()));
+        }
+        Ok(())
+    }
+
+    /// Apply a reloaded config and describe the result. The connection,
+    /// operator and audit log are fixed for the session.
+    fn apply_config(&mut self, config: Config) -> Result<String> {
+        // Everything fallible happens before any state changes
+        let vuln_db = config.vuln_db.as_deref().map(VulnDatabase::load).transpose()?;
+        let keymap = Keymap::new(&config.keys).map_err(|problems| anyhow::anyhow!(problems.join("; ")))?;
+
+        self.confirm_policy = config.confirm.clone();
+        self.force_read_only = config.read_only;
+        if let Ok(context) = config.context(&self.context.name) {
+            if context.api_url == self.context.api_url {
+                self.context.read_only = context.read_only;
+                self.context.theme = context.theme;
+            }
+        }
+        self.read_only = self.fleet.is_some() || self.force_read_only || self.context.read_only || self.plane.read_only();
+        self.contexts = config.all_contexts(&self.context);
+        self.local_calendar = config.maintenance.clone();
+        self.dnssec = DnssecValidator::from_config(&config.dnssec);
+        self.vuln_db = vuln_db;
+        self.refresh_interval = config.refresh_interval_secs.map(Duration::from_secs);
+        self.keymap = keymap;
+        self.pending_keys.clear();
+        self.mouse = config.mouse.unwrap_or(true);
+        self.layout = config.layout.clone();
+        self.default_theme = config.theme.clone();
+        self.user_themes = config.themes.clone();
+        self.select_theme();
+
+        let audit_path = config.audit_log.clone().or_else(AuditLog::default_path);
+        let mut fixed = Vec::new();
+        if config.operator() != self.operator {
+            fixed.push("operator");
+        }
+        if audit_path.as_deref() != self.audit.as_ref().map(AuditLog::path) {
+            fixed.push("audit_log");
+        }
+        Ok(if fixed.is_empty() {
+            "Configuration reloaded".to_string()
+        } else {
+            format!("Configuration reloaded ({} applies after restart)", fixed.join(", "))
+        })
+    }
+
+    /// Fetch everything again, raising a toast for each new critical alert
+    async fn refresh_data(&mut self) -> Result<()> {
+        // This fetch is newer than any still running in the background
+        if let Some(task) = self.refresh_task.take() {
+            task.abort();
+        }
+        self.last_refresh = Instant::now();
+        let refresh = self.refresh_job().run().await;
+        self.take_refresh(refresh)?;
+        self.enforce_rollbacks().await;
+        Ok(())
+    }
+
+    /// Everything a refresh needs from the app, for it to run on its own
+    fn refresh_job(&self) -> RefreshJob {
+        RefreshJob {
+            plane: Arc::clone(&self.plane),
+            fleet: self.fleet.as_ref().map(|fleet| Box::pin(fleet.fetch()) as _),
+            routes: self.routes(),
+            dnssec: self.dnssec.clone(),
+            scan: self.vuln_db.is_some(),
+        }
+    }
+
+    /// Where to send a site's requests: the fleet's planes, or the one plane
+    fn routes(&self) -> Routes {
+        match &self.fleet {
+            Some(fleet) => fleet.clients(),
+            None => vec![(String::new(), Arc::clone(&self.plane))],
+        }
+    }
+
+    /// Apply what a refresh fetched, raising a toast for each new critical alert
+    fn take_refresh(&mut self, refresh: Refresh) -> Result<()> {
+        let known: HashSet<String> = self.alerts.iter().map(|a| a.id.clone()).collect();
+        self.apply_refresh(refresh)?;
+        let new: Vec<&Alert> = self.alerts.iter()
+            .filter(|a| a.severity == AlertSeverity::Critical && !a.acknowledged && !known.contains(&a.id))
+            .filter(|a| !self.is_silenced(&a.site_id))
+            .collect();
+        let messages: Vec<String> = if new.len() > MAX_TOASTS {
+            vec![format!("{} new critical alerts", new.len())]
+        } else {
+            new.iter().map(|a| format!("Critical: {} - {}", a.site_id, a.message)).collect()
+        };
+        for message in messages {
+            self.notifications.push(Level::Error, message);
+        }
+        Ok(())
+    }
+
+    /// An unreachable plane fails the refresh and leaves everything as it was
+    fn apply_refresh(&mut self, refresh: Refresh) -> Result<()> {
+        let Refresh { fetched, dnssec, inventories, warnings } = refresh;
+        let selected = self.location();
+        match fetched {
+            Fetched::Fleet(results) => {
+                let Some(fleet) = self.fleet.as_mut() else {
+                    return Ok(());
+                };
+                fleet.apply(results);
+                self.sites = fleet.sites();
+                self.alerts = fleet.alerts();
+                self.pending_deployments = fleet.deployments();
+                // Each plane enforces its own RBAC; the aggregate is read-only anyway
+                self.permissions = Permissions::default();
+                self.groups.clear();
+                self.change_calendar = self.local_calendar.clone();
+                self.deployment_history.clear();
+                plan_missing_batches(&mut self.pending_deployments);
+                self.flag_unhealthy_rollouts();
+            }
+            Fetched::Plane(data) => {
+                let data = (*data)?;
+                self.sites = data.sites;
+                self.permissions = data.permissions;
+                if !self.can_view(self.view) {
+                    self.view = View::Dashboard;
+                }
+                self.alerts = data.alerts;
+                self.pending_deployments = data.pending_deployments;
+                self.groups = data.groups;
+                self.change_calendar = self.local_calendar.clone();
+                self.change_calendar.merge(data.calendar);
+                plan_missing_batches(&mut self.pending_deployments);
+                self.flag_unhealthy_rollouts();
+                self.deployment_history = data.deployment_history;
+            }
+        }
+        for warning in warnings {
+            self.notifications.push(Level::Warning, warning);
+        }
+        if self.dnssec.is_some() {
+            // Zones of planes that did not answer keep their last report
+            self.dnssec_reports.extend(dnssec);
+            let zones: HashSet<&str> = self.sites.iter().map(Site::zone).collect();
+            self.dnssec_reports.retain(|zone, _| zones.contains(zone.as_str()));
+            self.rebuild_dnssec_alerts();
+        }
+        self.apply_inventories(inventories);
+        self.reselect_rows(selected);
+        Ok(())
+    }
+
+    /// Refresh every site's inventory and match it against the vulnerability database
+    async fn scan_vulnerabilities(&mut self) {
+        if self.vuln_db.is_none() {
+            return;
+        }
+        let sites: Vec<Site> = self.sites.iter()
+            .filter(|site| !self.fleet.as_ref().is_some_and(|f| f.is_stale(&site.id)))
+            .cloned()
+            .collect();
+        let inventories = refresh::fetch_inventories(&self.routes(), &sites).await;
+        self.apply_inventories(inventories);
+    }
+
+    fn apply_inventories(&mut self, inventories: Vec<(String, Result<Option<WordPressInventory>>)>) {
+        let Some(db) = &self.vuln_db else {
+            return;
+        };
+        for (site_id, inventory) in inventories {
+            // A site that fails to answer keeps its last inventory
+            match inventory {
+                Ok(Some(inventory)) => {
+                    self.wp_inventory.insert(site_id, inventory);
+                }
+                Ok(None) => {
+                    self.wp_inventory.remove(&site_id);
+                }
+                Err(e) => tracing::warn!("WordPress inventory for {} failed: {:#}", site_id, e),
+            }
+        }
+        self.vuln_findings = db.scan_fleet(self.wp_inventory.values());
+
+        self.alerts.retain(|a| !a.id.starts_with("vuln-"));
+        let now = self.now();
+        let mut first_seen = HashMap::new();
+        for finding in self.vuln_findings.values().flatten() {
+            let id = finding.alert_id();
+            let seen = self.vuln_first_seen.get(&id).copied().unwrap_or(now);
+            self.alerts.push(finding.to_alert(seen));
+            first_seen.insert(id, seen);
+        }
+        // A finding that goes away and comes back is new again
+        self.vuln_first_seen = first_seen;
+        self.restore_acknowledgements();
+    }
+
+    /// Mark rebuilt local alerts the operator had already acknowledged
+    fn restore_acknowledgements(&mut self) {
+        for alert in self.alerts.iter_mut().filter(|a| self.acknowledged_local.contains(&a.id)) {
+            alert.acknowledged = true;
+        }
+    }
+
+    /// Findings matching the current query (all findings when the query is empty)
+    pub fn filtered_findings(&self) -> Vec<&Finding> {
+        let mut findings: Vec<&Finding> = self.vuln_findings.values()
+            .flatten()
+            .filter(|f| {
+                self.vuln_query.is_empty()
+                    || self.vuln_db.as_ref().is_some_and(|db| {
+                        db.vulnerabilities.iter().any(|v| v.id == f.vuln_id && v.is_named(&self.vuln_query))
+                    })
+            })
+            .collect();
+        findings.sort_by(|a, b| a.vuln_id.cmp(&b.vuln_id).then(a.site_id.cmp(&b.site_id)));
+        findings
+    }
+
+    /// Sites with at least one finding for the named vulnerability
+    pub fn sites_affected_by(&self, query: &str) -> Vec<&Site> {
+        let Some(db) = &self.vuln_db else {
+            return Vec::new();
+        };
+        self.sites.iter()
+            .filter(|site| {
+                self.vuln_findings.get(&site.id).is_some_and(|findings| {
+                    findings.iter().any(|f| {
+                        db.vulnerabilities.iter().any(|v| v.id == f.vuln_id && v.is_named(query))
+                    })
+                })
+            })
+            .collect()
+    }
+
+    /// Production changes need at least one approver besides the creator,
+    /// whatever the server reports
+    pub fn required_approvals(&self, deployment: &Deployment) -> u32 {
+        let touches_production = self.sites.iter()
+            .any(|s| deployment.sites.contains(&s.id) && s.environment == "production");
+        deployment.required_approvals.max(u32::from(touches_production))
+    }
+
+    pub fn approvals_outstanding(&self, deployment: &Deployment) -> u32 {
+        let received = deployment.approvals.iter()
+            .filter(|a| a.operator != deployment.created_by)
+            .count() as u32;
+        self.required_approvals(deployment).saturating_sub(received)
+    }
+
+    /// Active deployments whose deployed batches fail health probes, with the failing sites
+    fn unhealthy_rollouts(&self) -> Vec<(&Deployment, Vec<String>)> {
+        self.pending_deployments.iter()
+            .filter(|d| d.is_active())
+            .map(|d| (d, d.batches.iter().flat_map(|b| b.unhealthy_sites(&self.sites)).collect::<Vec<_>>()))
+            .filter(|(_, failed)| !failed.is_empty())
+            .collect()
+    }
+
+    /// Alert on every unhealthy rollout; run whenever deployments or sites are (re)loaded
+    fn flag_unhealthy_rollouts(&mut self) {
+        let now = self.now();
+        let alerts: Vec<Alert> = self.unhealthy_rollouts().into_iter()
+            .map(|(deployment, failed)| {
+                let next = match (deployment.rollout.rollback_on_failure, self.read_only) {
+                    (true, false) => "rolling back",
+                    (true, true) => "this session is read-only and cannot roll it back",
+                    (false, _) => "abort or roll back manually",
+                };
+                let id = format!("rollout-{}", deployment.id);
+                let timestamp = self.rollout_first_seen.get(&id).copied().unwrap_or(now);
+                Alert {
+                    id,
+                    site_id: failed[0].clone(),
+                    severity: AlertSeverity::Critical,
+                    message: format!("Deployment {}: health probes failed on {}; {}", deployment.id, failed.join(", "), next),
+                    timestamp,
+                    acknowledged: false,
+                }
+            })
+            .collect();
+        self.alerts.retain(|a| !a.id.starts_with("rollout-"));
+        self.rollout_first_seen = alerts.iter().map(|a| (a.id.clone(), a.timestamp)).collect();
+        // Raised as a toast by the new-alert check in take_refresh
+        self.alerts.extend(alerts);
+        self.restore_acknowledgements();
+    }
+
+    /// Roll back unhealthy rollouts whose policy asks for it. Each deployment is
+    /// requested once per session; a failed request is reported, not retried.
+    async fn enforce_rollbacks(&mut self) {
+        if self.read_only {
+            return;
+        }
+        let due: Vec<(String, Vec<String>)> = self.unhealthy_rollouts().into_iter()
+            .filter(|(d, _)| d.rollout.rollback_on_failure && !self.rollbacks_requested.contains(&d.id))
+            .map(|(d, failed)| (d.id.clone(), failed))
+            .collect();
+        for (id, failed) in due {
+            self.rollbacks_requested.insert(id.clone());
+            let result = self.plane.rollback_deployment(&id).await;
+            self.record("auto-rollback", &id, serde_json::json!({ "failed_sites": failed }), &result);
+            let (level, message) = match result {
+                Ok(()) => {
+                    if let Some(deployment) = self.pending_deployments.iter_mut().find(|d| d.id == id) {
+                        deployment.status = DeploymentStatus::RolledBack;
+                        for site in deployment.batches.iter_mut().flat_map(|b| b.sites.iter_mut()) {
+                            if site.status != SiteRolloutStatus::Pending {
+                                site.status = SiteRolloutStatus::RolledBack;
+                            }
+                        }
+                    }
+                    (Level::Warning, format!("Deployment {} rolled back: health probes failed on {}", id, failed.join(", ")))
+                }
+                Err(e) => (
+                    Level::Error,
+                    format!("Deployment {}: health probes failed on {}; rollback failed: {:#}", id, failed.join(", "), e),
+                ),
+            };
+            if let Some(alert) = self.alerts.iter_mut().find(|a| a.id == format!("rollout-{}", id)) {
+                alert.message = message.clone();
+            }
+            self.notifications.push(level, message);
+        }
+    }
+
+    /// Validate every distinct zone behind the managed sites, all at once
+    async fn check_dnssec(&mut self) {
+        let Some(validator) = &self.dnssec else {
+            return;
+        };
+        self.dnssec_reports = refresh::check_zones(validator, &self.sites).await;
+        self.rebuild_dnssec_alerts();
+    }
+
+    /// Replace DNSSEC-derived alerts with those from the current reports
+    fn rebuild_dnssec_alerts(&mut self) {
+        self.alerts.retain(|a| !a.id.starts_with("dnssec-"));
+        for report in self.dnssec_reports.values() {
+            let site_ids: Vec<String> = self.sites.iter()
+                .filter(|s| s.zone() == report.zone)
+                .map(|s| s.id.clone())
+                .collect();
+            self.alerts.extend(report.alerts(&site_ids));
+        }
+        self.restore_acknowledgements();
+    }
+
+    pub fn dnssec_report(&self, site: &Site) -> Option<&ZoneReport> {
+        self.dnssec_reports.get(site.zone())
+    }
+
+    pub fn now(&self) -> chrono::DateTime<chrono::Utc> {
+        self.clock.unwrap_or_else(chrono::Utc::now)
+    }
+
+    pub fn healthy_sites(&self) -> usize {
+        self.sites.iter().filter(|s| s.status == SiteStatus::Healthy).count()
+    }
+
+    pub fn warning_sites(&self) -> usize {
+        self.sites.iter().filter(|s| s.status == SiteStatus::Warning).count()
+    }
+
+    pub fn critical_sites(&self) -> usize {
+        self.sites.iter().filter(|s| s.status == SiteStatus::Critical).count()
+    }
+
+    pub fn unacknowledged_alerts(&self) -> usize {
+        self.alerts.iter().filter(|a| !a.acknowledged && !self.is_silenced(&a.site_id)).count()
+    }
+}
+
+/// Client serving `id` and the ID as that plane knows it
+fn client_for<'a>(fleet: &'a Option<Fleet>, default: &'a dyn ControlPlane, id: &'a str) -> (&'a dyn ControlPlane, &'a str) {
+    fleet.as_ref()
+        .and_then(|f| f.route(id))
+        .unwrap_or((default, id))
+}
+
+/// "sites, alerts: connection refused; history: 404", grouping what failed the same way
+fn describe_failures(failures: &[(&str, anyhow::Error)]) -> String {
+    let mut groups: Vec<(Vec<&str>, String)> = Vec::new();
+    for (what, error) in failures {
+        // The outer errors name each URL; the cause is what they have in common
+        let error = error.root_cause().to_string();
+        match groups.iter_mut().find(|(_, e)| *e == error) {
+            Some((whats, _)) => whats.push(what),
+            None => groups.push((vec![what], error)),
+        }
+    }
+    groups.iter()
+        .map(|(whats, error)| format!("{}: {}", whats.join(", "), error))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Row of the item with `id`; one that has gone leaves the `current` row selected
+fn reselect<T>(items: &[T], item_id: impl Fn(&T) -> &String, id: Option<String>, current: usize) -> usize {
+    id.and_then(|id| items.iter().position(|item| *item_id(item) == id))
+        .unwrap_or(current)
+        .min(items.len().saturating_sub(1))
+}
+
+/// Deployments reported without batch detail get one planned from their policy
+fn plan_missing_batches(deployments: &mut [Deployment]) {
+    for deployment in deployments {
+        if deployment.batches.is_empty() {
+            deployment.batches = deployment.rollout.plan(&deployment.sites);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ApiClient;
+    use crate::fixtures::{app, fixture, press, NOW};
+    use crate::mock::MockPlane;
+
+    fn approval(operator: &str) -> Approval {
+        Approval { operator: operator.to_string(), at: NOW.parse().unwrap() }
+    }
+
+    fn latest(app: &App) -> &str {
+        app.notifications.history().next().map_or("", |n| n.message.as_str())
+    }
+
+    #[test]
+    fn creators_do_not_count_towards_approvals() {
+        let app = app("approvals-outstanding");
+        let mut deployment = app.pending_deployments[0].clone();
+        assert_eq!(app.approvals_outstanding(&deployment), 1);
+        deployment.approvals.push(approval("alice"));
+        assert_eq!(app.approvals_outstanding(&deployment), 1);
+        deployment.approvals.push(approval("carol"));
+        assert_eq!(app.approvals_outstanding(&deployment), 0);
+
+        // Production always needs a second operator, even when the server asks for none
+        deployment.required_approvals = 0;
+        deployment.approvals.clear();
+        assert_eq!(app.approvals_outstanding(&deployment), 1);
+        deployment.sites = vec!["site-3".to_string()];
+        assert_eq!(app.approvals_outstanding(&deployment), 0);
+        deployment.required_approvals = 2;
+        deployment.approvals.push(approval("carol"));
+        assert_eq!(app.approvals_outstanding(&deployment), 1);
+    }
+
+    #[tokio::test]
+    async fn operators_cannot_approve_their_own_deployments() {
+        let mut app = app("self-approval");
+        // Windows are checked against the wall clock; keep them out of this
+        app.change_calendar = ChangeCalendar::default();
+        app.pending_deployments[0].created_by = "tester".to_string();
+        press(&mut app, "2 a").await;
+        assert!(app.confirmation.is_none());
+        assert_eq!(latest(&app), "You created deploy-001; another operator must approve it");
+        assert!(app.pending_deployments[0].approvals.is_empty());
+
+        app.pending_deployments[0].created_by = "alice".to_string();
+        press(&mut app, "a y").await;
+        assert_eq!(app.pending_deployments[0].approvers(), ["tester"]);
+        press(&mut app, "a").await;
+        assert!(app.confirmation.is_none());
+        assert_eq!(latest(&app), "deploy-001 is not awaiting approval");
+    }
+
+    #[tokio::test]
+    async fn failed_fetches_are_reported_instead_of_connecting() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let config = Config {
+            operator: Some("tester".to_string()),
+            audit_log: Some(std::env::temp_dir().join(format!("socp-tui-unreachable-{}.log", std::process::id()))),
+            ..Config::default()
+        };
+        let context = config.select_context(None, Some(format!("http://127.0.0.1:{}", port))).unwrap();
+        let app = App::new(context, &config).await.unwrap();
+
+        let notifications: Vec<_> = app.notifications.history().collect();
+        assert!(!notifications.iter().any(|n| n.message.starts_with("Connected to")));
+        let error = notifications.iter().find(|n| n.level == Level::Error).expect("no error notification");
+        assert!(
+            error.message.starts_with(
+                "Could not fetch from default: sites, alerts, deployments, groups, change calendar, history, permissions: "
+            ),
+            "{}",
+            error.message
+        );
+        assert!(app.sites.is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_actions_are_reported_and_the_session_carries_on() {
+        let mut app = app("closed-port");
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let mut context = app.context.clone();
+        context.api_url = format!("http://127.0.0.1:{}", port);
+        app.plane = plane::connect(&context, "tester").unwrap();
+
+        press(&mut app, "3 a").await;
+        assert!(app.running);
+        let error = app.notifications.history().next().unwrap();
+        assert_eq!(error.level, Level::Error);
+        assert!(error.message.starts_with("acknowledge failed: "), "{}", error.message);
+        let entry = app.audit_entries.last().unwrap();
+        assert_eq!((entry.action.as_str(), entry.target.as_str()), ("acknowledge", "alert-1"));
+        assert!(entry.result.starts_with("error: "), "{}", entry.result);
+
+        press(&mut app, "2 n").await;
+        app.composer.as_mut().unwrap().change_type = "security-headers".to_string();
+        press(&mut app, "ctrl+s y").await;
+        assert!(app.running);
+        assert_eq!(app.view, View::Composer);
+        assert_eq!(app.composer.as_ref().unwrap().change_type, "security-headers");
+        let error = app.notifications.history().next().unwrap();
+        assert!(error.message.starts_with("create-deployment failed: "), "{}", error.message);
+    }
+
+    #[test]
+    fn failures_are_grouped_by_error() {
+        let failures = [
+            ("sites", anyhow::anyhow!("connection reset")),
+            ("alerts", anyhow::anyhow!("HTTP 500")),
+            ("history", anyhow::anyhow!("connection reset")),
+        ];
+        assert_eq!(describe_failures(&failures), "sites, history: connection reset; alerts: HTTP 500");
+    }
+
+    #[tokio::test]
+    async fn back_and_forward_reselect_rows_by_id() {
+        let mut app = app("reselect");
+        press(&mut app, "1 j j 2 j").await;
+        assert_eq!(app.sites[app.selected_site].id, "site-3");
+        assert_eq!(app.pending_deployments[app.selected_deployment].id, "deploy-002");
+        press(&mut app, "3").await;
+
+        // A site and a deployment ahead of the selected ones disappear
+        let mut changed = fixture();
+        changed.plane.sites.remove(0);
+        changed.plane.pending_deployments.remove(0);
+        app.plane = Arc::new(MockPlane::new(changed, "tester"));
+        app.refresh_data().await.unwrap();
+
+        press(&mut app, "alt+left").await;
+        assert_eq!(app.view, View::Deployments);
+        assert_eq!(app.pending_deployments[app.selected_deployment].id, "deploy-002");
+        press(&mut app, "alt+left").await;
+        assert_eq!(app.view, View::SiteList);
+        assert_eq!(app.sites[app.selected_site].id, "site-3");
+
+        // The selected row itself is gone: stay on the row the cursor is on
+        press(&mut app, "j").await;
+        let mut changed = fixture();
+        changed.plane.sites.retain(|s| s.id != "site-1" && s.id != "site-4");
+        app.plane = Arc::new(MockPlane::new(changed, "tester"));
+        press(&mut app, "2").await;
+        app.refresh_data().await.unwrap();
+        press(&mut app, "alt+left").await;
+        assert_eq!(app.view, View::SiteList);
+        assert_eq!(app.sites[app.selected_site].id, "site-3");
+        press(&mut app, "alt+right").await;
+        assert_eq!(app.view, View::Deployments);
+    }
+
+    #[tokio::test]
+    async fn refresh_keeps_the_selected_site_when_the_list_shrinks() {
+        let mut app = app("shrinking-sites");
+        press(&mut app, "1 j j j").await;
+        assert_eq!(app.sites[app.selected_site].id, "site-4");
+
+        let mut changed = fixture();
+        changed.plane.sites.retain(|s| s.id != "site-2");
+        app.plane = Arc::new(MockPlane::new(changed, "tester"));
+        app.refresh_data().await.unwrap();
+        assert_eq!(app.sites[app.selected_site].id, "site-4");
+
+        let mut changed = fixture();
+        changed.plane.sites.truncate(1);
+        app.plane = Arc::new(MockPlane::new(changed, "tester"));
+        app.refresh_data().await.unwrap();
+        assert_eq!(app.selected_site, 0);
+    }
+
+    #[tokio::test]
+    async fn composer_opens_and_closes_through_history() {
+        let mut app = app("composer-history");
+        press(&mut app, "2 n").await;
+        assert_eq!(app.view, View::Composer);
+        press(&mut app, "esc").await;
+        assert_eq!(app.view, View::Deployments);
+        press(&mut app, "alt+left").await;
+        assert_eq!(app.view, View::Dashboard);
+        // Forward skips the composer that was closed
+        press(&mut app, "alt+right alt+right").await;
+        assert_eq!(app.view, View::Deployments);
+        assert!(app.composer.is_none());
+
+        press(&mut app, "n").await;
+        app.composer.as_mut().unwrap().change_type = "security-headers".to_string();
+        press(&mut app, "ctrl+s y").await;
+        assert_eq!(app.pending_deployments.len(), 3);
+        assert_eq!(app.view, View::Deployments);
+        assert_eq!(app.selected_deployment, app.pending_deployments.len() - 1);
+        press(&mut app, "alt+left").await;
+        assert_eq!(app.view, View::Dashboard);
+    }
+
+    #[tokio::test]
+    async fn periodic_refreshes_run_in_the_background() {
+        let mut app = app("background-refresh");
+        let mut changed = fixture();
+        let mut alert = changed.plane.alerts[0].clone();
+        alert.id = "alert-new".to_string();
+        alert.severity = AlertSeverity::Critical;
+        alert.acknowledged = false;
+        alert.message = "Origin unreachable".to_string();
+        let toast = format!("Critical: {} - Origin unreachable", alert.site_id);
+        changed.plane.alerts.push(alert);
+        app.plane = Arc::new(MockPlane::new(changed, "tester"));
+        app.refresh_interval = Some(Duration::ZERO);
+
+        app.tick().await.unwrap();
+        assert!(app.refresh_task.is_some());
+        assert!(!app.alerts.iter().any(|a| a.id == "alert-new"));
+        for _ in 0..100 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            app.tick().await.unwrap();
+            if app.alerts.iter().any(|a| a.id == "alert-new") {
+                break;
+            }
+        }
+        assert!(app.alerts.iter().any(|a| a.id == "alert-new"));
+        assert!(app.notifications.history().any(|n| n.message == toast));
+    }
+
+    #[tokio::test]
+    async fn ticks_do_not_wait_for_a_silent_plane() {
+        let mut app = app("silent-plane");
+        // Accepts connections but never answers
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let context = Config::default().select_context(None, Some(url)).unwrap();
+        app.plane = Arc::new(ApiClient::new(&context, "tester").unwrap());
+        app.refresh_interval = Some(Duration::ZERO);
+        let sites = app.sites.len();
+
+        for _ in 0..3 {
+            tokio::time::timeout(Duration::from_secs(1), app.tick()).await.expect("tick blocked").unwrap();
+        }
+        assert!(app.refresh_task.as_ref().is_some_and(|task| !task.is_finished()));
+        assert_eq!(app.sites.len(), sites);
+        app.refresh_task.take().unwrap().abort();
     }
 }
@@ -0,0 +1,355 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Tamper-evident local audit log of operator actions
+//!
+//! Each mutating action is appended as one JSON line. Every entry carries the
+//! SHA-256 of the previous entry and its own hash over that link plus its
+//! contents, so editing, reordering or deleting a line breaks the chain from
+//! that point on. Truncating the tail cannot be detected locally; the
+//! control plane keeps its own audit trail for that.
+//!
+//! Several sessions may share one log: each append holds an exclusive lock on
+//! the file while it reads the last entry and links the new one to it.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub operator: String,
+    /// Control plane the action was sent to. Entries written before contexts
+    /// existed have none; leaving it out when empty keeps their hashes valid.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub context: String,
+    pub action: String,
+    pub target: String,
+    pub args: serde_json::Value,
+    /// "ok" or the error returned by the control plane
+    pub result: String,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditEntry {
+    fn compute_hash(&self) -> Result<String> {
+        let unsigned = AuditEntry { hash: String::new(), ..self.clone() };
+        let mut hasher = Sha256::new();
+        hasher.update(self.prev_hash.as_bytes());
+        hasher.update(serde_json::to_vec(&unsigned)?);
+        Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    pub fn succeeded(&self) -> bool {
+        self.result == "ok"
+    }
+}
+
+/// Outcome of checking the hash chain
+#[derive(Debug, Clone)]
+pub struct Verification {
+    pub entries: usize,
+    /// Line number (1-based) and reason of the first broken link
+    pub broken: Option<(usize, String)>,
+}
+
+impl Verification {
+    pub fn is_intact(&self) -> bool {
+        self.broken.is_none()
+    }
+
+    pub fn summary(&self) -> String {
+        match &self.broken {
+            None => format!("Audit chain intact ({} entries)", self.entries),
+            Some((line, reason)) => format!("Audit chain BROKEN at line {}: {}", line, reason),
+        }
+    }
+}
+
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn default_path() -> Option<PathBuf> {
+        directories::ProjectDirs::from("sh", "rhodium", "socp-tui")
+            .map(|dirs| dirs.data_dir().join("audit.jsonl"))
+    }
+
+    /// Open (or create) the log; appends continue the chain from whatever entry is last at the time
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("creating audit log directory {}", parent.display()))?;
+        }
+        read_entries(path)?;
+        Ok(Self { path: path.to_path_buf() })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(
+        &self,
+        operator: &str,
+        context: &str,
+        action: &str,
+        target: &str,
+        args: serde_json::Value,
+        result: String,
+    ) -> Result<AuditEntry> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&self.path)
+            .with_context(|| format!("opening audit log {}", self.path.display()))?;
+        // Released when the file is closed; another session appending meanwhile waits here
+        file.lock().with_context(|| format!("locking audit log {}", self.path.display()))?;
+        let mut contents = String::new();
+        file.seek(SeekFrom::Start(0))?;
+        file.read_to_string(&mut contents)
+            .with_context(|| format!("reading audit log {}", self.path.display()))?;
+        let (seq, prev_hash) = match parse_entries(&contents).last() {
+            Some(entry) => (entry.seq + 1, entry.hash.clone()),
+            None => (0, GENESIS_HASH.to_string()),
+        };
+
+        let mut entry = AuditEntry {
+            seq,
+            timestamp: Utc::now(),
+            operator: operator.to_string(),
+            context: context.to_string(),
+            action: action.to_string(),
+            target: target.to_string(),
+            args,
+            result,
+            prev_hash,
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash()?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        file.sync_data()?;
+        Ok(entry)
+    }
+
+    pub fn entries(&self) -> Result<Vec<AuditEntry>> {
+        read_entries(&self.path)
+    }
+
+    pub fn verify(&self) -> Result<Verification> {
+        verify(&self.path)
+    }
+}
+
+/// Parseable entries in file order; damaged lines are skipped here and
+/// reported by [`verify`]
+fn read_entries(path: &Path) -> Result<Vec<AuditEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("reading audit log {}", path.display()))?;
+    Ok(parse_entries(&contents))
+}
+
+fn parse_entries(contents: &str) -> Vec<AuditEntry> {
+    contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// Walk the chain and report the first entry that does not link up
+pub fn verify(path: &Path) -> Result<Verification> {
+    if !path.exists() {
+        return Ok(Verification { entries: 0, broken: None });
+    }
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("reading audit log {}", path.display()))?;
+
+    let mut prev_hash = GENESIS_HASH.to_string();
+    let mut expected_seq = 0;
+    let mut entries = 0;
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_no = i + 1;
+        let broken = |reason: String| Ok(Verification { entries, broken: Some((line_no, reason)) });
+
+        let entry: AuditEntry = match serde_json::from_str(line) {
+            Ok(entry) => entry,
+            Err(e) => return broken(format!("unparseable entry ({})", e)),
+        };
+        if entry.seq != expected_seq {
+            return broken(format!("expected seq {}, found {}", expected_seq, entry.seq));
+        }
+        if entry.prev_hash != prev_hash {
+            return broken("previous-hash link does not match".to_string());
+        }
+        if entry.compute_hash()? != entry.hash {
+            return broken("entry contents do not match its hash".to_string());
+        }
+
+        prev_hash = entry.hash;
+        expected_seq += 1;
+        entries += 1;
+    }
+    Ok(Verification { entries, broken: None })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A log with `n` entries, one per line
+    fn log(name: &str, n: usize) -> (AuditLog, Vec<String>) {
+        let path = std::env::temp_dir().join(format!("socp-tui-audit-{}-{}.jsonl", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        let log = AuditLog::open(&path).unwrap();
+        for i in 0..n {
+            let result = if i == 1 { "403 Forbidden".to_string() } else { "ok".to_string() };
+            log.append("alice", "prod", "sync", &format!("site-{}", i), serde_json::json!({ "override_reason": null }), result)
+                .unwrap();
+        }
+        let lines = fs::read_to_string(&path).unwrap().lines().map(str::to_string).collect();
+        (log, lines)
+    }
+
+    fn verify_lines(log: &AuditLog, lines: &[String]) -> Verification {
+        fs::write(log.path(), lines.join("\n") + "\n").unwrap();
+        log.verify().unwrap()
+    }
+
+    #[test]
+    fn intact_chain_verifies() {
+        let (log, _) = log("intact", 3);
+        let verification = log.verify().unwrap();
+        assert_eq!(verification.summary(), "Audit chain intact (3 entries)");
+        assert_eq!(log.entries().unwrap().iter().filter(|e| e.succeeded()).count(), 2);
+        fs::remove_file(log.path()).unwrap();
+    }
+
+    #[test]
+    fn tampering_is_reported() {
+        let (log, lines) = log("tampered", 4);
+
+        let mut edited = lines.clone();
+        edited[1] = edited[1].replace("403 Forbidden", "ok");
+        assert_eq!(verify_lines(&log, &edited).broken, Some((2, "entry contents do not match its hash".to_string())));
+
+        let mut deleted = lines.clone();
+        deleted.remove(2);
+        assert_eq!(verify_lines(&log, &deleted).broken, Some((3, "expected seq 2, found 3".to_string())));
+
+        let mut reordered = lines.clone();
+        reordered.swap(1, 2);
+        assert_eq!(verify_lines(&log, &reordered).broken, Some((2, "expected seq 1, found 2".to_string())));
+
+        // Renumbering hides a deletion from the sequence check but not from the chain
+        let mut renumbered = lines.clone();
+        renumbered.remove(2);
+        renumbered[2] = renumbered[2].replace("\"seq\":3", "\"seq\":2");
+        assert_eq!(verify_lines(&log, &renumbered).broken, Some((3, "previous-hash link does not match".to_string())));
+
+        let head_truncated = lines[2..].to_vec();
+        let verification = verify_lines(&log, &head_truncated);
+        assert_eq!(verification.broken, Some((1, "expected seq 0, found 2".to_string())));
+        assert_eq!(verification.entries, 0);
+
+        let mut torn = lines.clone();
+        let half = torn[3].len() / 2;
+        torn[3].truncate(half);
+        let verification = verify_lines(&log, &torn);
+        assert_eq!(verification.entries, 3);
+        assert!(verification.broken.as_ref().is_some_and(|(line, reason)| *line == 4 && reason.starts_with("unparseable entry")));
+        assert!(verification.summary().starts_with("Audit chain BROKEN at line 4"));
+
+        fs::remove_file(log.path()).unwrap();
+    }
+
+    #[test]
+    fn sessions_sharing_a_log_extend_one_chain() {
+        let (first, _) = log("shared", 2);
+        let second = AuditLog::open(first.path()).unwrap();
+        first.append("alice", "prod", "sync", "site-a", serde_json::json!({}), "ok".to_string()).unwrap();
+        let entry = second.append("bob", "prod", "sync", "site-b", serde_json::json!({}), "ok".to_string()).unwrap();
+        assert_eq!(entry.seq, 3);
+
+        let path = first.path().to_path_buf();
+        let writers: Vec<_> = (0..4)
+            .map(|n| {
+                let log = AuditLog::open(&path).unwrap();
+                std::thread::spawn(move || {
+                    for i in 0..10 {
+                        log.append("carol", "prod", "approve", &format!("deploy-{}-{}", n, i), serde_json::json!({}), "ok".to_string())
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert_eq!(first.verify().unwrap().summary(), "Audit chain intact (44 entries)");
+        fs::remove_file(&path).unwrap();
+    }
+
+    /// Entry as written before contexts were recorded
+    #[derive(Serialize)]
+    struct LegacyEntry {
+        seq: u64,
+        timestamp: DateTime<Utc>,
+        operator: String,
+        action: String,
+        target: String,
+        args: serde_json::Value,
+        result: String,
+        prev_hash: String,
+        hash: String,
+    }
+
+    #[test]
+    fn logs_from_before_contexts_still_verify() {
+        let path = std::env::temp_dir().join(format!("socp-tui-audit-legacy-{}.jsonl", std::process::id()));
+        let mut lines = Vec::new();
+        let mut prev_hash = GENESIS_HASH.to_string();
+        for seq in 0..2 {
+            let mut entry = LegacyEntry {
+                seq,
+                timestamp: "2026-01-10T12:00:00Z".parse().unwrap(),
+                operator: "alice".to_string(),
+                action: "approve".to_string(),
+                target: format!("deploy-00{}", seq),
+                args: serde_json::json!({ "override_reason": null }),
+                result: "ok".to_string(),
+                prev_hash: prev_hash.clone(),
+                hash: String::new(),
+            };
+            let mut hasher = Sha256::new();
+            hasher.update(prev_hash.as_bytes());
+            hasher.update(serde_json::to_vec(&entry).unwrap());
+            entry.hash = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+            prev_hash = entry.hash.clone();
+            lines.push(serde_json::to_string(&entry).unwrap());
+        }
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+
+        assert_eq!(verify(&path).unwrap().summary(), "Audit chain intact (2 entries)");
+        let log = AuditLog::open(&path).unwrap();
+        assert_eq!(log.entries().unwrap()[0].context, "");
+        let entry = log.append("bob", "prod", "sync", "site-1", serde_json::json!({}), "ok".to_string()).unwrap();
+        assert_eq!((entry.seq, entry.prev_hash.as_str()), (2, prev_hash.as_str()));
+        assert_eq!(log.verify().unwrap().summary(), "Audit chain intact (3 entries)");
+        fs::remove_file(&path).unwrap();
+    }
+}
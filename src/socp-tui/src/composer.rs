@@ -0,0 +1,392 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Deployment composer: target selection, change set, schedule and rollout
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::app::Site;
+use crate::rollout::{parse_duration, RolloutPolicy, RolloutStrategy};
+
+/// A named group of sites (config/groups/*.ncl)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SiteGroup {
+    pub id: String,
+    pub sites: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetMode {
+    Selection,
+    Tag,
+    Environment,
+    Group,
+}
+
+impl TargetMode {
+    const ALL: [TargetMode; 4] = [
+        TargetMode::Selection,
+        TargetMode::Tag,
+        TargetMode::Environment,
+        TargetMode::Group,
+    ];
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|m| *m == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(self) -> Self {
+        let i = Self::ALL.iter().position(|m| *m == self).unwrap_or(0);
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TargetMode::Selection => "sites",
+            TargetMode::Tag => "tag",
+            TargetMode::Environment => "environment",
+            TargetMode::Group => "group",
+        }
+    }
+}
+
+/// How the change set is given
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeMode {
+    /// A change type the control plane renders per site, e.g. "php-8.3"
+    Named,
+    /// A unified config diff read from a local file
+    Diff,
+}
+
+impl ChangeMode {
+    pub fn toggle(self) -> Self {
+        match self {
+            ChangeMode::Named => ChangeMode::Diff,
+            ChangeMode::Diff => ChangeMode::Named,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ChangeMode::Named => "change type",
+            ChangeMode::Diff => "config diff",
+        }
+    }
+}
+
+/// Field with keyboard focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComposerField {
+    Targets,
+    ChangeSet,
+    Schedule,
+    Rollout,
+}
+
+impl ComposerField {
+    pub fn next(self) -> Self {
+        match self {
+            ComposerField::Targets => ComposerField::ChangeSet,
+            ComposerField::ChangeSet => ComposerField::Schedule,
+            ComposerField::Schedule => ComposerField::Rollout,
+            ComposerField::Rollout => ComposerField::Targets,
+        }
+    }
+
+    pub fn prev(self) -> Self {
+        match self {
+            ComposerField::Targets => ComposerField::Rollout,
+            ComposerField::ChangeSet => ComposerField::Targets,
+            ComposerField::Schedule => ComposerField::ChangeSet,
+            ComposerField::Rollout => ComposerField::Schedule,
+        }
+    }
+
+    /// Name used by the API for validation errors on this field
+    pub fn api_name(self) -> &'static str {
+        match self {
+            ComposerField::Targets => "sites",
+            ComposerField::ChangeSet => "change_type",
+            ComposerField::Schedule => "scheduled",
+            ComposerField::Rollout => "rollout",
+        }
+    }
+
+    /// Whether a validation error on the API field `name` belongs here
+    pub fn reports(self, name: &str) -> bool {
+        match self {
+            ComposerField::ChangeSet => name == "change_type" || name == "config_diff",
+            ComposerField::Rollout => name == "rollout" || name.starts_with("rollout."),
+            field => name == field.api_name(),
+        }
+    }
+}
+
+/// Payload for `POST /deployments` and `POST /deployments/preview`
+#[derive(Debug, Clone, Serialize)]
+pub struct DeploymentRequest {
+    pub sites: Vec<String>,
+    pub change_type: String,
+    /// Unified diff to apply to every target; `change_type` then only labels it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_diff: Option<String>,
+    pub scheduled: Option<DateTime<Utc>>,
+    pub rollout: RolloutPolicy,
+}
+
+impl DeploymentRequest {
+    /// The config diff headed by the sites it applies to, for planes that apply it as is
+    pub fn verbatim_preview(&self) -> Option<String> {
+        self.config_diff.as_ref().map(|diff| format!("# {} on {}\n{}", self.change_type, self.sites.join(", "), diff))
+    }
+}
+
+/// A validation error reported by the server for one field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Composer {
+    pub focus: ComposerField,
+    pub target_mode: TargetMode,
+    /// Tag, environment or group name for the non-selection modes
+    pub target_value: String,
+    pub selected_sites: BTreeSet<String>,
+    pub cursor: usize,
+    pub change_mode: ChangeMode,
+    pub change_type: String,
+    /// File holding the config diff in `ChangeMode::Diff`
+    pub diff_path: String,
+    /// "now", "YYYY-MM-DD HH:MM" (UTC) or a relative offset such as "+6h"
+    pub schedule: String,
+    pub strategy: RolloutStrategy,
+    /// `key=value` overrides as in a group's `deployment` block, e.g. "batch_size=2 pause_between=10m"
+    pub rollout: String,
+    pub errors: Vec<FieldError>,
+}
+
+impl Composer {
+    pub fn new(preselected: Option<&Site>) -> Self {
+        Self {
+            focus: ComposerField::Targets,
+            target_mode: TargetMode::Selection,
+            target_value: String::new(),
+            selected_sites: preselected.map(|s| s.id.clone()).into_iter().collect(),
+            cursor: 0,
+            change_mode: ChangeMode::Named,
+            change_type: String::new(),
+            diff_path: String::new(),
+            schedule: "now".to_string(),
+            strategy: RolloutStrategy::default(),
+            rollout: String::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Site IDs the deployment would target
+    pub fn resolve_targets(&self, sites: &[Site], groups: &[SiteGroup]) -> Vec<String> {
+        let value = self.target_value.trim();
+        match self.target_mode {
+            TargetMode::Selection => sites
+                .iter()
+                .filter(|s| self.selected_sites.contains(&s.id))
+                .map(|s| s.id.clone())
+                .collect(),
+            TargetMode::Tag => sites
+                .iter()
+                .filter(|s| s.tags.iter().any(|t| t == value))
+                .map(|s| s.id.clone())
+                .collect(),
+            TargetMode::Environment => sites
+                .iter()
+                .filter(|s| s.environment == value)
+                .map(|s| s.id.clone())
+                .collect(),
+            TargetMode::Group => groups
+                .iter()
+                .find(|g| g.id == value)
+                .map(|g| g.sites.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn parse_schedule(&self, now: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, String> {
+        let value = self.schedule.trim();
+        if value.is_empty() || value.eq_ignore_ascii_case("now") {
+            return Ok(None);
+        }
+        if let Some(offset) = value.strip_prefix('+') {
+            return parse_duration(offset)
+                .map(|secs| Some(now + chrono::Duration::seconds(secs as i64)))
+                .ok_or_else(|| format!("invalid offset: {}", value));
+        }
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M")
+            .map(|t| Some(t.and_utc()))
+            .map_err(|_| "expected \"now\", \"+6h\" or \"YYYY-MM-DD HH:MM\"".to_string())
+    }
+
+    /// The selected strategy with the typed overrides applied
+    pub fn rollout_policy(&self) -> Result<RolloutPolicy, String> {
+        let mut policy = RolloutPolicy { strategy: self.strategy, ..RolloutPolicy::default() };
+        for option in self.rollout.split_whitespace() {
+            let Some((key, value)) = option.split_once('=') else {
+                return Err(format!("expected key=value, got {}", option));
+            };
+            let invalid = || format!("invalid {}: {}", key, value);
+            let count = || value.parse().ok().filter(|n| *n > 0).ok_or_else(invalid);
+            match key {
+                "batch_size" => policy.batch_size = count()?,
+                "canary_size" => policy.canary_size = count()?,
+                "pause_between" => policy.pause_between_secs = parse_duration(value).ok_or_else(invalid)?,
+                "rollback_on_failure" => policy.rollback_on_failure = value.parse().map_err(|_| invalid())?,
+                _ => {
+                    return Err(format!(
+                        "unknown option {} (batch_size, canary_size, pause_between, rollback_on_failure)",
+                        key
+                    ))
+                }
+            }
+        }
+        Ok(policy)
+    }
+
+    /// Change type and, for a diff, its contents
+    fn change_set(&self) -> Result<(String, Option<String>), String> {
+        match self.change_mode {
+            ChangeMode::Named => Ok((self.change_type.trim().to_string(), None)),
+            ChangeMode::Diff => {
+                let path = self.diff_path.trim();
+                if path.is_empty() {
+                    return Err("path to a diff file is required".to_string());
+                }
+                let diff = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+                if diff.trim().is_empty() {
+                    return Err(format!("{} is empty", path));
+                }
+                let name = Path::new(path).file_name().map_or_else(|| path.into(), |n| n.to_string_lossy());
+                Ok((format!("config-diff {}", name), Some(diff)))
+            }
+        }
+    }
+
+    pub fn build_request(&self, sites: &[Site], groups: &[SiteGroup]) -> Result<DeploymentRequest, FieldError> {
+        let error = |field: ComposerField| move |message| FieldError { field: field.api_name().to_string(), message };
+        let (change_type, config_diff) = self.change_set().map_err(error(ComposerField::ChangeSet))?;
+        let scheduled = self.parse_schedule(Utc::now()).map_err(error(ComposerField::Schedule))?;
+        let rollout = self.rollout_policy().map_err(error(ComposerField::Rollout))?;
+        Ok(DeploymentRequest {
+            sites: self.resolve_targets(sites, groups),
+            change_type,
+            config_diff,
+            scheduled,
+            rollout,
+        })
+    }
+
+    pub fn error_for(&self, field: ComposerField) -> Option<&str> {
+        self.errors
+            .iter()
+            .find(|e| field.reports(&e.field))
+            .map(|e| e.message.as_str())
+    }
+
+    /// Text buffer behind the focused field, if it takes free text
+    pub fn focused_text(&mut self) -> Option<&mut String> {
+        match self.focus {
+            ComposerField::Targets if self.target_mode != TargetMode::Selection => Some(&mut self.target_value),
+            ComposerField::Targets => None,
+            ComposerField::ChangeSet => Some(match self.change_mode {
+                ChangeMode::Named => &mut self.change_type,
+                ChangeMode::Diff => &mut self.diff_path,
+            }),
+            ComposerField::Schedule => Some(&mut self.schedule),
+            ComposerField::Rollout => Some(&mut self.rollout),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{app, fixture, press, type_text};
+
+    fn composer(rollout: &str) -> Composer {
+        Composer { rollout: rollout.to_string(), ..Composer::new(None) }
+    }
+
+    #[test]
+    fn rollout_overrides_apply_to_the_chosen_strategy() {
+        let policy = Composer { strategy: RolloutStrategy::Canary, ..composer("batch_size=3 pause_between=10m") }
+            .rollout_policy()
+            .unwrap();
+        assert_eq!(policy.strategy, RolloutStrategy::Canary);
+        assert_eq!((policy.batch_size, policy.canary_size, policy.pause_between_secs), (3, 1, 600));
+        assert!(policy.rollback_on_failure);
+
+        let policy = composer("canary_size=2 rollback_on_failure=false").rollout_policy().unwrap();
+        assert_eq!((policy.canary_size, policy.rollback_on_failure), (2, false));
+
+        for bad in ["batch_size", "batch_size=0", "batch_size=x", "pause_between=soon", "rollback_on_failure=1", "retries=3"] {
+            assert!(composer(bad).rollout_policy().is_err(), "{} accepted", bad);
+        }
+    }
+
+    #[test]
+    fn requests_carry_the_diff_and_rollout() {
+        let path = std::env::temp_dir().join(format!("socp-tui-headers-{}.diff", std::process::id()));
+        std::fs::write(&path, "-  ttl = 300,\n+  ttl = 600,\n").unwrap();
+        let data = fixture().plane;
+        let mut composer = Composer {
+            rollout: "batch_size=2".to_string(),
+            change_mode: ChangeMode::Diff,
+            diff_path: path.display().to_string(),
+            strategy: RolloutStrategy::Batched,
+            ..Composer::new(data.sites.first())
+        };
+
+        let request = composer.build_request(&data.sites, &data.groups).unwrap();
+        assert_eq!(request.sites, ["site-1"]);
+        assert_eq!(request.change_type, format!("config-diff {}", path.file_name().unwrap().to_string_lossy()));
+        assert_eq!(request.config_diff.as_deref(), Some("-  ttl = 300,\n+  ttl = 600,\n"));
+        assert_eq!((request.rollout.strategy, request.rollout.batch_size), (RolloutStrategy::Batched, 2));
+        std::fs::remove_file(&path).unwrap();
+
+        let error = composer.build_request(&data.sites, &data.groups).unwrap_err();
+        assert_eq!(error.field, "change_type");
+        composer.rollout = "batch_size=none".to_string();
+        composer.change_mode = ChangeMode::Named;
+        let error = composer.build_request(&data.sites, &data.groups).unwrap_err();
+        assert_eq!(error.field, "rollout");
+        assert!(ComposerField::Rollout.reports("rollout.batch_size"));
+    }
+
+    #[tokio::test]
+    async fn preview_shows_the_proposed_change() {
+        let mut app = app("composer-preview");
+        press(&mut app, "2 n tab").await;
+        type_text(&mut app, "php-8.3").await;
+        press(&mut app, "ctrl+d").await;
+        assert!(app.show_popup);
+        assert!(app.popup_content.starts_with("# php-8.3 on site-1\n"), "{}", app.popup_content);
+
+        let path = std::env::temp_dir().join(format!("socp-tui-preview-{}.diff", std::process::id()));
+        std::fs::write(&path, "+  ttl = 600,\n").unwrap();
+        press(&mut app, "esc right").await;
+        type_text(&mut app, &path.display().to_string()).await;
+        press(&mut app, "ctrl+d").await;
+        std::fs::remove_file(&path).unwrap();
+        assert!(app.popup_content.ends_with("on site-1\n+  ttl = 600,\n"), "{}", app.popup_content);
+        assert!(!app.popup_content.contains("x_frame_options"));
+
+        press(&mut app, "esc ctrl+d").await;
+        assert!(!app.show_popup);
+        let error = app.composer.as_ref().unwrap().error_for(ComposerField::ChangeSet).unwrap();
+        assert!(error.starts_with(&path.display().to_string()), "{}", error);
+    }
+}
@@ -1,29 +1,675 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 //! Configuration loading
+//!
+//! Layers, lowest precedence first: system file, user file (or `--config`),
+//! the nearest project-local `.socp-tui.toml`, `SOCP_*` environment variables
+//! (`__` separates nested keys, e.g. `SOCP_DNSSEC__WARN_DAYS`), and CLI flags.
+//! Unknown keys and bad values are errors rather than silently ignored.
 
-use anyhow::Result;
-use serde::Deserialize;
-use std::path::Path;
+use ::config::{Environment, File, FileFormat, Source, Value, ValueKind};
+use anyhow::{bail, Context as _, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-#[derive(Debug, Deserialize, Default)]
+use crate::keymap::{KeyConfig, Keymap};
+use crate::layout::LayoutConfig;
+use crate::maintenance::ChangeCalendar;
+use crate::plane::Backend;
+use crate::policy::ConfirmPolicy;
+use crate::theme::{Theme, ThemeSpec, BUILTIN_THEMES};
+
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Config {
     pub api_url: Option<String>,
+    /// Context to use when neither `--context` nor `--api-url` is given
+    pub context: Option<String>,
+    /// Named control planes (`[contexts.<name>]`)
+    #[serde(default)]
+    pub contexts: BTreeMap<String, Context>,
+    /// Operator identity; defaults to $USER
+    pub operator: Option<String>,
+    /// Refresh data from the control plane this often; off when unset
     pub refresh_interval_secs: Option<u64>,
+    /// Built-in theme (dark, light, high-contrast, colorblind) or one from `themes`
     pub theme: Option<String>,
+    /// User-defined themes (`[themes.<name>]`)
+    #[serde(default)]
+    pub themes: BTreeMap<String, ThemeSpec>,
+    /// Key bindings per mode (`[keys.<mode>]`), replacing the defaults per command
+    #[serde(default)]
+    pub keys: KeyConfig,
+    /// Capture the mouse for clicks and scrolling (default true); false keeps
+    /// the terminal's own text selection
+    pub mouse: Option<bool>,
+    /// Dashboard panels and split-pane width (`[layout]`)
+    #[serde(default)]
+    pub layout: LayoutConfig,
+    /// Disable all mutating actions (also `--read-only`)
+    #[serde(default)]
+    pub read_only: bool,
+    /// `[[confirm]]` rules, added to the built-in typed confirmation for production syncs
+    #[serde(default)]
+    pub confirm: ConfirmPolicy,
+    /// Audit log location; defaults to the user data directory
+    pub audit_log: Option<PathBuf>,
+    /// Local vulnerability database (JSON export) to match inventories against
+    pub vuln_db: Option<PathBuf>,
+    #[serde(default)]
+    pub dnssec: DnssecConfig,
+    /// Local maintenance windows and freezes, merged with those from the API
+    #[serde(default)]
+    pub maintenance: ChangeCalendar,
+}
+
+/// A named control plane
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Context {
+    #[serde(skip)]
+    pub name: String,
+    /// `https://...`, or `mock:[fixture.json]` / `repo:<checkout>` for offline data
+    pub api_url: String,
+    /// PEM file with the client certificate and private key
+    pub identity: Option<PathBuf>,
+    /// Additional CA certificate (PEM) to trust, e.g. a private PKI root
+    pub ca_cert: Option<PathBuf>,
+    /// Overrides the top-level `theme` while connected to this plane
+    pub theme: Option<String>,
+    /// Start in read-only mode when talking to this plane
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+impl Context {
+    /// Unnamed endpoint from `--api-url` or the top-level `api_url`
+    fn adhoc(name: &str, api_url: String) -> Self {
+        Self {
+            name: name.to_string(),
+            api_url,
+            identity: None,
+            ca_cert: None,
+            theme: None,
+            read_only: false,
+        }
+    }
+}
+
+const DEFAULT_API_URL: &str = "https://[::1]:8443";
+
+/// DNSSEC validation settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DnssecConfig {
+    /// Resolver or authoritative server to query; checks are disabled when unset
+    pub resolver: Option<SocketAddr>,
+    pub timeout_ms: u64,
+    /// Raise a warning when signatures expire within this many days
+    pub warn_days: i64,
+    /// Raise a critical alert when signatures expire within this many days
+    pub critical_days: i64,
+}
+
+impl Default for DnssecConfig {
+    fn default() -> Self {
+        Self {
+            resolver: None,
+            timeout_ms: 3000,
+            warn_days: 7,
+            critical_days: 2,
+        }
+    }
+}
+
+impl Config {
+    pub fn context(&self, name: &str) -> Result<Context> {
+        match self.contexts.get(name) {
+            Some(context) => Ok(Context { name: name.to_string(), ..context.clone() }),
+            None => bail!(
+                "unknown context '{}' (configured: {})",
+                name,
+                self.contexts.keys().cloned().collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+
+    /// Control plane to start with: `--context`, then `--api-url`, then the configured default
+    pub fn select_context(&self, name: Option<&str>, api_url: Option<String>) -> Result<Context> {
+        if let Some(name) = name {
+            return self.context(name);
+        }
+        if let Some(url) = api_url {
+            return Ok(Context::adhoc("default", url));
+        }
+        if let Some(name) = &self.context {
+            return self.context(name);
+        }
+        let url = self.api_url.clone().unwrap_or_else(|| DEFAULT_API_URL.to_string());
+        Ok(Context::adhoc("default", url))
+    }
+
+    /// Every configured context, plus `current` if it is ad hoc
+    pub fn all_contexts(&self, current: &Context) -> Vec<Context> {
+        let mut contexts: Vec<Context> = self.contexts.keys()
+            .filter_map(|name| self.context(name).ok())
+            .collect();
+        if !self.contexts.contains_key(&current.name) {
+            contexts.insert(0, current.clone());
+        }
+        contexts
+    }
+
+    fn has_theme(&self, name: &str) -> bool {
+        BUILTIN_THEMES.contains(&name) || self.themes.contains_key(name)
+    }
+
+    pub fn operator(&self) -> String {
+        self.operator.clone()
+            .or_else(|| std::env::var("USER").ok())
+            .unwrap_or_else(|| "unknown".to_string())
+    }
+
+    /// Values that parse but make no sense
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        let mut urls: Vec<(String, &str)> = Vec::new();
+        if let Some(url) = &self.api_url {
+            urls.push(("api_url".to_string(), url));
+        }
+        for (name, context) in &self.contexts {
+            urls.push((format!("contexts.{}.api_url", name), &context.api_url));
+        }
+        for (key, url) in urls {
+            if let Err(e) = Backend::parse(url) {
+                problems.push(format!("{}: {}", key, e));
+            }
+        }
+
+        if let Some(name) = &self.context {
+            if !self.contexts.contains_key(name) {
+                problems.push(format!("context: no [contexts.{}] defined", name));
+            }
+        }
+        problems.extend(Theme::check(&self.themes));
+        if let Err(keymap_problems) = Keymap::new(&self.keys) {
+            problems.extend(keymap_problems);
+        }
+        let mut themes = vec![("theme".to_string(), &self.theme)];
+        for (name, context) in &self.contexts {
+            themes.push((format!("contexts.{}.theme", name), &context.theme));
+        }
+        for (key, theme) in themes {
+            if let Some(theme) = theme.as_deref().filter(|t| !self.has_theme(t)) {
+                problems.push(format!("{}: unknown theme '{}'", key, theme));
+            }
+        }
+        problems.extend(self.layout.check());
+        if self.refresh_interval_secs == Some(0) {
+            problems.push("refresh_interval_secs: must be greater than 0".to_string());
+        }
+        if self.dnssec.timeout_ms == 0 {
+            problems.push("dnssec.timeout_ms: must be greater than 0".to_string());
+        }
+        if self.dnssec.critical_days > self.dnssec.warn_days {
+            problems.push(format!(
+                "dnssec.critical_days ({}) exceeds dnssec.warn_days ({})",
+                self.dnssec.critical_days, self.dnssec.warn_days
+            ));
+        }
+
+        let mut files: Vec<(String, &Path)> = Vec::new();
+        if let Some(path) = &self.vuln_db {
+            files.push(("vuln_db".to_string(), path));
+        }
+        // Other contexts' certificates are read when switching to them
+        if let Some((name, context)) = self.context.as_ref().and_then(|name| self.contexts.get_key_value(name)) {
+            if let Some(path) = &context.identity {
+                files.push((format!("contexts.{}.identity", name), path));
+            }
+            if let Some(path) = &context.ca_cert {
+                files.push((format!("contexts.{}.ca_cert", name), path));
+            }
+        }
+        for (key, path) in files {
+            if !path.is_file() {
+                problems.push(format!("{}: {} does not exist", key, path.display()));
+            }
+        }
+
+        for (i, window) in self.maintenance.windows.iter().enumerate() {
+            if window.days.is_empty() {
+                problems.push(format!("maintenance.windows[{}] ({}): no days", i, window.name));
+            }
+            if window.scope.describe().is_empty() {
+                problems.push(format!("maintenance.windows[{}] ({}): covers no environments or tags", i, window.name));
+            }
+        }
+        for (i, freeze) in self.maintenance.freezes.iter().enumerate() {
+            if freeze.from >= freeze.until {
+                problems.push(format!("maintenance.freezes[{}] ({}): 'from' is not before 'until'", i, freeze.name));
+            }
+        }
+        problems
+    }
+}
+
+/// CLI flags, which override every other layer
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    pub api_url: Option<String>,
+    pub context: Option<String>,
+    pub read_only: bool,
+}
+
+/// A source of configuration, lowest precedence first
+#[derive(Debug)]
+pub struct Layer {
+    pub name: &'static str,
+    pub path: Option<PathBuf>,
+    pub present: bool,
+}
+
+/// The effective configuration and where each value came from
+pub struct LoadedConfig {
+    pub config: Config,
+    pub layers: Vec<Layer>,
+    /// Key, value and source of every value that was set
+    pub values: Vec<(String, String, String)>,
+}
+
+impl LoadedConfig {
+    /// Report for `socp-tui config show`
+    pub fn show(&self) -> String {
+        let mut out = String::from("# Layers (lowest precedence first)\n");
+        for layer in &self.layers {
+            let location = layer.path.as_ref().map(|p| p.display().to_string()).unwrap_or_default();
+            let state = if layer.present { "used" } else { "absent" };
+            out.push_str(&format!("#   {:<12} {:<7} {}\n", layer.name, state, location));
+        }
+        out.push_str("\n# Effective values; anything unset uses its built-in default\n");
+        let width = self.values.iter().map(|(k, v, _)| k.len() + v.len()).max().unwrap_or(0) + 3;
+        for (key, value, source) in &self.values {
+            let line = format!("{} = {}", key, value);
+            out.push_str(&format!("{:<width$}  # {}\n", line, source, width = width));
+        }
+        out
+    }
+}
+
+const PROJECT_FILE: &str = ".socp-tui.toml";
+const SYSTEM_FILE: &str = "/etc/socp-tui/config.toml";
+const ENV_PREFIX: &str = "SOCP";
+const ENV_ORIGIN: &str = "the environment";
+
+fn user_file() -> Option<PathBuf> {
+    directories::ProjectDirs::from("sh", "rhodium", "socp-tui").map(|dirs| {
+        dirs.config_dir().join("config.toml")
+    })
 }
 
-pub fn load_config(path: Option<&str>) -> Result<Config> {
-    let config_path = path.map(|p| Path::new(p).to_path_buf()).or_else(|| {
-        directories::ProjectDirs::from("sh", "rhodium", "socp-tui").map(|dirs| {
-            dirs.config_dir().join("config.toml")
-        })
+/// Nearest `.socp-tui.toml` in `cwd` or one of its parents
+fn project_file(cwd: &Path) -> Option<PathBuf> {
+    cwd.ancestors().map(|dir| dir.join(PROJECT_FILE)).find(|path| path.is_file())
+}
+
+/// Config files in precedence order; `path` (from `--config`) replaces the user file
+fn layer_files(path: Option<&str>, cwd: Option<&Path>) -> [(&'static str, Option<PathBuf>); 3] {
+    [
+        ("system", Some(PathBuf::from(SYSTEM_FILE))),
+        ("user", path.map(PathBuf::from).or_else(user_file)),
+        ("project", cwd.and_then(project_file)),
+    ]
+}
+
+fn source_name(value: &Value) -> String {
+    match value.origin() {
+        None => "command line".to_string(),
+        Some(ENV_ORIGIN) => format!("environment ({}_*)", ENV_PREFIX),
+        Some(origin) => origin.to_string(),
+    }
+}
+
+fn render(value: &Value) -> String {
+    match &value.kind {
+        ValueKind::String(s) => format!("{:?}", s),
+        ValueKind::Array(items) => format!("[{}]", items.iter().map(render).collect::<Vec<_>>().join(", ")),
+        _ => value.to_string(),
+    }
+}
+
+/// Every leaf as (dotted key, rendered value, source)
+fn flatten(table: &::config::Map<String, Value>, prefix: &str, out: &mut Vec<(String, String, String)>) {
+    let mut keys: Vec<&String> = table.keys().collect();
+    keys.sort();
+    for key in keys {
+        let value = &table[key];
+        let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match &value.kind {
+            ValueKind::Table(inner) => flatten(inner, &path, out),
+            ValueKind::Array(items) if items.iter().any(|i| matches!(i.kind, ValueKind::Table(_))) => {
+                for (i, item) in items.iter().enumerate() {
+                    match &item.kind {
+                        ValueKind::Table(inner) => flatten(inner, &format!("{}[{}]", path, i), out),
+                        _ => out.push((format!("{}[{}]", path, i), render(item), source_name(item))),
+                    }
+                }
+            }
+            _ => out.push((path, render(value), source_name(value))),
+        }
+    }
+}
+
+/// Keys present in the raw layers that the parsed config did not pick up
+fn unknown_keys(table: &::config::Map<String, Value>, known: &serde_json::Value, prefix: &str, out: &mut Vec<String>) {
+    for (key, value) in table {
+        let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        let Some(known) = known.get(key) else {
+            out.push(format!("unknown key '{}' (from {})", path, source_name(value)));
+            continue;
+        };
+        match &value.kind {
+            ValueKind::Table(inner) => unknown_keys(inner, known, &path, out),
+            ValueKind::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    if let (ValueKind::Table(inner), Some(known)) = (&item.kind, known.get(i)) {
+                        unknown_keys(inner, known, &format!("{}[{}]", path, i), out);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Put back the key spellings from the files, which `config` lowercases while
+/// merging, so `[contexts.Prod]` still matches `context = "Prod"`
+fn restore_case(table: &mut ::config::Map<String, Value>, files: &[&toml::Table]) {
+    let keys: Vec<String> = table.keys().cloned().collect();
+    for key in keys {
+        let written: Vec<(&String, &toml::Value)> = files.iter()
+            .filter_map(|file| file.iter().find(|(k, _)| k.to_lowercase() == key))
+            .collect();
+        let Some(mut value) = table.remove(&key) else {
+            continue;
+        };
+        match &mut value.kind {
+            ValueKind::Table(inner) => {
+                let nested: Vec<&toml::Table> = written.iter().filter_map(|(_, v)| v.as_table()).collect();
+                restore_case(inner, &nested);
+            }
+            ValueKind::Array(items) => {
+                for (i, item) in items.iter_mut().enumerate() {
+                    if let ValueKind::Table(inner) = &mut item.kind {
+                        let nested: Vec<&toml::Table> = written.iter()
+                            .filter_map(|(_, v)| v.as_array()?.get(i)?.as_table())
+                            .collect();
+                        restore_case(inner, &nested);
+                    }
+                }
+            }
+            _ => {}
+        }
+        // The highest-precedence file wins if two spell the key differently
+        let spelling = written.last().map_or(key, |(k, _)| (*k).clone());
+        table.insert(spelling, value);
+    }
+}
+
+/// Merge all layers; `path` (from `--config`) replaces the user file
+pub fn load_config(path: Option<&str>, overrides: &Overrides) -> Result<LoadedConfig> {
+    load_from(path, std::env::current_dir().ok().as_deref(), overrides)
+}
+
+/// [`load_config`] with the project file looked up from `cwd`
+fn load_from(path: Option<&str>, cwd: Option<&Path>, overrides: &Overrides) -> Result<LoadedConfig> {
+    if let Some(path) = path {
+        if !Path::new(path).is_file() {
+            bail!("config file {} not found", path);
+        }
+    }
+    let files = layer_files(path, cwd);
+
+    let mut layers = Vec::new();
+    let mut builder = ::config::Config::builder();
+    for (name, file) in &files {
+        if let Some(file) = file {
+            builder = builder.add_source(
+                File::new(&file.to_string_lossy(), FileFormat::Toml).required(false),
+            );
+        }
+        layers.push(Layer { name, present: file.as_ref().is_some_and(|f| f.is_file()), path: file.clone() });
+    }
+
+    let env_prefix = format!("{}_", ENV_PREFIX);
+    layers.push(Layer {
+        name: "environment",
+        path: None,
+        present: std::env::vars().any(|(key, _)| key.starts_with(&env_prefix)),
     });
+    builder = builder.add_source(
+        Environment::with_prefix(ENV_PREFIX)
+            .prefix_separator("_")
+            .separator("__")
+            .try_parsing(true),
+    );
+
+    layers.push(Layer {
+        name: "command line",
+        path: None,
+        present: overrides.api_url.is_some() || overrides.context.is_some() || overrides.read_only,
+    });
+    builder = builder
+        .set_override_option("api_url", overrides.api_url.clone())?
+        .set_override_option("context", overrides.context.clone())?;
+    if overrides.read_only {
+        builder = builder.set_override("read_only", true)?;
+    }
+
+    let merged = builder.build().context("reading configuration")?;
+    let mut raw = merged.collect()?;
+    // Parsed without errors by `config` above; anything unreadable here was absent
+    let tables: Vec<toml::Table> = files.iter()
+        .filter_map(|(_, file)| std::fs::read_to_string(file.as_ref()?).ok())
+        .filter_map(|contents| contents.parse().ok())
+        .collect();
+    restore_case(&mut raw, &tables.iter().collect::<Vec<_>>());
+    let config: Config = Value::new(None, ValueKind::Table(raw.clone()))
+        .try_deserialize()
+        .context("invalid configuration")?;
 
-    match config_path {
-        Some(path) if path.exists() => {
-            let contents = std::fs::read_to_string(&path)?;
-            Ok(toml::from_str(&contents)?)
+    let mut problems = Vec::new();
+    unknown_keys(&raw, &serde_json::to_value(&config)?, "", &mut problems);
+    problems.extend(config.validate());
+    if !problems.is_empty() {
+        bail!("invalid configuration:\n  - {}", problems.join("\n  - "));
+    }
+
+    let mut values = Vec::new();
+    flatten(&raw, "", &mut values);
+    Ok(LoadedConfig { config, layers, values })
+}
+
+/// Notices edits to the config files so they can be applied without a restart
+///
+/// Files are polled by modification time rather than watched, which also
+/// catches editors that save by replacing the file and files created later.
+pub struct ConfigWatcher {
+    path: Option<String>,
+    overrides: Overrides,
+    /// Where to look for the project file; the working directory at startup
+    cwd: Option<PathBuf>,
+    stamps: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl ConfigWatcher {
+    pub fn new(path: Option<String>, overrides: Overrides) -> Self {
+        let mut watcher = Self { path, overrides, cwd: std::env::current_dir().ok(), stamps: Vec::new() };
+        watcher.stamps = watcher.stamps();
+        watcher
+    }
+
+    /// Every config file that would be read now, and when it was last modified
+    fn stamps(&self) -> Vec<(PathBuf, Option<SystemTime>)> {
+        // Resolved again each time so a project file created later is picked up
+        layer_files(self.path.as_deref(), self.cwd.as_deref())
+            .into_iter()
+            .filter_map(|(_, file)| file)
+            .map(|file| {
+                let stamp = modified(&file);
+                (file, stamp)
+            })
+            .collect()
+    }
+
+    /// Reload all layers if any file appeared, disappeared or changed since the last call
+    pub fn poll(&mut self) -> Option<Result<LoadedConfig>> {
+        let stamps = self.stamps();
+        if stamps == self.stamps {
+            return None;
         }
-        _ => Ok(Config::default()),
+        self.stamps = stamps;
+        Some(load_from(self.path.as_deref(), self.cwd.as_deref(), &self.overrides))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Load `contents` as the `--config` file
+    fn load(name: &str, contents: &str, overrides: &Overrides) -> Result<LoadedConfig> {
+        let path = std::env::temp_dir().join(format!("socp-tui-config-{}-{}.toml", name, std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        let loaded = load_config(Some(&path.to_string_lossy()), overrides);
+        std::fs::remove_file(&path).unwrap();
+        loaded
+    }
+
+    fn problems(name: &str, contents: &str) -> Vec<String> {
+        let error = load(name, contents, &Overrides::default()).err().expect("configuration accepted");
+        format!("{:#}", error).lines().skip(1).map(|l| l.trim_start_matches("  - ").to_string()).collect()
+    }
+
+    #[test]
+    fn names_keep_their_case() {
+        let contents = r#"
+            context = "Prod"
+            theme = "MyTheme"
+            [contexts.Prod]
+            api_url = "mock:"
+            theme = "MyTheme"
+            [themes.MyTheme]
+            base = "light"
+        "#;
+        let loaded = load("case", contents, &Overrides::default()).unwrap();
+        assert_eq!(loaded.config.contexts.keys().collect::<Vec<_>>(), ["Prod"]);
+        assert_eq!(loaded.config.select_context(None, None).unwrap().name, "Prod");
+        assert!(loaded.config.themes.contains_key("MyTheme"));
+        assert!(loaded.values.iter().any(|(key, _, _)| key == "contexts.Prod.api_url"));
+
+        let cli = Overrides { context: Some("Prod".to_string()), ..Overrides::default() };
+        assert_eq!(load("case-cli", contents, &cli).unwrap().config.context.as_deref(), Some("Prod"));
+        let wrong = Overrides { context: Some("prod".to_string()), ..Overrides::default() };
+        assert!(format!("{:#}", load("case-wrong", contents, &wrong).err().unwrap()).contains("no [contexts.prod] defined"));
+    }
+
+    #[test]
+    fn unknown_keys_are_errors() {
+        let contents = r#"
+            colour = "dark"
+            [dnssec]
+            warn_dayz = 3
+            [[confirm]]
+            action = "sync"
+            mode = "typed"
+            enviroments = ["production"]
+        "#;
+        let mut found = problems("unknown", contents);
+        found.sort();
+        assert_eq!(found.len(), 3, "{:?}", found);
+        assert!(found[0].starts_with("unknown key 'colour' (from "), "{}", found[0]);
+        assert!(found[1].starts_with("unknown key 'confirm[0].enviroments' (from "), "{}", found[1]);
+        assert!(found[2].starts_with("unknown key 'dnssec.warn_dayz' (from "), "{}", found[2]);
+    }
+
+    #[test]
+    fn only_the_selected_context_needs_its_certificates() {
+        let contents = r#"
+            context = "eu"
+            [contexts.eu]
+            api_url = "https://eu.example.test"
+            identity = "/nonexistent/eu.pem"
+            [contexts.us]
+            api_url = "https://us.example.test"
+            ca_cert = "/nonexistent/us-ca.pem"
+        "#;
+        assert_eq!(problems("certs", contents), ["contexts.eu.identity: /nonexistent/eu.pem does not exist"]);
+        let unselected = contents.replace("context = \"eu\"", "");
+        assert!(load("certs-unselected", &unselected, &Overrides::default()).is_ok());
+    }
+
+    #[test]
+    fn environment_overrides_files_and_flags_override_both() {
+        let contents = r#"
+            api_url = "https://file.example.test"
+            [dnssec]
+            warn_days = 10
+        "#;
+        std::env::set_var("SOCP_DNSSEC__WARN_DAYS", "5");
+        std::env::set_var("SOCP_API_URL", "https://env.example.test");
+        let from_env = load("env", contents, &Overrides::default());
+        let cli = Overrides { api_url: Some("https://cli.example.test".to_string()), ..Overrides::default() };
+        let from_cli = load("env-cli", contents, &cli);
+        std::env::remove_var("SOCP_DNSSEC__WARN_DAYS");
+        std::env::remove_var("SOCP_API_URL");
+
+        let from_env = from_env.unwrap();
+        assert_eq!(from_env.config.dnssec.warn_days, 5);
+        assert_eq!(from_env.config.api_url.as_deref(), Some("https://env.example.test"));
+        let source = |loaded: &LoadedConfig, key: &str| {
+            loaded.values.iter().find(|(k, _, _)| k == key).map(|(_, _, source)| source.clone()).unwrap()
+        };
+        assert_eq!(source(&from_env, "dnssec.warn_days"), "environment (SOCP_*)");
+
+        let from_cli = from_cli.unwrap();
+        assert_eq!(from_cli.config.api_url.as_deref(), Some("https://cli.example.test"));
+        assert_eq!(from_cli.config.dnssec.warn_days, 5);
+        assert_eq!(source(&from_cli, "api_url"), "command line");
+        assert!(from_cli.layers.iter().any(|l| l.name == "command line" && l.present));
+    }
+
+    #[test]
+    fn project_files_created_later_are_picked_up() {
+        let root = std::env::temp_dir().join(format!("socp-tui-watch-{}", std::process::id()));
+        let cwd = root.join("checkout");
+        std::fs::create_dir_all(&cwd).unwrap();
+        let user = root.join("config.toml");
+        std::fs::write(&user, "").unwrap();
+        let mut watcher = ConfigWatcher {
+            path: Some(user.to_string_lossy().into_owned()),
+            overrides: Overrides::default(),
+            cwd: Some(cwd.clone()),
+            stamps: Vec::new(),
+        };
+        watcher.stamps = watcher.stamps();
+        assert!(watcher.poll().is_none());
+
+        let project = root.join(PROJECT_FILE);
+        std::fs::write(&project, "read_only = true\n").unwrap();
+        let reloaded = watcher.poll().expect("new project file not noticed").unwrap();
+        assert!(reloaded.layers.iter().any(|l| l.name == "project" && l.present && l.path.as_ref() == Some(&project)));
+        assert!(reloaded.config.read_only);
+        assert!(watcher.poll().is_none());
+
+        std::fs::remove_file(&project).unwrap();
+        let reloaded = watcher.poll().expect("removed project file not noticed").unwrap();
+        assert!(!reloaded.config.read_only);
+        std::fs::remove_dir_all(&root).unwrap();
     }
 }
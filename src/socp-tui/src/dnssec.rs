@@ -0,0 +1,837 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! DNSSEC chain validation for managed zones
+//!
+//! Queries DS, DNSKEY and RRSIG records directly over DNS (UDP with the EDNS0
+//! DO bit, falling back to TCP on truncation) and checks that the chain of
+//! trust is present, that signatures are inside their validity window and
+//! that only strong algorithms are in use. Signature cryptography is left to
+//! the validating resolver; this is an operator health check.
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpStream, UdpSocket};
+
+use crate::app::{Alert, AlertSeverity};
+use crate::config::DnssecConfig;
+
+const TYPE_SOA: u16 = 6;
+const TYPE_DS: u16 = 43;
+const TYPE_RRSIG: u16 = 46;
+const TYPE_DNSKEY: u16 = 48;
+const TYPE_OPT: u16 = 41;
+const CLASS_IN: u16 = 1;
+const EDNS_UDP_SIZE: u16 = 1232;
+
+/// Overall DNSSEC state of a zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DnssecStatus {
+    /// Signed, DS published and signatures comfortably valid
+    Secure,
+    /// Chain intact but signatures expire inside the warning window
+    Expiring,
+    /// No DNSKEY published
+    Unsigned,
+    /// Something an external validator would reject
+    Broken,
+    /// The resolver could not be queried
+    Unreachable,
+}
+
+/// How an algorithm number rates against current guidance (RFC 8624)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlgorithmStrength {
+    Strong,
+    Deprecated,
+    Prohibited,
+    Unknown,
+}
+
+#[derive(Debug, Clone)]
+pub struct DsRecord {
+    pub key_tag: u16,
+    pub algorithm: u8,
+    pub digest_type: u8,
+}
+
+#[derive(Debug, Clone)]
+pub struct DnskeyRecord {
+    pub flags: u16,
+    pub algorithm: u8,
+    pub key_tag: u16,
+}
+
+impl DnskeyRecord {
+    pub fn is_sep(&self) -> bool {
+        self.flags & 0x0001 != 0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RrsigRecord {
+    pub type_covered: u16,
+    pub key_tag: u16,
+    pub inception: DateTime<Utc>,
+    pub expiration: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DnssecIssue {
+    /// What is wrong and with which record, e.g. "ds-sha1-12345"; stays
+    /// the same across checks so acknowledgements stick to the issue
+    pub key: String,
+    pub severity: AlertSeverity,
+    pub message: String,
+}
+
+/// Result of validating one zone
+#[derive(Debug, Clone)]
+pub struct ZoneReport {
+    pub zone: String,
+    pub checked_at: DateTime<Utc>,
+    pub status: DnssecStatus,
+    pub ds: Vec<DsRecord>,
+    pub dnskeys: Vec<DnskeyRecord>,
+    pub rrsigs: Vec<RrsigRecord>,
+    pub issues: Vec<DnssecIssue>,
+}
+
+impl ZoneReport {
+    /// Earliest signature expiry across all collected RRSIGs
+    pub fn earliest_expiry(&self) -> Option<DateTime<Utc>> {
+        self.rrsigs.iter().map(|s| s.expiration).min()
+    }
+
+    /// One-line summary for the site detail view
+    pub fn summary(&self) -> String {
+        match self.status {
+            DnssecStatus::Secure | DnssecStatus::Expiring => match self.earliest_expiry() {
+                Some(exp) => format!(
+                    "{:?} (signatures expire in {} days)",
+                    self.status,
+                    (exp - self.checked_at).num_days()
+                ),
+                None => format!("{:?}", self.status),
+            },
+            _ => match self.issues.first() {
+                Some(issue) => format!("{:?} - {}", self.status, issue.message),
+                None => format!("{:?}", self.status),
+            },
+        }
+    }
+
+    /// Convert issues into alerts attached to every site served by this zone
+    pub fn alerts(&self, site_ids: &[String]) -> Vec<Alert> {
+        site_ids
+            .iter()
+            .flat_map(|site_id| {
+                self.issues.iter().map(move |issue| Alert {
+                    id: format!("dnssec-{}-{}-{}", self.zone, site_id, issue.key),
+                    site_id: site_id.clone(),
+                    severity: issue.severity,
+                    message: issue.message.clone(),
+                    timestamp: self.checked_at,
+                    acknowledged: false,
+                })
+            })
+            .collect()
+    }
+}
+
+pub fn algorithm_name(algorithm: u8) -> &'static str {
+    match algorithm {
+        1 => "RSAMD5",
+        3 => "DSA",
+        5 => "RSASHA1",
+        6 => "DSA-NSEC3-SHA1",
+        7 => "RSASHA1-NSEC3-SHA1",
+        8 => "RSASHA256",
+        10 => "RSASHA512",
+        12 => "ECC-GOST",
+        13 => "ECDSAP256SHA256",
+        14 => "ECDSAP384SHA384",
+        15 => "ED25519",
+        16 => "ED448",
+        _ => "UNKNOWN",
+    }
+}
+
+pub fn algorithm_strength(algorithm: u8) -> AlgorithmStrength {
+    match algorithm {
+        8 | 13 | 14 | 15 | 16 => AlgorithmStrength::Strong,
+        5 | 7 | 10 => AlgorithmStrength::Deprecated,
+        1 | 3 | 6 | 12 => AlgorithmStrength::Prohibited,
+        _ => AlgorithmStrength::Unknown,
+    }
+}
+
+/// Checks zones against a configured resolver or authoritative server
+#[derive(Debug, Clone)]
+pub struct DnssecValidator {
+    resolver: SocketAddr,
+    timeout: Duration,
+    warn_days: i64,
+    critical_days: i64,
+}
+
+impl DnssecValidator {
+    /// Returns `None` when no resolver is configured
+    pub fn from_config(config: &DnssecConfig) -> Option<Self> {
+        config.resolver.map(|resolver| Self {
+            resolver,
+            timeout: Duration::from_millis(config.timeout_ms),
+            warn_days: config.warn_days,
+            critical_days: config.critical_days,
+        })
+    }
+
+    pub async fn check_zone(&self, zone: &str) -> ZoneReport {
+        let now = Utc::now();
+        match self.collect(zone).await {
+            Ok(records) => evaluate(zone, records, now, self.warn_days, self.critical_days),
+            Err(e) => ZoneReport {
+                zone: zone.to_string(),
+                checked_at: now,
+                status: DnssecStatus::Unreachable,
+                ds: Vec::new(),
+                dnskeys: Vec::new(),
+                rrsigs: Vec::new(),
+                issues: vec![DnssecIssue {
+                    key: "unreachable".to_string(),
+                    severity: AlertSeverity::Warning,
+                    message: format!("DNSSEC check for {} failed: {:#}", zone, e),
+                }],
+            },
+        }
+    }
+
+    async fn collect(&self, zone: &str) -> Result<ZoneRecords> {
+        let (ds, dnskey, soa) = tokio::try_join!(
+            self.query(zone, TYPE_DS),
+            self.query(zone, TYPE_DNSKEY),
+            self.query(zone, TYPE_SOA),
+        )?;
+        let mut records = ZoneRecords::default();
+        for (qtype, answers) in [(TYPE_DS, ds), (TYPE_DNSKEY, dnskey), (TYPE_SOA, soa)] {
+            for rr in answers {
+                match rr.rtype {
+                    TYPE_DS => records.ds.push(parse_ds(&rr.rdata)?),
+                    TYPE_DNSKEY => records.dnskeys.push(parse_dnskey(&rr.rdata)?),
+                    // The DS signature belongs to the parent zone's signer
+                    TYPE_RRSIG if qtype != TYPE_DS => records.rrsigs.push(parse_rrsig(&rr.rdata)?),
+                    _ => {}
+                }
+            }
+        }
+        Ok(records)
+    }
+
+    async fn query(&self, zone: &str, qtype: u16) -> Result<Vec<ResourceRecord>> {
+        let id = query_id()?;
+        let packet = build_query(id, zone, qtype)?;
+
+        let bind: SocketAddr = if self.resolver.is_ipv6() {
+            "[::]:0".parse()?
+        } else {
+            "0.0.0.0:0".parse()?
+        };
+        let socket = UdpSocket::bind(bind).await?;
+        socket.connect(self.resolver).await?;
+        socket.send(&packet).await?;
+
+        let mut buf = vec![0u8; 4096];
+        let len = tokio::time::timeout(self.timeout, socket.recv(&mut buf))
+            .await
+            .map_err(|_| anyhow!("timed out querying {}", self.resolver))??;
+        buf.truncate(len);
+
+        // TC bit set: retry over TCP for large DNSKEY sets
+        if buf.len() > 2 && buf[2] & 0x02 != 0 {
+            buf = tokio::time::timeout(self.timeout, self.query_tcp(&packet))
+                .await
+                .map_err(|_| anyhow!("timed out querying {} over TCP", self.resolver))??;
+        }
+
+        parse_response(&buf, id)
+    }
+
+    async fn query_tcp(&self, packet: &[u8]) -> Result<Vec<u8>> {
+        let mut stream = TcpStream::connect(self.resolver).await?;
+        stream.write_all(&(packet.len() as u16).to_be_bytes()).await?;
+        stream.write_all(packet).await?;
+        let len = stream.read_u16().await? as usize;
+        let mut buf = vec![0u8; len];
+        stream.read_exact(&mut buf).await?;
+        Ok(buf)
+    }
+}
+
+#[derive(Debug, Default)]
+struct ZoneRecords {
+    ds: Vec<DsRecord>,
+    dnskeys: Vec<DnskeyRecord>,
+    rrsigs: Vec<RrsigRecord>,
+}
+
+/// Apply the presence, expiry and algorithm checks to collected records
+fn evaluate(
+    zone: &str,
+    records: ZoneRecords,
+    now: DateTime<Utc>,
+    warn_days: i64,
+    critical_days: i64,
+) -> ZoneReport {
+    let mut issues = Vec::new();
+    let mut broken = false;
+    let mut expiring = false;
+
+    let issue = |key: String, severity, message: String| DnssecIssue { key, severity, message };
+
+    if records.dnskeys.is_empty() {
+        if records.ds.is_empty() {
+            issues.push(issue("unsigned".into(), AlertSeverity::Warning, format!("{} is not DNSSEC signed", zone)));
+        } else {
+            issues.push(issue(
+                "no-dnskey".into(),
+                AlertSeverity::Critical,
+                format!("{} has DS at parent but publishes no DNSKEY", zone),
+            ));
+            broken = true;
+        }
+        let status = if broken { DnssecStatus::Broken } else { DnssecStatus::Unsigned };
+        return ZoneReport {
+            zone: zone.to_string(),
+            checked_at: now,
+            status,
+            ds: records.ds,
+            dnskeys: records.dnskeys,
+            rrsigs: records.rrsigs,
+            issues,
+        };
+    }
+
+    // Chain of trust: parent DS must reference a published key
+    if records.ds.is_empty() {
+        issues.push(issue(
+            "no-ds".into(),
+            AlertSeverity::Warning,
+            format!("{} is signed but has no DS record at the parent", zone),
+        ));
+    } else if !records.ds.iter().any(|ds| {
+        records
+            .dnskeys
+            .iter()
+            .any(|k| k.key_tag == ds.key_tag && k.algorithm == ds.algorithm)
+    }) {
+        issues.push(issue(
+            "ds-mismatch".into(),
+            AlertSeverity::Critical,
+            format!("No DS record for {} matches a published DNSKEY", zone),
+        ));
+        broken = true;
+    }
+
+    for ds in &records.ds {
+        if ds.digest_type == 1 {
+            issues.push(issue(
+                format!("ds-sha1-{}", ds.key_tag),
+                AlertSeverity::Warning,
+                format!("DS {} for {} uses SHA-1 digest", ds.key_tag, zone),
+            ));
+        }
+    }
+
+    for key in &records.dnskeys {
+        match algorithm_strength(key.algorithm) {
+            AlgorithmStrength::Strong => {}
+            AlgorithmStrength::Deprecated => issues.push(issue(
+                format!("algorithm-{}", key.key_tag),
+                AlertSeverity::Warning,
+                format!(
+                    "DNSKEY {} for {} uses deprecated algorithm {}",
+                    key.key_tag,
+                    zone,
+                    algorithm_name(key.algorithm)
+                ),
+            )),
+            AlgorithmStrength::Prohibited | AlgorithmStrength::Unknown => {
+                issues.push(issue(
+                    format!("algorithm-{}", key.key_tag),
+                    AlertSeverity::Critical,
+                    format!(
+                        "DNSKEY {} for {} uses insecure algorithm {} ({})",
+                        key.key_tag,
+                        zone,
+                        key.algorithm,
+                        algorithm_name(key.algorithm)
+                    ),
+                ));
+                broken = true;
+            }
+        }
+    }
+
+    let dnskey_sigs: Vec<_> = records.rrsigs.iter().filter(|s| s.type_covered == TYPE_DNSKEY).collect();
+    if dnskey_sigs.is_empty() {
+        issues.push(issue("dnskey-unsigned".into(), AlertSeverity::Critical, format!("DNSKEY set for {} is not signed", zone)));
+        broken = true;
+    } else if !dnskey_sigs
+        .iter()
+        .any(|s| records.dnskeys.iter().any(|k| k.key_tag == s.key_tag))
+    {
+        issues.push(issue(
+            "dnskey-unknown-signer".into(),
+            AlertSeverity::Critical,
+            format!("DNSKEY set for {} is signed by an unpublished key", zone),
+        ));
+        broken = true;
+    }
+
+    for sig in &records.rrsigs {
+        if sig.inception > now {
+            issues.push(issue(
+                format!("not-yet-valid-{}-{}", sig.type_covered, sig.key_tag),
+                AlertSeverity::Critical,
+                format!("DNSSEC signature for {} is not valid until {}", zone, sig.inception),
+            ));
+            broken = true;
+        }
+    }
+
+    if let Some(expiration) = records.rrsigs.iter().map(|s| s.expiration).min() {
+        let days = (expiration - now).num_days();
+        if expiration <= now {
+            issues.push(issue(
+                "expired".into(),
+                AlertSeverity::Critical,
+                format!("DNSSEC signatures for {} expired on {}", zone, expiration.format("%Y-%m-%d")),
+            ));
+            broken = true;
+        } else if days < warn_days {
+            let severity = if days < critical_days {
+                AlertSeverity::Critical
+            } else {
+                AlertSeverity::Warning
+            };
+            // Turning critical is worth seeing again even if the warning was acknowledged
+            let key = if days < critical_days { "expiring-critical" } else { "expiring" };
+            issues.push(issue(
+                key.into(),
+                severity,
+                format!("DNSSEC signatures for {} expire in {} days", zone, days),
+            ));
+            expiring = true;
+        }
+    }
+
+    let status = if broken {
+        DnssecStatus::Broken
+    } else if expiring {
+        DnssecStatus::Expiring
+    } else {
+        DnssecStatus::Secure
+    };
+
+    ZoneReport {
+        zone: zone.to_string(),
+        checked_at: now,
+        status,
+        ds: records.ds,
+        dnskeys: records.dnskeys,
+        rrsigs: records.rrsigs,
+        issues,
+    }
+}
+
+// --- Wire format ---------------------------------------------------------
+
+struct ResourceRecord {
+    rtype: u16,
+    rdata: Vec<u8>,
+}
+
+/// Random so an off-path attacker cannot guess it (RFC 5452)
+fn query_id() -> Result<u16> {
+    let mut id = [0u8; 2];
+    getrandom::getrandom(&mut id).map_err(|e| anyhow!("no randomness for DNS query ID: {}", e))?;
+    Ok(u16::from_be_bytes(id))
+}
+
+fn build_query(id: u16, name: &str, qtype: u16) -> Result<Vec<u8>> {
+    let mut packet = Vec::with_capacity(64);
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&0x0100u16.to_be_bytes()); // RD
+    packet.extend_from_slice(&1u16.to_be_bytes()); // QDCOUNT
+    packet.extend_from_slice(&0u16.to_be_bytes()); // ANCOUNT
+    packet.extend_from_slice(&0u16.to_be_bytes()); // NSCOUNT
+    packet.extend_from_slice(&1u16.to_be_bytes()); // ARCOUNT (OPT)
+
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            bail!("invalid zone name: {}", name);
+        }
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    packet.extend_from_slice(&qtype.to_be_bytes());
+    packet.extend_from_slice(&CLASS_IN.to_be_bytes());
+
+    // EDNS0 OPT pseudo-record with the DO bit set
+    packet.push(0);
+    packet.extend_from_slice(&TYPE_OPT.to_be_bytes());
+    packet.extend_from_slice(&EDNS_UDP_SIZE.to_be_bytes());
+    packet.extend_from_slice(&0x0000_8000u32.to_be_bytes());
+    packet.extend_from_slice(&0u16.to_be_bytes());
+    Ok(packet)
+}
+
+fn parse_response(buf: &[u8], id: u16) -> Result<Vec<ResourceRecord>> {
+    if buf.len() < 12 {
+        bail!("short DNS response");
+    }
+    if read_u16(buf, 0)? != id {
+        bail!("DNS response ID mismatch");
+    }
+    let rcode = buf[3] & 0x0f;
+    // NXDOMAIN still carries a usable (empty) answer for our purposes
+    if rcode != 0 && rcode != 3 {
+        bail!("DNS server returned rcode {}", rcode);
+    }
+
+    let qdcount = read_u16(buf, 4)?;
+    let ancount = read_u16(buf, 6)?;
+    let mut pos = 12;
+    for _ in 0..qdcount {
+        pos = skip_name(buf, pos)? + 4;
+    }
+
+    let mut records = Vec::with_capacity(ancount as usize);
+    for _ in 0..ancount {
+        pos = skip_name(buf, pos)?;
+        let rtype = read_u16(buf, pos)?;
+        let rdlen = read_u16(buf, pos + 8)? as usize;
+        let start = pos + 10;
+        let rdata = buf
+            .get(start..start + rdlen)
+            .context("truncated DNS record")?
+            .to_vec();
+        records.push(ResourceRecord { rtype, rdata });
+        pos = start + rdlen;
+    }
+    Ok(records)
+}
+
+fn skip_name(buf: &[u8], mut pos: usize) -> Result<usize> {
+    loop {
+        let len = *buf.get(pos).context("truncated DNS name")?;
+        match len {
+            0 => return Ok(pos + 1),
+            l if l & 0xc0 == 0xc0 => return Ok(pos + 2),
+            l => pos += 1 + l as usize,
+        }
+    }
+}
+
+fn read_u16(buf: &[u8], pos: usize) -> Result<u16> {
+    let bytes = buf.get(pos..pos + 2).context("truncated DNS message")?;
+    Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(buf: &[u8], pos: usize) -> Result<u32> {
+    let bytes = buf.get(pos..pos + 4).context("truncated DNS message")?;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn parse_ds(rdata: &[u8]) -> Result<DsRecord> {
+    Ok(DsRecord {
+        key_tag: read_u16(rdata, 0)?,
+        algorithm: *rdata.get(2).context("truncated DS record")?,
+        digest_type: *rdata.get(3).context("truncated DS record")?,
+    })
+}
+
+fn parse_dnskey(rdata: &[u8]) -> Result<DnskeyRecord> {
+    Ok(DnskeyRecord {
+        flags: read_u16(rdata, 0)?,
+        algorithm: *rdata.get(3).context("truncated DNSKEY record")?,
+        key_tag: key_tag(rdata),
+    })
+}
+
+fn parse_rrsig(rdata: &[u8]) -> Result<RrsigRecord> {
+    Ok(RrsigRecord {
+        type_covered: read_u16(rdata, 0)?,
+        expiration: serial_time(read_u32(rdata, 8)?),
+        inception: serial_time(read_u32(rdata, 12)?),
+        key_tag: read_u16(rdata, 16)?,
+    })
+}
+
+/// Key tag over DNSKEY RDATA (RFC 4034, Appendix B)
+fn key_tag(rdata: &[u8]) -> u16 {
+    let mut acc: u32 = 0;
+    for (i, b) in rdata.iter().enumerate() {
+        acc += if i & 1 == 0 { (*b as u32) << 8 } else { *b as u32 };
+    }
+    acc += (acc >> 16) & 0xffff;
+    (acc & 0xffff) as u16
+}
+
+/// RRSIG timestamps are 32-bit serial numbers (RFC 4034, 3.1.5)
+fn serial_time(value: u32) -> DateTime<Utc> {
+    let now = Utc::now().timestamp();
+    let base = now - (now as u32 as i64);
+    let mut ts = base + value as i64;
+    // Pick the wrap-around epoch closest to the current time
+    if ts - now > i64::from(u32::MAX / 2) {
+        ts -= 1 << 32;
+    } else if now - ts > i64::from(u32::MAX / 2) {
+        ts += 1 << 32;
+    }
+    Utc.timestamp_opt(ts, 0).single().unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as Days;
+
+    const ZONE: &str = "example.test";
+
+    /// Records an authoritative stub serves for `ZONE`
+    #[derive(Clone, Default)]
+    struct StubZone {
+        ds: Vec<Vec<u8>>,
+        dnskeys: Vec<Vec<u8>>,
+        /// (type covered, RDATA)
+        rrsigs: Vec<(u16, Vec<u8>)>,
+    }
+
+    fn dnskey(flags: u16, algorithm: u8) -> Vec<u8> {
+        let mut rdata = flags.to_be_bytes().to_vec();
+        rdata.extend([3, algorithm]);
+        rdata.extend([0x03, 0x01, 0x00, 0x01, 0xab, 0xcd, 0xef, 0x12]);
+        rdata
+    }
+
+    fn ds(key_tag: u16, algorithm: u8, digest_type: u8) -> Vec<u8> {
+        let mut rdata = key_tag.to_be_bytes().to_vec();
+        rdata.extend([algorithm, digest_type]);
+        rdata.extend([0x5a; 32]);
+        rdata
+    }
+
+    fn rrsig(type_covered: u16, key_tag: u16, inception: DateTime<Utc>, expiration: DateTime<Utc>) -> Vec<u8> {
+        let mut rdata = type_covered.to_be_bytes().to_vec();
+        rdata.extend([13, 2]);
+        rdata.extend(3600u32.to_be_bytes());
+        rdata.extend((expiration.timestamp() as u32).to_be_bytes());
+        rdata.extend((inception.timestamp() as u32).to_be_bytes());
+        rdata.extend(key_tag.to_be_bytes());
+        rdata.extend(b"\x07example\x04test\x00");
+        rdata.extend([0x99; 64]);
+        rdata
+    }
+
+    /// A zone signed with one KSK of `algorithm` whose signatures expire in `days`
+    fn signed_zone(algorithm: u8, days: i64) -> StubZone {
+        let key = dnskey(257, algorithm);
+        let tag = key_tag(&key);
+        let now = Utc::now();
+        let (inception, expiration) = (now - Days::days(1), now + Days::days(days) + Days::hours(1));
+        StubZone {
+            ds: vec![ds(tag, algorithm, 2)],
+            dnskeys: vec![key],
+            rrsigs: vec![
+                (TYPE_DNSKEY, rrsig(TYPE_DNSKEY, tag, inception, expiration)),
+                (TYPE_SOA, rrsig(TYPE_SOA, tag, inception, expiration)),
+                // Signed by the parent; must not count towards this zone's expiry
+                (TYPE_DS, rrsig(TYPE_DS, 4242, inception, now + Days::hours(1))),
+            ],
+        }
+    }
+
+    fn answer(response: &mut Vec<u8>, rtype: u16, rdata: &[u8]) {
+        response.extend([0xc0, 0x0c]); // pointer to the question name
+        response.extend(rtype.to_be_bytes());
+        response.extend(CLASS_IN.to_be_bytes());
+        response.extend(300u32.to_be_bytes());
+        response.extend((rdata.len() as u16).to_be_bytes());
+        response.extend(rdata);
+    }
+
+    /// Answer queries for `zone` on a local UDP port until the test ends
+    async fn serve(zone: StubZone) -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            while let Ok((len, peer)) = socket.recv_from(&mut buf).await {
+                let query = &buf[..len];
+                let question_end = skip_name(query, 12).unwrap() + 4;
+                let qtype = read_u16(query, question_end - 4).unwrap();
+
+                let mut answers = Vec::new();
+                match qtype {
+                    TYPE_DS => answers.extend(zone.ds.iter().map(|r| (TYPE_DS, r.as_slice()))),
+                    TYPE_DNSKEY => answers.extend(zone.dnskeys.iter().map(|r| (TYPE_DNSKEY, r.as_slice()))),
+                    TYPE_SOA => answers.push((TYPE_SOA, &[0u8; 22][..])),
+                    _ => {}
+                }
+                answers.extend(
+                    zone.rrsigs.iter()
+                        .filter(|(covered, _)| *covered == qtype)
+                        .map(|(_, r)| (TYPE_RRSIG, r.as_slice())),
+                );
+
+                let mut response = query[..2].to_vec();
+                response.extend(0x8180u16.to_be_bytes()); // QR, RD, RA
+                response.extend(1u16.to_be_bytes());
+                response.extend((answers.len() as u16).to_be_bytes());
+                response.extend([0, 0, 0, 0]);
+                response.extend(&query[12..question_end]);
+                for (rtype, rdata) in answers {
+                    answer(&mut response, rtype, rdata);
+                }
+                let _ = socket.send_to(&response, peer).await;
+            }
+        });
+        addr
+    }
+
+    fn validator(resolver: SocketAddr) -> DnssecValidator {
+        DnssecValidator::from_config(&DnssecConfig {
+            resolver: Some(resolver),
+            timeout_ms: 1000,
+            ..DnssecConfig::default()
+        })
+        .unwrap()
+    }
+
+    async fn check(zone: StubZone) -> ZoneReport {
+        validator(serve(zone).await).check_zone(ZONE).await
+    }
+
+    #[test]
+    fn key_tag_folds_the_carry() {
+        assert_eq!(key_tag(&[0x01, 0x01, 0x03, 0x08]), 0x0409);
+        assert_eq!(key_tag(&[0xff, 0xff, 0xff, 0xff]), 0xffff);
+        assert_eq!(key_tag(&[0x80, 0x00, 0x80, 0x00, 0x00, 0x01]), 0x0002);
+    }
+
+    #[test]
+    fn parse_response_rejects_a_foreign_id() {
+        let query = build_query(0x1234, ZONE, TYPE_DNSKEY).unwrap();
+        assert!(parse_response(&query, 0x1234).unwrap().is_empty());
+        assert!(parse_response(&query, 0x4321).is_err());
+        assert!(parse_response(&query[..8], 0x1234).is_err());
+    }
+
+    #[test]
+    fn query_ids_are_not_derived_from_the_question() {
+        let ids: std::collections::HashSet<u16> = (0..16).map(|_| query_id().unwrap()).collect();
+        assert!(ids.len() > 1);
+    }
+
+    #[tokio::test]
+    async fn healthy_zone_is_secure() {
+        let report = check(signed_zone(13, 30)).await;
+        assert_eq!(report.status, DnssecStatus::Secure, "{:?}", report.issues);
+        assert!(report.issues.is_empty());
+        assert_eq!(report.dnskeys.len(), 1);
+        assert_eq!(report.dnskeys[0].key_tag, key_tag(&dnskey(257, 13)));
+        assert!(report.dnskeys[0].is_sep());
+        // Only the zone's own signatures, not the parent's DS signature
+        assert_eq!(report.rrsigs.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn expiring_signatures_warn_then_turn_critical() {
+        let report = check(signed_zone(13, 5)).await;
+        assert_eq!(report.status, DnssecStatus::Expiring);
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].severity, AlertSeverity::Warning);
+        assert!(report.issues[0].message.contains("expire in 5 days"), "{}", report.issues[0].message);
+
+        let report = check(signed_zone(13, 1)).await;
+        assert_eq!(report.status, DnssecStatus::Expiring);
+        assert_eq!(report.issues[0].severity, AlertSeverity::Critical);
+
+        let mut expired = signed_zone(13, 30);
+        let tag = key_tag(&expired.dnskeys[0]);
+        let now = Utc::now();
+        expired.rrsigs[0].1 = rrsig(TYPE_DNSKEY, tag, now - Days::days(30), now - Days::days(1));
+        let report = check(expired).await;
+        assert_eq!(report.status, DnssecStatus::Broken);
+        assert!(report.issues.iter().any(|i| i.message.contains("expired")));
+    }
+
+    #[tokio::test]
+    async fn unsupported_algorithm_breaks_the_zone() {
+        let report = check(signed_zone(1, 30)).await;
+        assert_eq!(report.status, DnssecStatus::Broken);
+        assert!(report.issues.iter().any(|i| {
+            i.severity == AlertSeverity::Critical && i.message.contains("insecure algorithm 1 (RSAMD5)")
+        }));
+
+        let report = check(signed_zone(5, 30)).await;
+        assert_eq!(report.status, DnssecStatus::Secure);
+        assert!(report.issues.iter().any(|i| {
+            i.severity == AlertSeverity::Warning && i.message.contains("deprecated algorithm RSASHA1")
+        }));
+    }
+
+    #[tokio::test]
+    async fn alert_ids_follow_the_issue_not_its_position() {
+        let sites = vec!["site-1".to_string()];
+        let zone = signed_zone(5, 30);
+        let tag = key_tag(&zone.dnskeys[0]);
+        let before = check(zone.clone()).await.alerts(&sites);
+        let algorithm = format!("dnssec-{}-site-1-algorithm-{}", ZONE, tag);
+        assert_eq!(before.iter().map(|a| a.id.as_str()).collect::<Vec<_>>(), [algorithm.as_str()]);
+
+        // A new issue listed ahead of the old one leaves the old one's ID alone
+        let mut sha1 = zone;
+        sha1.ds.insert(0, ds(tag, 5, 1));
+        let after = check(sha1).await.alerts(&sites);
+        let ids: Vec<&str> = after.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, [format!("dnssec-{}-site-1-ds-sha1-{}", ZONE, tag), algorithm.clone()]);
+        let message = |alerts: &[Alert], id: &str| alerts.iter().find(|a| a.id == id).map(|a| a.message.clone());
+        assert_eq!(message(&after, &algorithm), message(&before, &algorithm));
+    }
+
+    #[tokio::test]
+    async fn ds_must_match_a_published_key() {
+        let mut zone = signed_zone(13, 30);
+        zone.ds = vec![ds(key_tag(&zone.dnskeys[0]).wrapping_add(1), 13, 2)];
+        let report = check(zone).await;
+        assert_eq!(report.status, DnssecStatus::Broken);
+        assert!(report.issues.iter().any(|i| i.message.contains("No DS record")));
+
+        let mut zone = signed_zone(13, 30);
+        zone.dnskeys.clear();
+        let report = check(zone).await;
+        assert_eq!(report.status, DnssecStatus::Broken);
+        assert!(report.issues[0].message.contains("publishes no DNSKEY"));
+
+        let report = check(StubZone::default()).await;
+        assert_eq!(report.status, DnssecStatus::Unsigned);
+    }
+
+    #[tokio::test]
+    async fn silent_resolver_is_unreachable() {
+        let silent = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let validator = DnssecValidator::from_config(&DnssecConfig {
+            resolver: Some(silent.local_addr().unwrap()),
+            timeout_ms: 50,
+            ..DnssecConfig::default()
+        })
+        .unwrap();
+        let report = validator.check_zone(ZONE).await;
+        assert_eq!(report.status, DnssecStatus::Unreachable);
+        assert!(report.summary().contains("timed out"));
+    }
+}
@@ -39,10 +39,8 @@ pub fn new(tick_rate_ms: u64) -> Self {
                             break;
                         }
                     }
-                } else {
-                    if tx_clone.send(Event::Tick).is_err() {
-                        break;
-                    }
+                } else if tx_clone.send(Event::Tick).is_err() {
+                    break;
                 }
             }
         });
@@ -50,7 +48,7 @@ pub fn new(tick_rate_ms: u64) -> Self {
         Self { rx, _tx: tx }
     }
 
-    pub async fn next(&self) -> Result<Event> {
+    pub async fn next(&mut self) -> Result<Event> {
         // Safety: We keep _tx alive so the channel never closes
         Ok(self.rx.recv().await.expect("Event channel closed"))
     }
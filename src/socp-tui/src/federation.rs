@@ -0,0 +1,239 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Federated fleet view across several control planes
+//!
+//! Every plane is queried concurrently with its own timeout. IDs are
+//! namespaced as `<context>/<id>` so rows from different planes cannot
+//! collide, and a plane that fails keeps its last good rows, marked stale,
+//! instead of blanking the view.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinSet;
+
+use crate::app::{Alert, Deployment, Site};
+use crate::config::Context;
+use crate::plane::{self, ControlPlane};
+
+/// How long one plane may take before it counts as down
+const PLANE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Default)]
+struct PlaneData {
+    sites: Vec<Site>,
+    alerts: Vec<Alert>,
+    deployments: Vec<Deployment>,
+}
+
+pub struct Plane {
+    pub context: Context,
+    client: Arc<dyn ControlPlane>,
+    data: PlaneData,
+    /// Error from the most recent refresh
+    pub error: Option<String>,
+    pub last_success: Option<DateTime<Utc>>,
+}
+
+impl Plane {
+    /// Showing rows from an earlier refresh
+    pub fn is_stale(&self) -> bool {
+        self.error.is_some() && self.last_success.is_some()
+    }
+
+    pub fn site_count(&self) -> usize {
+        self.data.sites.len()
+    }
+}
+
+pub struct Fleet {
+    pub planes: Vec<Plane>,
+}
+
+impl Fleet {
+    pub fn new(contexts: Vec<Context>, operator: &str) -> Result<Self> {
+        let planes = contexts
+            .into_iter()
+            .map(|context| {
+                Ok(Plane {
+                    client: plane::connect(&context, operator)?,
+                    context,
+                    data: PlaneData::default(),
+                    error: None,
+                    last_success: None,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { planes })
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.planes.iter().map(|p| p.context.name.as_str()).collect()
+    }
+
+    /// Query every plane concurrently without holding on to the fleet;
+    /// failures are recorded per plane by [`Fleet::apply`]
+    pub fn fetch(&self) -> impl Future<Output = FleetResults> + Send + 'static {
+        let clients: Vec<(String, Arc<dyn ControlPlane>)> = self.planes.iter()
+            .map(|plane| (plane.context.name.clone(), Arc::clone(&plane.client)))
+            .collect();
+        async move {
+            let mut tasks = JoinSet::new();
+            for (i, (name, client)) in clients.into_iter().enumerate() {
+                tasks.spawn(async move {
+                    let result = tokio::time::timeout(PLANE_TIMEOUT, fetch_plane(client.as_ref(), &name))
+                        .await
+                        .unwrap_or_else(|_| Err(anyhow!("timed out after {}s", PLANE_TIMEOUT.as_secs())));
+                    (i, result)
+                });
+            }
+            let mut results = Vec::new();
+            while let Some(joined) = tasks.join_next().await {
+                if let Ok(result) = joined {
+                    results.push(result);
+                }
+            }
+            FleetResults(results)
+        }
+    }
+
+    /// Record what [`Fleet::fetch`] returned
+    pub fn apply(&mut self, results: FleetResults) {
+        for (i, result) in results.0 {
+            let Some(plane) = self.planes.get_mut(i) else {
+                continue;
+            };
+            match result {
+                Ok(data) => {
+                    plane.data = data;
+                    plane.error = None;
+                    plane.last_success = Some(Utc::now());
+                }
+                Err(e) => {
+                    tracing::warn!("Control plane {} unavailable: {:#}", plane.context.name, e);
+                    plane.error = Some(format!("{:#}", e));
+                }
+            }
+        }
+    }
+
+    /// ID prefix and client of every plane, to reach them without the fleet
+    pub fn clients(&self) -> Vec<(String, Arc<dyn ControlPlane>)> {
+        self.planes.iter()
+            .map(|plane| (qualify(&plane.context.name, ""), Arc::clone(&plane.client)))
+            .collect()
+    }
+
+    pub fn sites(&self) -> Vec<Site> {
+        self.planes.iter().flat_map(|p| p.data.sites.iter().cloned()).collect()
+    }
+
+    pub fn alerts(&self) -> Vec<Alert> {
+        self.planes.iter().flat_map(|p| p.data.alerts.iter().cloned()).collect()
+    }
+
+    pub fn deployments(&self) -> Vec<Deployment> {
+        self.planes.iter().flat_map(|p| p.data.deployments.iter().cloned()).collect()
+    }
+
+    /// Client and plane-local ID for a namespaced ID
+    pub fn route<'a>(&'a self, id: &'a str) -> Option<(&'a dyn ControlPlane, &'a str)> {
+        let (name, local) = id.split_once('/')?;
+        self.planes
+            .iter()
+            .find(|p| p.context.name == name)
+            .map(|p| (p.client.as_ref(), local))
+    }
+
+    pub fn is_stale(&self, id: &str) -> bool {
+        id.split_once('/')
+            .and_then(|(name, _)| self.planes.iter().find(|p| p.context.name == name))
+            .is_some_and(|p| p.error.is_some())
+    }
+}
+
+/// Per-plane outcome of one [`Fleet::fetch`]
+pub struct FleetResults(Vec<(usize, Result<PlaneData>)>);
+
+impl FleetResults {
+    /// Sites from the planes that answered
+    pub fn sites(&self) -> Vec<Site> {
+        self.0.iter()
+            .filter_map(|(_, result)| result.as_ref().ok())
+            .flat_map(|data| data.sites.iter().cloned())
+            .collect()
+    }
+}
+
+fn qualify(plane: &str, id: &str) -> String {
+    format!("{}/{}", plane, id)
+}
+
+async fn fetch_plane(client: &dyn ControlPlane, name: &str) -> Result<PlaneData> {
+    let mut sites = client.get_sites().await?;
+    let mut alerts = client.get_alerts().await?;
+    let mut deployments = client.get_pending_deployments().await?;
+
+    for site in &mut sites {
+        site.id = qualify(name, &site.id);
+        site.origin = Some(name.to_string());
+    }
+    for alert in &mut alerts {
+        alert.id = qualify(name, &alert.id);
+        alert.site_id = qualify(name, &alert.site_id);
+    }
+    for deployment in &mut deployments {
+        deployment.id = qualify(name, &deployment.id);
+        deployment.origin = Some(name.to_string());
+        for site_id in &mut deployment.sites {
+            *site_id = qualify(name, site_id);
+        }
+        for site in deployment.batches.iter_mut().flat_map(|b| b.sites.iter_mut()) {
+            site.site_id = qualify(name, &site.site_id);
+        }
+    }
+    Ok(PlaneData { sites, alerts, deployments })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(name: &str, api_url: String) -> Context {
+        Context { name: name.to_string(), api_url, identity: None, ca_cert: None, theme: None, read_only: false }
+    }
+
+    /// A plane nothing listens on
+    fn unreachable() -> String {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        format!("http://127.0.0.1:{}", port)
+    }
+
+    #[tokio::test]
+    async fn one_failing_plane_does_not_hide_the_others() {
+        let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/src/fixtures/plane.json");
+        let contexts = vec![context("eu", format!("mock:{}", fixture)), context("us", unreachable())];
+        let mut fleet = Fleet::new(contexts, "tester").unwrap();
+        fleet.apply(fleet.fetch().await);
+
+        let (eu, us) = (&fleet.planes[0], &fleet.planes[1]);
+        assert!(eu.error.is_none() && eu.last_success.is_some());
+        assert!(us.error.is_some() && us.last_success.is_none() && !us.is_stale());
+        let sites = fleet.sites();
+        assert_eq!(sites.len(), 4);
+        assert!(sites.iter().all(|s| s.id.starts_with("eu/") && s.origin.as_deref() == Some("eu")));
+        assert_eq!(fleet.alerts()[0].site_id, "eu/site-2");
+        assert_eq!(fleet.deployments()[1].batches[0].sites[0].site_id, "eu/site-1");
+        assert!(fleet.route("eu/site-1").is_some_and(|(_, id)| id == "site-1"));
+        assert!(fleet.route("ap/site-1").is_none());
+        assert!(fleet.is_stale("us/site-1") && !fleet.is_stale("eu/site-1"));
+
+        // When the good plane goes down too, its last rows stay, marked stale
+        fleet.planes[0].client = plane::connect(&context("eu", unreachable()), "tester").unwrap();
+        fleet.apply(fleet.fetch().await);
+        assert!(fleet.planes[0].is_stale());
+        assert!(fleet.is_stale("eu/site-1"));
+        assert_eq!(fleet.sites().len(), 4);
+    }
+}
@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Fixture-backed `App` and helpers for rendering tests
+//!
+//! The app is built from `fixtures/plane.json` and `fixtures/vulndb.json` with the clock frozen at
+//! [`NOW`], driven with key sequences and rendered to a `TestBackend`.
+//! Snapshots live in `src/snapshots/`; `UPDATE_SNAPSHOTS=1` rewrites them.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{backend::TestBackend, Terminal};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::app::App;
+use crate::config::Config;
+use crate::mock::{Fixture, MockPlane};
+use crate::palette::History;
+use crate::ui;
+
+/// Time the fixture app believes it is
+pub const NOW: &str = "2026-01-15T09:30:00Z";
+
+pub fn fixture() -> Fixture {
+    serde_json::from_str(include_str!("fixtures/plane.json")).expect("fixtures/plane.json")
+}
+
+/// App over a mock plane serving the fixture; `name` keeps each test's audit log apart
+pub fn app(name: &str) -> App {
+    let audit_log = std::env::temp_dir().join(format!("socp-tui-{}-{}.log", name, std::process::id()));
+    let _ = std::fs::remove_file(&audit_log);
+    let config = Config {
+        operator: Some("tester".to_string()),
+        audit_log: Some(audit_log),
+        vuln_db: Some(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/fixtures/vulndb.json")),
+        ..Config::default()
+    };
+    let context = config.select_context(None, Some("https://control.test".to_string())).unwrap();
+    let plane = Arc::new(MockPlane::new(fixture(), "tester"));
+    let mut app = App::from_data(context, &config, plane, fixture().plane).unwrap();
+    app.announce_connection();
+    app.clock = Some(NOW.parse().unwrap());
+    app.notifications.freeze_clock(app.now());
+    app.palette_history = History::load(None);
+    settle(&mut app);
+    app
+}
+
+/// Toasts expire on a timer; keep them out of the snapshots
+pub fn settle(app: &mut App) {
+    app.notifications.dismiss_toasts();
+    app.notifications.mark_seen();
+}
+
+/// One key as written in `[keys]`: "j", "enter", "ctrl+p", "f2", ...
+fn key(token: &str) -> KeyEvent {
+    let (modifiers, name) = match token.split_once('+').filter(|(_, rest)| !rest.is_empty()) {
+        Some(("ctrl", rest)) => (KeyModifiers::CONTROL, rest),
+        Some(("alt", rest)) => (KeyModifiers::ALT, rest),
+        _ => (KeyModifiers::NONE, token),
+    };
+    let code = match name {
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "space" => KeyCode::Char(' '),
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        name if name.chars().count() == 1 => KeyCode::Char(name.chars().next().unwrap()),
+        name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+            Some(n) => KeyCode::F(n),
+            None => panic!("unknown key '{}'", token),
+        },
+    };
+    KeyEvent::new(code, modifiers)
+}
+
+/// Press whitespace-separated keys, e.g. `"1 j enter"`
+pub async fn press(app: &mut App, keys: &str) {
+    for token in keys.split_whitespace() {
+        app.handle_key(key(token)).await.unwrap();
+    }
+}
+
+/// Type text into whatever has focus
+pub async fn type_text(app: &mut App, text: &str) {
+    for c in text.chars() {
+        app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)).await.unwrap();
+    }
+}
+
+/// The screen as text, trailing spaces trimmed
+pub fn render(app: &App, (width, height): (u16, u16)) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|frame| ui::draw(frame, app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..height)
+        .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect::<String>().trim_end().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+        + "\n"
+}
+
+/// Compare with `src/snapshots/<name>.txt`
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/snapshots").join(format!("{}.txt", name));
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("{}: {} (run with UPDATE_SNAPSHOTS=1 to create)", path.display(), e));
+    pretty_assertions::assert_eq!(expected, actual, "snapshot {}", name);
+}
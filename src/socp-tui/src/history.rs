@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Deployment history (mirrors `deployment-history` in STATE.scm)
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::app::DeploymentStatus;
+
+/// Number of entries the state manager retains
+pub const HISTORY_LIMIT: usize = 100;
+
+/// A finished deployment and the config change it applied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeploymentRecord {
+    pub id: String,
+    pub operator: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub change_type: String,
+    pub sites: Vec<String>,
+    pub result: DeploymentStatus,
+    /// Config hash in place before this deployment; the rollback target
+    pub previous_config_hash: Option<String>,
+    pub config_hash: String,
+    pub diff: String,
+}
+
+impl DeploymentRecord {
+    /// Rolling back needs a known prior config and a change that actually landed
+    pub fn can_roll_back(&self) -> bool {
+        self.previous_config_hash.is_some()
+            && matches!(self.result, DeploymentStatus::Completed | DeploymentStatus::Failed)
+    }
+
+    pub fn duration(&self) -> Option<chrono::Duration> {
+        self.finished_at.map(|f| f - self.started_at)
+    }
+}
@@ -0,0 +1,765 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Configurable key bindings
+//!
+//! Each view is a mode whose bindings are checked together with the global
+//! ones. A binding is a sequence of keys ("g s" is g, then s), so a key may
+//! start a chord. `[keys.<mode>]` tables in the config rebind commands;
+//! clashes are config errors, and the help screen is generated from the
+//! active keymap. Prompts and text fields keep fixed keys.
+
+use anyhow::{bail, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::app::View;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Mode {
+    Global,
+    Dashboard,
+    Sites,
+    SiteDetail,
+    WordPress,
+    Deployments,
+    History,
+    Alerts,
+    Logs,
+    Vulnerabilities,
+    Help,
+}
+
+const MODES: [Mode; 11] = [
+    Mode::Global,
+    Mode::Dashboard,
+    Mode::Sites,
+    Mode::SiteDetail,
+    Mode::WordPress,
+    Mode::Deployments,
+    Mode::History,
+    Mode::Alerts,
+    Mode::Logs,
+    Mode::Vulnerabilities,
+    Mode::Help,
+];
+
+impl Mode {
+    /// Name of the `[keys.<mode>]` table
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Global => "global",
+            Mode::Dashboard => "dashboard",
+            Mode::Sites => "sites",
+            Mode::SiteDetail => "site-detail",
+            Mode::WordPress => "wordpress",
+            Mode::Deployments => "deployments",
+            Mode::History => "history",
+            Mode::Alerts => "alerts",
+            Mode::Logs => "logs",
+            Mode::Vulnerabilities => "vulns",
+            Mode::Help => "help",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Mode::Global => "EVERYWHERE",
+            Mode::Dashboard => "DASHBOARD",
+            Mode::Sites => "SITES",
+            Mode::SiteDetail => "SITE DETAIL",
+            Mode::WordPress => "WORDPRESS",
+            Mode::Deployments => "DEPLOYMENTS",
+            Mode::History => "HISTORY",
+            Mode::Alerts => "ALERTS",
+            Mode::Logs => "LOGS",
+            Mode::Vulnerabilities => "VULNERABILITIES",
+            Mode::Help => "HELP",
+        }
+    }
+
+    /// Views with their own text entry (the composer) have no mode
+    pub fn of(view: View) -> Option<Mode> {
+        match view {
+            View::Dashboard => Some(Mode::Dashboard),
+            View::SiteList => Some(Mode::Sites),
+            View::SiteDetail => Some(Mode::SiteDetail),
+            View::WordPress => Some(Mode::WordPress),
+            View::Deployments => Some(Mode::Deployments),
+            View::History => Some(Mode::History),
+            View::Alerts => Some(Mode::Alerts),
+            View::Logs => Some(Mode::Logs),
+            View::Vulnerabilities => Some(Mode::Vulnerabilities),
+            View::Help => Some(Mode::Help),
+            View::Composer | View::Secrets => None,
+        }
+    }
+}
+
+/// Something a key can be bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Quit,
+    Close,
+    ContextPicker,
+    Palette,
+    Help,
+    Up,
+    Down,
+    Open,
+    Back,
+    Forward,
+    NextTab,
+    PrevTab,
+    TabMenu,
+    Notifications,
+    Refresh,
+    GotoSites,
+    GotoDeployments,
+    GotoHistory,
+    GotoAlerts,
+    GotoLogs,
+    GotoVulns,
+    Sync,
+    Diff,
+    WordPress,
+    Dnssec,
+    NewDeployment,
+    Pause,
+    Abort,
+    Approve,
+    Reject,
+    Rollback,
+    Acknowledge,
+    Dismiss,
+    Silence,
+    VerifyAudit,
+    Search,
+    ClearSearch,
+}
+
+const COMMANDS: [Command; 37] = [
+    Command::Quit,
+    Command::Close,
+    Command::ContextPicker,
+    Command::Palette,
+    Command::Help,
+    Command::Up,
+    Command::Down,
+    Command::Open,
+    Command::Back,
+    Command::Forward,
+    Command::NextTab,
+    Command::PrevTab,
+    Command::TabMenu,
+    Command::Notifications,
+    Command::Refresh,
+    Command::GotoSites,
+    Command::GotoDeployments,
+    Command::GotoHistory,
+    Command::GotoAlerts,
+    Command::GotoLogs,
+    Command::GotoVulns,
+    Command::Sync,
+    Command::Diff,
+    Command::WordPress,
+    Command::Dnssec,
+    Command::NewDeployment,
+    Command::Pause,
+    Command::Abort,
+    Command::Approve,
+    Command::Reject,
+    Command::Rollback,
+    Command::Acknowledge,
+    Command::Dismiss,
+    Command::Silence,
+    Command::VerifyAudit,
+    Command::Search,
+    Command::ClearSearch,
+];
+
+impl Command {
+    /// Name used in `[keys.<mode>]`
+    pub fn name(self) -> &'static str {
+        match self {
+            Command::Quit => "quit",
+            Command::Close => "close",
+            Command::ContextPicker => "context-picker",
+            Command::Palette => "palette",
+            Command::Help => "help",
+            Command::Up => "up",
+            Command::Down => "down",
+            Command::Open => "open",
+            Command::Back => "back",
+            Command::Forward => "forward",
+            Command::NextTab => "next-tab",
+            Command::PrevTab => "prev-tab",
+            Command::TabMenu => "tab-menu",
+            Command::Notifications => "notifications",
+            Command::Refresh => "refresh",
+            Command::GotoSites => "goto-sites",
+            Command::GotoDeployments => "goto-deployments",
+            Command::GotoHistory => "goto-history",
+            Command::GotoAlerts => "goto-alerts",
+            Command::GotoLogs => "goto-logs",
+            Command::GotoVulns => "goto-vulns",
+            Command::Sync => "sync",
+            Command::Diff => "diff",
+            Command::WordPress => "wordpress",
+            Command::Dnssec => "dnssec",
+            Command::NewDeployment => "new-deployment",
+            Command::Pause => "pause",
+            Command::Abort => "abort",
+            Command::Approve => "approve",
+            Command::Reject => "reject",
+            Command::Rollback => "rollback",
+            Command::Acknowledge => "acknowledge",
+            Command::Dismiss => "dismiss",
+            Command::Silence => "silence",
+            Command::VerifyAudit => "verify-audit",
+            Command::Search => "search",
+            Command::ClearSearch => "clear-search",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Command::Quit => "Quit",
+            Command::Close => "Close popup / back to dashboard (quit from dashboard)",
+            Command::ContextPicker => "Switch control plane or open the federated view",
+            Command::Palette => "Command palette (e.g. \"sync blog.example.com\")",
+            Command::Help => "Show this help",
+            Command::Up => "Move up",
+            Command::Down => "Move down",
+            Command::Open => "Open selected",
+            Command::Back => "Back to previous view",
+            Command::Forward => "Forward again",
+            Command::NextTab => "Next tab",
+            Command::PrevTab => "Previous tab",
+            Command::TabMenu => "Menu of views",
+            Command::Notifications => "Notification history",
+            Command::Refresh => "Refresh",
+            Command::GotoSites => "Sites view",
+            Command::GotoDeployments => "Deployments view",
+            Command::GotoHistory => "Deployment history",
+            Command::GotoAlerts => "Alerts view",
+            Command::GotoLogs => "Logs view",
+            Command::GotoVulns => "Vulnerabilities view",
+            Command::Sync => "Sync selected site",
+            Command::Diff => "Show config diff",
+            Command::WordPress => "WordPress inventory",
+            Command::Dnssec => "Validate DNSSEC",
+            Command::NewDeployment => "New deployment",
+            Command::Pause => "Pause / resume rollout",
+            Command::Abort => "Abort rollout",
+            Command::Approve => "Approve deployment (not your own)",
+            Command::Reject => "Reject deployment",
+            Command::Rollback => "Roll back to previous config",
+            Command::Acknowledge => "Acknowledge alert",
+            Command::Dismiss => "Dismiss alert",
+            Command::Silence => "Silence alert's site for 1h",
+            Command::VerifyAudit => "Verify audit log chain",
+            Command::Search => "Query sites by CVE or ID",
+            Command::ClearSearch => "Clear query",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        COMMANDS.into_iter().find(|c| c.name() == name)
+    }
+}
+
+/// Default bindings; a command can only be bound in the modes listed here
+const DEFAULTS: &[(Mode, Command, &[&str])] = &[
+    (Mode::Global, Command::Quit, &["ctrl+q", "ctrl+c"]),
+    (Mode::Global, Command::Close, &["esc"]),
+    (Mode::Global, Command::ContextPicker, &["C"]),
+    (Mode::Global, Command::Palette, &[":", "ctrl+p"]),
+    (Mode::Global, Command::Help, &["?", "f1"]),
+    (Mode::Global, Command::NextTab, &["tab"]),
+    (Mode::Global, Command::PrevTab, &["backtab"]),
+    (Mode::Global, Command::TabMenu, &["f2"]),
+    (Mode::Global, Command::Notifications, &["N"]),
+    (Mode::Global, Command::Back, &["alt+left"]),
+    (Mode::Global, Command::Forward, &["alt+right"]),
+    (Mode::Global, Command::GotoSites, &["1"]),
+    (Mode::Global, Command::GotoDeployments, &["2"]),
+    (Mode::Global, Command::GotoHistory, &[]),
+    (Mode::Global, Command::GotoAlerts, &["3"]),
+    (Mode::Global, Command::GotoLogs, &["4"]),
+    (Mode::Global, Command::GotoVulns, &["5"]),
+    (Mode::Dashboard, Command::GotoSites, &["s"]),
+    (Mode::Dashboard, Command::GotoDeployments, &["d"]),
+    (Mode::Dashboard, Command::GotoHistory, &["h"]),
+    (Mode::Dashboard, Command::GotoAlerts, &["a"]),
+    (Mode::Dashboard, Command::GotoLogs, &["l"]),
+    (Mode::Dashboard, Command::GotoVulns, &["v"]),
+    (Mode::Dashboard, Command::Refresh, &["r"]),
+    (Mode::Sites, Command::Up, &["up", "k"]),
+    (Mode::Sites, Command::Down, &["down", "j"]),
+    (Mode::Sites, Command::Open, &["enter"]),
+    (Mode::Sites, Command::Sync, &["s"]),
+    (Mode::SiteDetail, Command::Up, &["up", "k"]),
+    (Mode::SiteDetail, Command::Down, &["down", "j"]),
+    (Mode::SiteDetail, Command::Back, &["backspace", "b"]),
+    (Mode::SiteDetail, Command::Sync, &["s"]),
+    (Mode::SiteDetail, Command::Diff, &["c"]),
+    (Mode::SiteDetail, Command::WordPress, &["w"]),
+    (Mode::SiteDetail, Command::Dnssec, &["v"]),
+    (Mode::WordPress, Command::Up, &["up", "k"]),
+    (Mode::WordPress, Command::Down, &["down", "j"]),
+    (Mode::WordPress, Command::Back, &["backspace", "b"]),
+    (Mode::WordPress, Command::Refresh, &["r"]),
+    (Mode::Deployments, Command::Up, &["up", "k"]),
+    (Mode::Deployments, Command::Down, &["down", "j"]),
+    (Mode::Deployments, Command::NewDeployment, &["n"]),
+    (Mode::Deployments, Command::Approve, &["a"]),
+    (Mode::Deployments, Command::Reject, &["r"]),
+    (Mode::Deployments, Command::Pause, &["p"]),
+    (Mode::Deployments, Command::Abort, &["x"]),
+    (Mode::History, Command::Up, &["up", "k"]),
+    (Mode::History, Command::Down, &["down", "j"]),
+    (Mode::History, Command::Diff, &["enter", "c"]),
+    (Mode::History, Command::Rollback, &["r"]),
+    (Mode::Alerts, Command::Up, &["up", "k"]),
+    (Mode::Alerts, Command::Down, &["down", "j"]),
+    (Mode::Alerts, Command::Acknowledge, &["a"]),
+    (Mode::Alerts, Command::Dismiss, &["d"]),
+    (Mode::Alerts, Command::Silence, &["s"]),
+    (Mode::Logs, Command::Up, &["up", "k"]),
+    (Mode::Logs, Command::Down, &["down", "j"]),
+    (Mode::Logs, Command::VerifyAudit, &["v"]),
+    (Mode::Vulnerabilities, Command::Up, &["up", "k"]),
+    (Mode::Vulnerabilities, Command::Down, &["down", "j"]),
+    (Mode::Vulnerabilities, Command::Search, &["/"]),
+    (Mode::Vulnerabilities, Command::ClearSearch, &["c"]),
+    (Mode::Vulnerabilities, Command::Refresh, &["r"]),
+    (Mode::Help, Command::Back, &["q"]),
+];
+
+/// One key press, with Shift folded into the character
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub fn from_event(event: KeyEvent) -> Self {
+        let mut modifiers = event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        if let KeyCode::Char(_) | KeyCode::BackTab = event.code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self { code: event.code, modifiers }
+    }
+
+    /// "ctrl+q", "C", "?", "enter", "f1", ...
+    fn parse(text: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = text;
+        while let Some((modifier, tail)) = rest.split_once('+').filter(|(_, tail)| !tail.is_empty()) {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => bail!("unknown modifier '{}' in '{}'", modifier, text),
+            };
+            rest = tail;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => bail!("unknown key '{}'", text),
+                },
+            },
+        };
+        if modifiers.contains(KeyModifiers::SHIFT) {
+            if let KeyCode::Char(c) = code {
+                return Ok(Self { code: KeyCode::Char(c.to_ascii_uppercase()), modifiers: modifiers - KeyModifiers::SHIFT });
+            }
+        }
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        let ctrl = self.modifiers.contains(KeyModifiers::CONTROL);
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if ctrl => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            code => write!(f, "{}", code),
+        }
+    }
+}
+
+fn parse_sequence(text: &str) -> Result<Vec<Key>> {
+    let keys = text.split_whitespace().map(Key::parse).collect::<Result<Vec<_>>>()?;
+    if keys.is_empty() {
+        bail!("empty key binding");
+    }
+    Ok(keys)
+}
+
+pub fn show_sequence(keys: &[Key]) -> String {
+    keys.iter().map(Key::to_string).collect::<Vec<_>>().join(" ")
+}
+
+/// One key sequence or several
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KeySpec {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeySpec {
+    fn sequences(&self) -> Vec<&str> {
+        match self {
+            KeySpec::One(keys) => vec![keys.as_str()],
+            KeySpec::Many(keys) => keys.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+/// `[keys.<mode>]` tables: command name to key sequence(s)
+pub type KeyConfig = BTreeMap<String, BTreeMap<String, KeySpec>>;
+
+#[derive(Debug, Clone)]
+struct Binding {
+    mode: Mode,
+    command: Command,
+    keys: Vec<Key>,
+}
+
+/// Result of feeding the keys pressed so far to the keymap
+pub enum Lookup {
+    Command(Command),
+    /// The keys start a longer binding
+    Pending,
+    Unbound,
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&KeyConfig::new()).expect("default key bindings are valid")
+    }
+}
+
+impl Keymap {
+    /// Defaults with the config's bindings applied; errors list every problem
+    pub fn new(config: &KeyConfig) -> std::result::Result<Self, Vec<String>> {
+        let mut bindings: Vec<Binding> = DEFAULTS.iter()
+            .flat_map(|(mode, command, keys)| {
+                keys.iter().map(|keys| Binding {
+                    mode: *mode,
+                    command: *command,
+                    keys: parse_sequence(keys).expect("default key bindings parse"),
+                })
+            })
+            .collect();
+
+        let mut problems = Vec::new();
+        for (mode_name, table) in config {
+            let Some(mode) = MODES.into_iter().find(|m| m.name() == mode_name) else {
+                let known: Vec<&str> = MODES.iter().map(|m| m.name()).collect();
+                problems.push(format!("keys.{}: unknown mode (expected one of {})", mode_name, known.join(", ")));
+                continue;
+            };
+            for (command_name, spec) in table {
+                let key = format!("keys.{}.{}", mode_name, command_name);
+                let Some(command) = Command::from_name(command_name) else {
+                    problems.push(format!("{}: unknown command", key));
+                    continue;
+                };
+                if !DEFAULTS.iter().any(|(m, c, _)| *m == mode && *c == command) {
+                    problems.push(format!("{}: {} is not available in {} mode", key, command_name, mode_name));
+                    continue;
+                }
+                bindings.retain(|b| b.mode != mode || b.command != command);
+                for sequence in spec.sequences() {
+                    match parse_sequence(sequence) {
+                        Ok(keys) => bindings.push(Binding { mode, command, keys }),
+                        Err(e) => problems.push(format!("{}: {}", key, e)),
+                    }
+                }
+            }
+        }
+
+        let keymap = Self { bindings };
+        problems.extend(keymap.conflicts());
+        if problems.is_empty() {
+            Ok(keymap)
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Bindings that are equal to, or a prefix of, another binding active at the same time
+    fn conflicts(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (i, a) in self.bindings.iter().enumerate() {
+            for b in &self.bindings[i + 1..] {
+                let overlap = a.mode == b.mode || a.mode == Mode::Global || b.mode == Mode::Global;
+                let same = a.command == b.command && a.keys == b.keys;
+                if !overlap || same || !(a.keys.starts_with(&b.keys) || b.keys.starts_with(&a.keys)) {
+                    continue;
+                }
+                problems.push(format!(
+                    "keys: '{}' ({} in {} mode) clashes with '{}' ({} in {} mode)",
+                    show_sequence(&a.keys),
+                    a.command.name(),
+                    a.mode.name(),
+                    show_sequence(&b.keys),
+                    b.command.name(),
+                    b.mode.name(),
+                ));
+            }
+        }
+        problems
+    }
+
+    /// What `keys` pressed in `mode` (global bindings only when `None`) do
+    pub fn lookup(&self, mode: Option<Mode>, keys: &[Key]) -> Lookup {
+        let mut pending = false;
+        for binding in self.bindings.iter().filter(|b| b.mode == Mode::Global || Some(b.mode) == mode) {
+            if binding.keys == keys {
+                return Lookup::Command(binding.command);
+            }
+            pending |= binding.keys.starts_with(keys);
+        }
+        if pending {
+            Lookup::Pending
+        } else {
+            Lookup::Unbound
+        }
+    }
+
+    /// First key sequence for `command` in `mode` (or globally), for inline hints
+    pub fn key_for(&self, mode: Mode, command: Command) -> Option<String> {
+        self.bindings.iter()
+            .find(|b| b.command == command && (b.mode == mode || b.mode == Mode::Global))
+            .map(|b| show_sequence(&b.keys))
+    }
+
+    /// Help sections: mode title and (keys, description) rows
+    pub fn help(&self) -> Vec<(&'static str, Vec<(String, &'static str)>)> {
+        MODES.into_iter()
+            .filter_map(|mode| {
+                let mut rows: Vec<(Command, Vec<String>)> = Vec::new();
+                for binding in self.bindings.iter().filter(|b| b.mode == mode) {
+                    let keys = show_sequence(&binding.keys);
+                    match rows.iter_mut().find(|(c, _)| *c == binding.command) {
+                        Some((_, all)) => all.push(keys),
+                        None => rows.push((binding.command, vec![keys])),
+                    }
+                }
+                let rows: Vec<(String, &'static str)> = rows.into_iter()
+                    .map(|(command, keys)| (keys.join(" / "), command.description()))
+                    .collect();
+                (!rows.is_empty()).then(|| (mode.title(), rows))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Key {
+        Key { code, modifiers }
+    }
+
+    fn keys(text: &str) -> Vec<Key> {
+        parse_sequence(text).unwrap()
+    }
+
+    /// Mode, command and key sequences, as in `[keys.<mode>]`
+    type Bindings<'a> = &'a [(&'a str, &'a str, &'a [&'a str])];
+
+    fn config(bindings: Bindings) -> KeyConfig {
+        let mut config = KeyConfig::new();
+        for (mode, command, sequences) in bindings {
+            let spec = KeySpec::Many(sequences.iter().map(|s| s.to_string()).collect());
+            config.entry(mode.to_string()).or_default().insert(command.to_string(), spec);
+        }
+        config
+    }
+
+    #[test]
+    fn keys_parse_with_modifiers() {
+        let (none, ctrl, alt) = (KeyModifiers::NONE, KeyModifiers::CONTROL, KeyModifiers::ALT);
+        let cases = [
+            ("q", key(KeyCode::Char('q'), none)),
+            ("C", key(KeyCode::Char('C'), none)),
+            ("ctrl+q", key(KeyCode::Char('q'), ctrl)),
+            ("Control+q", key(KeyCode::Char('q'), ctrl)),
+            ("ctrl+alt+x", key(KeyCode::Char('x'), ctrl | alt)),
+            // Shift is folded into characters but kept on other keys
+            ("shift+a", key(KeyCode::Char('A'), none)),
+            ("shift+ctrl+a", key(KeyCode::Char('A'), ctrl)),
+            ("shift+tab", key(KeyCode::Tab, KeyModifiers::SHIFT)),
+            ("+", key(KeyCode::Char('+'), none)),
+            ("ctrl++", key(KeyCode::Char('+'), ctrl)),
+            ("space", key(KeyCode::Char(' '), none)),
+            ("Enter", key(KeyCode::Enter, none)),
+            ("alt+left", key(KeyCode::Left, alt)),
+            ("f1", key(KeyCode::F(1), none)),
+            ("F12", key(KeyCode::F(12), none)),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(Key::parse(text).unwrap(), expected, "{}", text);
+        }
+
+        let errors = [
+            ("f0", "unknown key 'f0'"),
+            ("f13", "unknown key 'f13'"),
+            ("ctrl+enterr", "unknown key 'ctrl+enterr'"),
+            ("hyper+a", "unknown modifier 'hyper' in 'hyper+a'"),
+            ("", "unknown key ''"),
+        ];
+        for (text, expected) in errors {
+            assert_eq!(Key::parse(text).unwrap_err().to_string(), expected, "{}", text);
+        }
+        assert_eq!(parse_sequence(" ").unwrap_err().to_string(), "empty key binding");
+    }
+
+    #[test]
+    fn events_match_parsed_keys() {
+        let cases = [
+            (KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT), "A"),
+            (KeyEvent::new(KeyCode::Char('?'), KeyModifiers::SHIFT), "?"),
+            (KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT), "backtab"),
+            (KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL), "ctrl+p"),
+            (KeyEvent::new(KeyCode::Tab, KeyModifiers::SHIFT), "shift+tab"),
+            (KeyEvent::new(KeyCode::F(2), KeyModifiers::NONE), "f2"),
+        ];
+        for (event, text) in cases {
+            assert_eq!(Key::from_event(event), Key::parse(text).unwrap(), "{}", text);
+        }
+        assert_eq!(show_sequence(&keys("ctrl+q g space backtab")), "Ctrl+Q g Space Shift+Tab");
+    }
+
+    #[test]
+    fn clashing_bindings_are_config_errors() {
+        assert!(Keymap::new(&KeyConfig::new()).is_ok());
+        let cases: [(Bindings, &[&str]); 6] = [
+            // The same key in two views is fine; they are never active together
+            (&[("deployments", "approve", &["s"])], &[]),
+            (&[("sites", "sync", &["s", "s"])], &[]),
+            (
+                &[("sites", "sync", &["j x"])],
+                &["keys: 'j' (down in sites mode) clashes with 'j x' (sync in sites mode)"],
+            ),
+            (
+                &[("sites", "sync", &["1"])],
+                &["keys: '1' (goto-sites in global mode) clashes with '1' (sync in sites mode)"],
+            ),
+            (
+                &[("global", "help", &["g"]), ("alerts", "silence", &["g s"])],
+                &["keys: 'g s' (silence in alerts mode) clashes with 'g' (help in global mode)"],
+            ),
+            (
+                &[("sites", "sync", &["k"]), ("sites", "frobnicate", &["z"]), ("alerts", "sync", &["z"])],
+                &[
+                    "keys.alerts.sync: sync is not available in alerts mode",
+                    "keys.sites.frobnicate: unknown command",
+                    "keys: 'k' (up in sites mode) clashes with 'k' (sync in sites mode)",
+                ],
+            ),
+        ];
+        for (bindings, expected) in cases {
+            let problems = Keymap::new(&config(bindings)).err().unwrap_or_default();
+            assert_eq!(problems, expected, "{:?}", bindings);
+        }
+    }
+
+    #[test]
+    fn chords_wait_for_their_last_key() {
+        let keymap = Keymap::new(&config(&[
+            ("dashboard", "goto-sites", &["g s"]),
+            ("dashboard", "goto-deployments", &["g d"]),
+        ]))
+        .unwrap();
+        let lookup = |mode, text: &str| keymap.lookup(mode, &keys(text));
+        assert!(matches!(lookup(Some(Mode::Dashboard), "g"), Lookup::Pending));
+        assert!(matches!(lookup(Some(Mode::Dashboard), "g s"), Lookup::Command(Command::GotoSites)));
+        assert!(matches!(lookup(Some(Mode::Dashboard), "g d"), Lookup::Command(Command::GotoDeployments)));
+        assert!(matches!(lookup(Some(Mode::Dashboard), "g x"), Lookup::Unbound));
+        // Rebinding replaces the default
+        assert!(matches!(lookup(Some(Mode::Dashboard), "s"), Lookup::Unbound));
+        // Chords of one view are not live in others, and global bindings are live everywhere
+        assert!(matches!(lookup(Some(Mode::Sites), "g"), Lookup::Unbound));
+        assert!(matches!(lookup(None, "g"), Lookup::Unbound));
+        assert!(matches!(lookup(Some(Mode::Dashboard), "1"), Lookup::Command(Command::GotoSites)));
+        assert!(matches!(lookup(None, "ctrl+q"), Lookup::Command(Command::Quit)));
+    }
+
+    #[test]
+    fn help_lists_every_binding() {
+        let keymap = Keymap::new(&config(&[
+            ("dashboard", "goto-sites", &["g s", "S"]),
+            ("global", "goto-history", &["6"]),
+        ]))
+        .unwrap();
+        let help = keymap.help();
+        let section = |title: &str| help.iter().find(|(t, _)| *t == title).map(|(_, rows)| rows.clone()).unwrap();
+
+        let titles: Vec<&str> = help.iter().map(|(title, _)| *title).collect();
+        assert_eq!(titles, MODES.map(Mode::title));
+        let shown: usize = help.iter().flat_map(|(_, rows)| rows).map(|(keys, _)| keys.split(" / ").count()).sum();
+        assert_eq!(shown, keymap.bindings.len());
+
+        assert!(section("EVERYWHERE").contains(&("6".to_string(), "Deployment history")));
+        assert!(section("EVERYWHERE").contains(&("? / F1".to_string(), "Show this help")));
+        assert!(section("DASHBOARD").contains(&("g s / S".to_string(), "Sites view")));
+        assert!(section("SITES").contains(&("↑ / k".to_string(), "Move up")));
+        for (mode, command, _) in DEFAULTS {
+            if let Some(keys) = keymap.key_for(*mode, *command) {
+                assert!(help.iter().flat_map(|(_, rows)| rows).any(|(shown, _)| shown.starts_with(&keys)), "{}", keys);
+            }
+        }
+    }
+}
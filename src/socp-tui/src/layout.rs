@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Screen layout settings
+//!
+//! `[layout]` arranges the dashboard from panels, one list per column, and
+//! sets how wide the terminal must be before the site list and site detail
+//! are shown side by side. Tables and the header also adapt to the width
+//! through fixed breakpoints.
+
+use serde::{Deserialize, Serialize};
+
+/// Something the dashboard can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Panel {
+    /// Site, deployment and alert counts
+    Overview,
+    /// Sites that are not healthy
+    NeedsAttention,
+    /// Latest audit log entries; plane status in the federated view
+    Activity,
+    /// Certificates closest to expiry
+    ExpiringCerts,
+    PendingDeployments,
+    /// Alerts per hour over the last day
+    AlertHistogram,
+    /// Latest notifications, newest first
+    Notifications,
+}
+
+impl Panel {
+    pub fn title(self) -> &'static str {
+        match self {
+            Panel::Overview => "Overview",
+            Panel::NeedsAttention => "Needs Attention",
+            Panel::Activity => "Recent Activity",
+            Panel::ExpiringCerts => "Expiring Certificates",
+            Panel::PendingDeployments => "Pending Deployments",
+            Panel::AlertHistogram => "Alerts (24h)",
+            Panel::Notifications => "Notifications",
+        }
+    }
+
+    /// Rows the panel needs, borders included; `None` shares what is left
+    pub fn height(self) -> Option<u16> {
+        match self {
+            Panel::Overview => Some(7),
+            Panel::AlertHistogram => Some(8),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// Dashboard columns, left to right, each a list of panels top to bottom
+    pub dashboard: Vec<Vec<Panel>>,
+    /// Show site list and detail side by side from this many columns; 0 never does
+    pub split_min_width: u16,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            dashboard: vec![
+                vec![Panel::Overview, Panel::Activity],
+                vec![Panel::NeedsAttention],
+            ],
+            split_min_width: 160,
+        }
+    }
+}
+
+impl LayoutConfig {
+    pub fn check(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.dashboard.is_empty() {
+            problems.push("layout.dashboard: needs at least one column".to_string());
+        }
+        for (i, column) in self.dashboard.iter().enumerate() {
+            if column.is_empty() {
+                problems.push(format!("layout.dashboard[{}]: empty column", i));
+            }
+        }
+        problems
+    }
+}
+
+/// Width classes tables and the header adapt to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Breakpoint {
+    /// Under 100 columns, e.g. an 80x24 SSH session: only essential columns
+    Narrow,
+    /// Under 140 columns: abbreviated timestamps
+    Medium,
+    Wide,
+}
+
+impl Breakpoint {
+    pub fn of(width: u16) -> Self {
+        match width {
+            0..=99 => Breakpoint::Narrow,
+            100..=139 => Breakpoint::Medium,
+            _ => Breakpoint::Wide,
+        }
+    }
+
+    /// Timestamp format for table cells
+    pub fn time_format(self) -> &'static str {
+        match self {
+            Breakpoint::Wide => "%Y-%m-%d %H:%M",
+            _ => "%m-%d %H:%M",
+        }
+    }
+}
@@ -9,9 +9,31 @@
 mod ui;
 mod config;
 mod events;
+mod dnssec;
+mod wordpress;
+mod vulndb;
+mod rollout;
+mod history;
+mod composer;
+mod maintenance;
+mod mock;
+mod notify;
+mod palette;
+mod plane;
+mod audit;
+mod federation;
+#[cfg(test)]
+mod fixtures;
+mod keymap;
+mod layout;
+mod policy;
+mod rbac;
+mod refresh;
+mod repo;
+mod theme;
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
@@ -27,10 +49,21 @@
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// Control plane API endpoint
-    #[arg(short, long, env = "SOCP_API_URL")]
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Control plane API endpoint; `mock:` or `repo:<path>` for offline data (SOCP_API_URL sets `api_url` in config)
+    #[arg(short, long)]
     api_url: Option<String>,
 
+    /// Named control plane from the config file
+    #[arg(long, conflicts_with = "api_url")]
+    context: Option<String>,
+
+    /// Aggregate several contexts (comma-separated; all when empty) into one read-only view
+    #[arg(long, value_delimiter = ',', num_args = 0.., conflicts_with = "context")]
+    federate: Option<Vec<String>>,
+
     /// Path to configuration file
     #[arg(short, long)]
     config: Option<String>,
@@ -38,6 +71,29 @@ struct Args {
     /// Enable debug logging
     #[arg(short, long)]
     debug: bool,
+
+    /// Browse only: disable every action that changes the control plane
+    #[arg(long)]
+    read_only: bool,
+
+    /// Verify the local audit log hash chain and exit
+    #[arg(long)]
+    verify_audit: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Inspect the layered configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigAction {
+    /// Print the effective configuration and where each value came from
+    Show,
 }
 
 #[tokio::main]
@@ -53,24 +109,47 @@ async fn main() -> Result<()> {
         .init();
 
     // Load configuration
-    let config = config::load_config(args.config.as_deref())?;
-    let api_url = args.api_url.or(config.api_url).unwrap_or_else(|| {
-        "https://[::1]:8443".to_string()
-    });
+    let overrides = config::Overrides {
+        api_url: args.api_url.clone(),
+        context: args.context.clone(),
+        read_only: args.read_only,
+    };
+    let loaded = config::load_config(args.config.as_deref(), &overrides)?;
+    if let Some(Command::Config { action: ConfigAction::Show }) = args.command {
+        print!("{}", loaded.show());
+        return Ok(());
+    }
+    let watcher = config::ConfigWatcher::new(args.config.clone(), overrides);
+    let config = loaded.config;
+
+    if args.verify_audit {
+        let path = config.audit_log.clone()
+            .or_else(audit::AuditLog::default_path)
+            .ok_or_else(|| anyhow::anyhow!("no audit log location configured"))?;
+        let verification = audit::verify(&path)?;
+        println!("{}: {}", path.display(), verification.summary());
+        std::process::exit(if verification.is_intact() { 0 } else { 1 });
+    }
+    let context = config.select_context(args.context.as_deref(), args.api_url)?;
+
+    // Create app state before taking over the terminal, so a startup error is
+    // printed to a usable shell
+    let mut app = App::new(context, &config).await?;
+    if let Some(names) = &args.federate {
+        app.federate(names).await?;
+    }
+    app.config_watcher = Some(watcher);
 
     // Initialize terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
-
-    // Create app state
-    let mut app = App::new(&api_url).await?;
-    let event_handler = EventHandler::new(250);
+    let mut event_handler = EventHandler::new(250);
 
     // Main loop
-    let result = run_app(&mut terminal, &mut app, &event_handler).await;
+    let result = run_app(&mut terminal, &mut app, &mut event_handler).await;
 
     // Restore terminal
     disable_raw_mode()?;
@@ -92,9 +171,19 @@ async fn main() -> Result<()> {
 async fn run_app<B: Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
-    event_handler: &EventHandler,
+    event_handler: &mut EventHandler,
 ) -> Result<()> {
+    let mut mouse_captured = false;
     loop {
+        // Follows `mouse` in the config, including after a reload
+        if app.mouse != mouse_captured {
+            if app.mouse {
+                execute!(io::stdout(), EnableMouseCapture)?;
+            } else {
+                execute!(io::stdout(), DisableMouseCapture)?;
+            }
+            mouse_captured = app.mouse;
+        }
         terminal.draw(|frame| ui::draw(frame, app))?;
 
         match event_handler.next().await? {
@@ -107,7 +196,7 @@ async fn run_app<B: Backend>(
                 }
             }
             events::Event::Mouse(mouse) => {
-                app.handle_mouse(mouse)?;
+                app.handle_mouse(mouse).await?;
             }
             events::Event::Resize(width, height) => {
                 app.handle_resize(width, height)?;
@@ -0,0 +1,270 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Maintenance windows and change freezes
+//!
+//! Windows are recurring weekly slots (UTC) during which changes are allowed;
+//! a site covered by at least one window may only change inside one. Freezes
+//! are absolute periods during which covered sites may not change at all.
+//! Both are scoped by environment and/or tag; an empty scope covers nothing.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Timelike, Utc, Weekday};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::app::Site;
+
+/// Which sites a window or freeze applies to
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scope {
+    pub environments: Vec<String>,
+    pub tags: Vec<String>,
+}
+
+impl Scope {
+    pub fn covers(&self, site: &Site) -> bool {
+        self.environments.contains(&site.environment)
+            || self.tags.iter().any(|t| site.tags.contains(t))
+    }
+
+    pub fn describe(&self) -> String {
+        self.environments
+            .iter()
+            .map(|e| format!("env:{}", e))
+            .chain(self.tags.iter().map(|t| format!("tag:{}", t)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    pub name: String,
+    #[serde(flatten)]
+    pub scope: Scope,
+    #[serde(deserialize_with = "deserialize_weekdays")]
+    pub days: Vec<Weekday>,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub start: NaiveTime,
+    /// May be earlier than `start` for windows that cross midnight
+    #[serde(deserialize_with = "deserialize_time")]
+    pub end: NaiveTime,
+}
+
+impl MaintenanceWindow {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let time = at.time();
+        if self.start <= self.end {
+            self.days.contains(&at.weekday()) && time >= self.start && time < self.end
+        } else {
+            // Overnight: starts on a listed day, ends the following morning
+            (self.days.contains(&at.weekday()) && time >= self.start)
+                || (self.days.contains(&at.weekday().pred()) && time < self.end)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FreezePeriod {
+    pub name: String,
+    #[serde(flatten)]
+    pub scope: Scope,
+    pub from: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+}
+
+impl FreezePeriod {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        at >= self.from && at < self.until
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChangeCalendar {
+    pub windows: Vec<MaintenanceWindow>,
+    pub freezes: Vec<FreezePeriod>,
+}
+
+impl ChangeCalendar {
+    pub fn merge(&mut self, other: ChangeCalendar) {
+        self.windows.extend(other.windows);
+        self.freezes.extend(other.freezes);
+    }
+
+    /// Why a change to `site` at `at` is not allowed, if it isn't
+    pub fn blocked(&self, site: &Site, at: DateTime<Utc>) -> Option<String> {
+        if let Some(freeze) = self.freezes.iter().find(|f| f.scope.covers(site) && f.contains(at)) {
+            return Some(format!(
+                "{} is frozen by '{}' until {}",
+                site.domain,
+                freeze.name,
+                freeze.until.format("%Y-%m-%d %H:%M UTC")
+            ));
+        }
+
+        let windows: Vec<&MaintenanceWindow> =
+            self.windows.iter().filter(|w| w.scope.covers(site)).collect();
+        if !windows.is_empty() && !windows.iter().any(|w| w.contains(at)) {
+            let names: Vec<&str> = windows.iter().map(|w| w.name.as_str()).collect();
+            return Some(format!(
+                "{} is outside its maintenance window ({})",
+                site.domain,
+                names.join(", ")
+            ));
+        }
+        None
+    }
+
+    /// First reason any of `sites` is blocked at `at`
+    pub fn blocked_any<'a>(&self, sites: impl IntoIterator<Item = &'a Site>, at: DateTime<Utc>) -> Option<String> {
+        sites.into_iter().find_map(|site| self.blocked(site, at))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty() && self.freezes.is_empty()
+    }
+}
+
+/// Number of slots per day in the timeline strip
+pub const SLOTS_PER_DAY: u32 = 8;
+
+/// Render `days` worth of slots for a predicate, starting at the current slot
+pub fn timeline(start: DateTime<Utc>, days: u32, active: impl Fn(DateTime<Utc>) -> bool) -> String {
+    let slot_hours = 24 / SLOTS_PER_DAY;
+    let origin = start
+        .with_hour(start.hour() - start.hour() % slot_hours)
+        .and_then(|t| t.with_minute(0))
+        .and_then(|t| t.with_second(0))
+        .unwrap_or(start);
+    (0..days * SLOTS_PER_DAY)
+        .map(|slot| {
+            let slot_start = origin + Duration::hours((slot * slot_hours) as i64);
+            // Sample the middle of the slot so short windows still show
+            if active(slot_start + Duration::minutes(slot_hours as i64 * 30)) || active(slot_start) {
+                '█'
+            } else {
+                '·'
+            }
+        })
+        .collect()
+}
+
+pub fn parse_weekday(value: &str) -> Result<Weekday> {
+    match value.trim().to_ascii_lowercase().get(..3) {
+        Some("mon") => Ok(Weekday::Mon),
+        Some("tue") => Ok(Weekday::Tue),
+        Some("wed") => Ok(Weekday::Wed),
+        Some("thu") => Ok(Weekday::Thu),
+        Some("fri") => Ok(Weekday::Fri),
+        Some("sat") => Ok(Weekday::Sat),
+        Some("sun") => Ok(Weekday::Sun),
+        _ => bail!("invalid weekday: {}", value),
+    }
+}
+
+fn deserialize_weekdays<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Weekday>, D::Error> {
+    let raw = Vec::<String>::deserialize(deserializer)?;
+    raw.iter()
+        .map(|d| parse_weekday(d).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+/// Accepts "HH:MM" as well as "HH:MM:SS"
+fn deserialize_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+    let raw = String::deserialize(deserializer)?;
+    NaiveTime::parse_from_str(&raw, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(&raw, "%H:%M:%S"))
+        .map_err(|_| serde::de::Error::custom(format!("invalid time: {}", raw)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::fixture;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    fn window(days: &[Weekday], start: &str, end: &str) -> MaintenanceWindow {
+        MaintenanceWindow {
+            name: "nightly".to_string(),
+            scope: Scope { environments: vec!["production".to_string()], tags: Vec::new() },
+            days: days.to_vec(),
+            start: start.parse().unwrap(),
+            end: end.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn overnight_windows_run_into_the_next_day() {
+        // 2026-01-16 is a Friday
+        let friday_night = window(&[Weekday::Fri], "22:00:00", "04:00:00");
+        let cases = [
+            ("2026-01-16T21:59:00Z", false),
+            ("2026-01-16T22:00:00Z", true),
+            ("2026-01-16T23:30:00Z", true),
+            ("2026-01-17T03:59:00Z", true),
+            ("2026-01-17T04:00:00Z", false),
+            ("2026-01-17T23:00:00Z", false),
+            ("2026-01-18T03:00:00Z", false),
+            ("2026-01-16T03:00:00Z", false),
+        ];
+        for (time, expected) in cases {
+            assert_eq!(friday_night.contains(at(time)), expected, "{}", time);
+        }
+
+        let same_day = window(&[Weekday::Sat], "02:00:00", "06:00:00");
+        assert!(same_day.contains(at("2026-01-17T02:00:00Z")));
+        assert!(!same_day.contains(at("2026-01-17T06:00:00Z")));
+        assert!(!same_day.contains(at("2026-01-18T03:00:00Z")));
+    }
+
+    #[test]
+    fn freezes_win_over_open_windows() {
+        let mut calendar = fixture().plane.calendar;
+        let sites = fixture().plane.sites;
+        let (example, staging, shop) = (&sites[0], &sites[2], &sites[3]);
+        // Saturday 03:00 is inside the weekend window
+        let saturday = at("2026-01-17T03:00:00Z");
+        let thursday = at("2026-01-15T09:30:00Z");
+
+        assert_eq!(calendar.blocked(example, saturday), None);
+        assert_eq!(calendar.blocked(staging, thursday), None);
+        assert_eq!(
+            calendar.blocked(shop, thursday).as_deref(),
+            Some("shop.example.com is outside its maintenance window (weekend)")
+        );
+        assert_eq!(
+            calendar.blocked(example, at("2026-01-19T03:00:00Z")).as_deref(),
+            Some("example.com is frozen by 'launch' until 2026-01-20 00:00 UTC")
+        );
+
+        calendar.merge(ChangeCalendar {
+            windows: Vec::new(),
+            freezes: vec![FreezePeriod {
+                name: "sale".to_string(),
+                scope: Scope { environments: vec!["production".to_string()], tags: Vec::new() },
+                from: at("2026-01-17T00:00:00Z"),
+                until: at("2026-01-18T00:00:00Z"),
+            }],
+        });
+        assert_eq!(
+            calendar.blocked(shop, saturday).as_deref(),
+            Some("shop.example.com is frozen by 'sale' until 2026-01-18 00:00 UTC")
+        );
+        // The freeze has ended and Sunday's window is open
+        assert_eq!(calendar.blocked(shop, at("2026-01-18T03:00:00Z")), None);
+        assert!(calendar.blocked_any([staging, shop], saturday).is_some());
+        assert_eq!(calendar.blocked_any([staging], saturday), None);
+    }
+
+    #[test]
+    fn empty_scopes_cover_nothing() {
+        let site = &fixture().plane.sites[0];
+        assert!(!Scope::default().covers(site));
+        let unscoped = MaintenanceWindow { scope: Scope::default(), ..window(&[Weekday::Mon], "00:00:00", "01:00:00") };
+        let calendar = ChangeCalendar { windows: vec![unscoped], freezes: Vec::new() };
+        assert_eq!(calendar.blocked(site, at("2026-01-15T09:30:00Z")), None);
+    }
+}
@@ -0,0 +1,584 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! In-memory control plane for demos and tests
+//!
+//! Serves built-in demo data (`mock:`) or a JSON fixture (`mock:<path>`)
+//! shaped like the API responses, with WordPress inventories under
+//! `wordpress` keyed by site ID. Actions change the data in memory, so a
+//! refresh shows their effect, and are refused with a 403 like the server
+//! would when the fixture's grants do not cover them.
+
+use anyhow::{bail, Context as _, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::api::ApiError;
+use crate::app::{Alert, AlertSeverity, Approval, Deployment, DeploymentStatus, PlaneData, Site, SiteStatus};
+use crate::composer::{DeploymentRequest, FieldError, SiteGroup};
+use crate::history::DeploymentRecord;
+use crate::maintenance::{ChangeCalendar, FreezePeriod, MaintenanceWindow, Scope};
+use crate::plane::ControlPlane;
+use crate::policy::Action;
+use crate::rbac::{Grant, Permissions};
+use crate::rollout::{BatchStatus, RolloutPolicy, RolloutStrategy, SiteRolloutStatus};
+use crate::wordpress::{CoreInfo, DeclaredPlugins, PluginInfo, PluginState, ThemeInfo, WordPressInventory};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Fixture {
+    #[serde(flatten)]
+    pub plane: PlaneData,
+    pub wordpress: HashMap<String, WordPressInventory>,
+}
+
+pub struct MockPlane {
+    operator: String,
+    data: Mutex<Fixture>,
+}
+
+impl MockPlane {
+    pub fn new(fixture: Fixture, operator: &str) -> Self {
+        Self {
+            operator: operator.to_string(),
+            data: Mutex::new(fixture),
+        }
+    }
+
+    pub fn load(path: &Path, operator: &str) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("reading fixture {}", path.display()))?;
+        let fixture = serde_json::from_str(&contents)
+            .with_context(|| format!("parsing fixture {}", path.display()))?;
+        Ok(Self::new(fixture, operator))
+    }
+
+    pub fn demo(operator: &str) -> Self {
+        Self::new(demo(), operator)
+    }
+
+    fn data(&self) -> MutexGuard<'_, Fixture> {
+        self.data.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn set_status(&self, deployment_id: &str, action: Action, status: DeploymentStatus) -> Result<()> {
+        let mut data = self.data();
+        authorize(&data.plane, action, &deployment_sites(&data.plane, deployment_id)?)?;
+        let Some(deployment) = data.plane.pending_deployments.iter_mut().find(|d| d.id == deployment_id) else {
+            bail!("no deployment {}", deployment_id);
+        };
+        tracing::info!("Deployment {} is now {:?}", deployment_id, status);
+        deployment.status = status;
+        Ok(())
+    }
+}
+
+/// Refuse like the control plane when the fixture's grants do not cover `action`
+fn authorize(plane: &PlaneData, action: Action, site_ids: &[String]) -> Result<()> {
+    let sites: Vec<&Site> = plane.sites.iter().filter(|s| site_ids.contains(&s.id)).collect();
+    match plane.permissions.denied(action, &sites) {
+        Some(reason) => Err(ApiError::PermissionDenied(reason).into()),
+        None => Ok(()),
+    }
+}
+
+fn deployment_sites(plane: &PlaneData, deployment_id: &str) -> Result<Vec<String>> {
+    match plane.pending_deployments.iter().find(|d| d.id == deployment_id) {
+        Some(deployment) => Ok(deployment.sites.clone()),
+        None => bail!("no deployment {}", deployment_id),
+    }
+}
+
+fn alert_site(plane: &PlaneData, alert_id: &str) -> Vec<String> {
+    plane.alerts.iter().filter(|a| a.id == alert_id).map(|a| a.site_id.clone()).collect()
+}
+
+/// The checks the control plane applies to a deployment request
+fn validate(request: &DeploymentRequest) -> Result<()> {
+    let mut errors = Vec::new();
+    if request.sites.is_empty() {
+        errors.push(FieldError {
+            field: "sites".to_string(),
+            message: "no sites match the selected targets".to_string(),
+        });
+    }
+    if request.change_type.is_empty() {
+        errors.push(FieldError {
+            field: "change_type".to_string(),
+            message: "change type is required".to_string(),
+        });
+    }
+    if request.scheduled.is_some_and(|t| t < chrono::Utc::now()) {
+        errors.push(FieldError {
+            field: "scheduled".to_string(),
+            message: "schedule is in the past".to_string(),
+        });
+    }
+    if !errors.is_empty() {
+        return Err(ApiError::Validation(errors).into());
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl ControlPlane for MockPlane {
+    async fn get_permissions(&self) -> Result<Permissions> {
+        Ok(self.data().plane.permissions.clone())
+    }
+
+    async fn get_sites(&self) -> Result<Vec<Site>> {
+        Ok(self.data().plane.sites.clone())
+    }
+
+    async fn get_alerts(&self) -> Result<Vec<Alert>> {
+        Ok(self.data().plane.alerts.clone())
+    }
+
+    async fn get_pending_deployments(&self) -> Result<Vec<Deployment>> {
+        Ok(self.data().plane.pending_deployments.clone())
+    }
+
+    async fn get_groups(&self) -> Result<Vec<SiteGroup>> {
+        Ok(self.data().plane.groups.clone())
+    }
+
+    async fn get_change_calendar(&self) -> Result<ChangeCalendar> {
+        Ok(self.data().plane.calendar.clone())
+    }
+
+    async fn get_deployment_history(&self, limit: usize) -> Result<Vec<DeploymentRecord>> {
+        Ok(self.data().plane.deployment_history.iter().take(limit).cloned().collect())
+    }
+
+    async fn get_config_diff(&self, site_id: &str) -> Result<String> {
+        Ok(format!(
+            r#"--- a/sites/{}/config.ncl
++++ b/sites/{}/config.ncl
+@@ -15,7 +15,7 @@
+   security = {{
+     headers = {{
+-      x_frame_options = 'SAMEORIGIN,
++      x_frame_options = 'DENY,
+       content_security_policy = "default-src 'self'",
+     }},
+   }},
+"#,
+            site_id, site_id
+        ))
+    }
+
+    async fn get_wordpress_inventory(&self, site_id: &str) -> Result<Option<WordPressInventory>> {
+        Ok(self.data().wordpress.get(site_id).cloned())
+    }
+
+    async fn sync_site(&self, site_id: &str, override_reason: Option<&str>) -> Result<()> {
+        let mut data = self.data();
+        authorize(&data.plane, Action::Sync, &[site_id.to_string()])?;
+        let Some(site) = data.plane.sites.iter_mut().find(|s| s.id == site_id) else {
+            bail!("no site {}", site_id);
+        };
+        tracing::info!("Syncing site: {} (override: {:?})", site_id, override_reason);
+        site.last_sync = Some(chrono::Utc::now());
+        Ok(())
+    }
+
+    async fn silence_site(&self, site_id: &str, duration_secs: u64) -> Result<chrono::DateTime<chrono::Utc>> {
+        authorize(&self.data().plane, Action::Silence, &[site_id.to_string()])?;
+        tracing::info!("Silencing site {} for {}s", site_id, duration_secs);
+        Ok(chrono::Utc::now() + chrono::Duration::seconds(duration_secs as i64))
+    }
+
+    async fn acknowledge_alert(&self, alert_id: &str) -> Result<()> {
+        let mut data = self.data();
+        authorize(&data.plane, Action::Acknowledge, &alert_site(&data.plane, alert_id))?;
+        let Some(alert) = data.plane.alerts.iter_mut().find(|a| a.id == alert_id) else {
+            bail!("no alert {}", alert_id);
+        };
+        alert.acknowledged = true;
+        Ok(())
+    }
+
+    async fn dismiss_alert(&self, alert_id: &str) -> Result<()> {
+        let mut data = self.data();
+        authorize(&data.plane, Action::Dismiss, &alert_site(&data.plane, alert_id))?;
+        let before = data.plane.alerts.len();
+        data.plane.alerts.retain(|a| a.id != alert_id);
+        if data.plane.alerts.len() == before {
+            bail!("no alert {}", alert_id);
+        }
+        Ok(())
+    }
+
+    async fn create_deployment(&self, request: &DeploymentRequest) -> Result<Deployment> {
+        validate(request)?;
+        authorize(&self.data().plane, Action::CreateDeployment, &request.sites)?;
+        tracing::info!("Creating deployment {} for {:?}", request.change_type, request.sites);
+        let deployment = Deployment {
+            id: format!("deploy-{}", chrono::Utc::now().timestamp()),
+            sites: request.sites.clone(),
+            change_type: request.change_type.clone(),
+            scheduled: request.scheduled,
+            status: DeploymentStatus::Pending,
+            created_by: self.operator.clone(),
+            required_approvals: 0,
+            approvals: Vec::new(),
+            rollout: request.rollout.clone(),
+            batches: Vec::new(),
+            origin: None,
+        };
+        self.data().plane.pending_deployments.push(deployment.clone());
+        Ok(deployment)
+    }
+
+    async fn preview_deployment(&self, request: &DeploymentRequest) -> Result<String> {
+        validate(request)?;
+        if let Some(preview) = request.verbatim_preview() {
+            return Ok(preview);
+        }
+        let mut preview = String::new();
+        for site_id in &request.sites {
+            preview.push_str(&format!("# {} on {}\n", request.change_type, site_id));
+            preview.push_str(&self.get_config_diff(site_id).await?);
+        }
+        Ok(preview)
+    }
+
+    async fn approve_deployment(&self, deployment_id: &str, override_reason: Option<&str>) -> Result<Approval> {
+        let mut data = self.data();
+        authorize(&data.plane, Action::Approve, &deployment_sites(&data.plane, deployment_id)?)?;
+        let Some(deployment) = data.plane.pending_deployments.iter_mut().find(|d| d.id == deployment_id) else {
+            bail!("no deployment {}", deployment_id);
+        };
+        tracing::info!("Approving deployment: {} (override: {:?})", deployment_id, override_reason);
+        let approval = Approval {
+            operator: self.operator.clone(),
+            at: chrono::Utc::now(),
+        };
+        deployment.approvals.push(approval.clone());
+        Ok(approval)
+    }
+
+    async fn reject_deployment(&self, deployment_id: &str) -> Result<()> {
+        self.set_status(deployment_id, Action::Reject, DeploymentStatus::Rejected)
+    }
+
+    async fn pause_deployment(&self, deployment_id: &str) -> Result<()> {
+        self.set_status(deployment_id, Action::Pause, DeploymentStatus::Paused)
+    }
+
+    async fn resume_deployment(&self, deployment_id: &str) -> Result<()> {
+        self.set_status(deployment_id, Action::Resume, DeploymentStatus::InProgress)
+    }
+
+    async fn abort_deployment(&self, deployment_id: &str) -> Result<()> {
+        self.set_status(deployment_id, Action::Abort, DeploymentStatus::Aborted)
+    }
+
+    async fn rollback_deployment(&self, deployment_id: &str) -> Result<()> {
+        self.set_status(deployment_id, Action::Rollback, DeploymentStatus::RolledBack)
+    }
+
+    async fn create_rollback(&self, record: &DeploymentRecord, config_hash: &str) -> Result<Deployment> {
+        authorize(&self.data().plane, Action::Rollback, &record.sites)?;
+        tracing::info!("Rolling back {} to {}", record.id, config_hash);
+        let deployment = Deployment {
+            id: format!("rollback-{}", record.id),
+            sites: record.sites.clone(),
+            change_type: format!("rollback {} to {}", record.change_type, config_hash),
+            scheduled: None,
+            status: DeploymentStatus::Pending,
+            created_by: self.operator.clone(),
+            required_approvals: 0,
+            approvals: Vec::new(),
+            rollout: RolloutPolicy::default(),
+            batches: Vec::new(),
+            origin: None,
+        };
+        self.data().plane.pending_deployments.push(deployment.clone());
+        Ok(deployment)
+    }
+}
+
+/// Demo fleet, timestamped relative to now
+fn demo() -> Fixture {
+    let now = chrono::Utc::now();
+    let grant = |actions: &[&str], environments: &[&str]| Grant {
+        actions: actions.iter().map(|a| a.to_string()).collect(),
+        sites: Vec::new(),
+        environments: environments.iter().map(|e| e.to_string()).collect(),
+    };
+    let permissions = Permissions {
+        role: Some("operator".to_string()),
+        views: None,
+        grants: Some(vec![
+            grant(&["*"], &["staging"]),
+            grant(
+                &["sync", "approve", "reject", "pause", "resume", "create-deployment", "acknowledge", "dismiss", "silence"],
+                &["production"],
+            ),
+        ]),
+    };
+
+    let sites = vec![
+        Site {
+            id: "site-1".to_string(),
+            domain: "example.com".to_string(),
+            status: SiteStatus::Healthy,
+            last_sync: Some(now),
+            config_hash: Some("sha256:abc123".to_string()),
+            response_time_ms: Some(145),
+            ssl_expires: Some(now + chrono::Duration::days(60)),
+            tags: vec!["production".to_string(), "wordpress".to_string()],
+            environment: "production".to_string(),
+            dns_zone: None,
+            origin: None,
+        },
+        Site {
+            id: "site-2".to_string(),
+            domain: "blog.example.com".to_string(),
+            status: SiteStatus::Warning,
+            last_sync: Some(now - chrono::Duration::hours(2)),
+            config_hash: Some("sha256:def456".to_string()),
+            response_time_ms: Some(523),
+            ssl_expires: Some(now + chrono::Duration::days(15)),
+            tags: vec!["production".to_string(), "wordpress".to_string()],
+            environment: "production".to_string(),
+            dns_zone: Some("example.com".to_string()),
+            origin: None,
+        },
+        Site {
+            id: "site-3".to_string(),
+            domain: "staging.example.com".to_string(),
+            status: SiteStatus::Drifted,
+            last_sync: Some(now - chrono::Duration::days(1)),
+            config_hash: Some("sha256:ghi789".to_string()),
+            response_time_ms: Some(89),
+            ssl_expires: Some(now + chrono::Duration::days(90)),
+            tags: vec!["staging".to_string()],
+            environment: "staging".to_string(),
+            dns_zone: Some("example.com".to_string()),
+            origin: None,
+        },
+    ];
+
+    let alerts = vec![
+        Alert {
+            id: "alert-1".to_string(),
+            site_id: "site-2".to_string(),
+            severity: AlertSeverity::Warning,
+            message: "SSL certificate expires in 15 days".to_string(),
+            timestamp: now,
+            acknowledged: false,
+        },
+        Alert {
+            id: "alert-2".to_string(),
+            site_id: "site-2".to_string(),
+            severity: AlertSeverity::Warning,
+            message: "Response time > 500ms".to_string(),
+            timestamp: now - chrono::Duration::hours(1),
+            acknowledged: false,
+        },
+    ];
+
+    let opcache_sites = vec!["site-3".to_string(), "site-1".to_string(), "site-2".to_string()];
+    let opcache_policy = RolloutPolicy {
+        strategy: RolloutStrategy::Canary,
+        ..RolloutPolicy::default()
+    };
+    let mut opcache_batches = opcache_policy.plan(&opcache_sites);
+    opcache_batches[0].status = BatchStatus::Completed;
+    opcache_batches[0].started_at = Some(now - chrono::Duration::minutes(12));
+    opcache_batches[0].completed_at = Some(now - chrono::Duration::minutes(10));
+    opcache_batches[0].sites[0].status = SiteRolloutStatus::Succeeded;
+    let pending_deployments = vec![
+        Deployment {
+            id: "deploy-1".to_string(),
+            sites: vec!["site-1".to_string(), "site-2".to_string()],
+            change_type: "security-headers-update".to_string(),
+            scheduled: Some(now + chrono::Duration::hours(6)),
+            status: DeploymentStatus::Pending,
+            created_by: "bob".to_string(),
+            required_approvals: 1,
+            approvals: Vec::new(),
+            rollout: RolloutPolicy::default(),
+            batches: Vec::new(),
+            origin: None,
+        },
+        Deployment {
+            id: "deploy-2".to_string(),
+            sites: opcache_sites,
+            change_type: "php-opcache-tuning".to_string(),
+            scheduled: None,
+            status: DeploymentStatus::Paused,
+            created_by: "alice".to_string(),
+            required_approvals: 1,
+            approvals: vec![Approval {
+                operator: "carol".to_string(),
+                at: now - chrono::Duration::minutes(20),
+            }],
+            rollout: opcache_policy,
+            batches: opcache_batches,
+            origin: None,
+        },
+    ];
+
+    let groups = vec![
+        SiteGroup {
+            id: "production".to_string(),
+            sites: vec!["site-1".to_string(), "site-2".to_string()],
+        },
+        SiteGroup {
+            id: "staging".to_string(),
+            sites: vec!["site-3".to_string()],
+        },
+    ];
+
+    let calendar = ChangeCalendar {
+        windows: vec![MaintenanceWindow {
+            name: "prod-nightly".to_string(),
+            scope: Scope {
+                environments: vec!["production".to_string()],
+                tags: Vec::new(),
+            },
+            days: vec![
+                chrono::Weekday::Mon,
+                chrono::Weekday::Tue,
+                chrono::Weekday::Wed,
+                chrono::Weekday::Thu,
+                chrono::Weekday::Fri,
+                chrono::Weekday::Sat,
+                chrono::Weekday::Sun,
+            ],
+            start: chrono::NaiveTime::from_hms_opt(1, 0, 0).unwrap_or_default(),
+            end: chrono::NaiveTime::from_hms_opt(5, 0, 0).unwrap_or_default(),
+        }],
+        freezes: vec![FreezePeriod {
+            name: "quarter-end".to_string(),
+            scope: Scope {
+                environments: Vec::new(),
+                tags: vec!["wordpress".to_string()],
+            },
+            from: now + chrono::Duration::days(3),
+            until: now + chrono::Duration::days(5),
+        }],
+    };
+
+    let deployment_history = vec![
+        DeploymentRecord {
+            id: "deploy-0042".to_string(),
+            operator: "alice".to_string(),
+            started_at: now - chrono::Duration::days(1),
+            finished_at: Some(now - chrono::Duration::days(1) + chrono::Duration::minutes(14)),
+            change_type: "csp-tighten".to_string(),
+            sites: vec!["site-1".to_string(), "site-2".to_string()],
+            result: DeploymentStatus::Completed,
+            previous_config_hash: Some("sha256:9f1e22".to_string()),
+            config_hash: "sha256:abc123".to_string(),
+            diff: r#"--- a/includes/security-headers-strict.ncl
++++ b/includes/security-headers-strict.ncl
+@@ -4,3 +4,3 @@
+-  content_security_policy = "default-src 'self' 'unsafe-inline'",
++  content_security_policy = "default-src 'self'",
+"#.to_string(),
+        },
+        DeploymentRecord {
+            id: "deploy-0041".to_string(),
+            operator: "bob".to_string(),
+            started_at: now - chrono::Duration::days(3),
+            finished_at: Some(now - chrono::Duration::days(3) + chrono::Duration::minutes(6)),
+            change_type: "php-8.3-upgrade".to_string(),
+            sites: vec!["site-3".to_string()],
+            result: DeploymentStatus::RolledBack,
+            previous_config_hash: Some("sha256:77aa01".to_string()),
+            config_hash: "sha256:e0c4d9".to_string(),
+            diff: r#"--- a/sites/site-3/config.ncl
++++ b/sites/site-3/config.ncl
+@@ -22,3 +22,3 @@
+   php = {
+-    version = "8.2",
++    version = "8.3",
+"#.to_string(),
+        },
+    ];
+
+    let plugin = |slug: &str, version: &str, update: Option<&str>, state| PluginInfo {
+        slug: slug.to_string(),
+        version: version.to_string(),
+        update_version: update.map(str::to_string),
+        state,
+    };
+    let declared = DeclaredPlugins {
+        must_use: vec!["query-monitor".to_string(), "wp-mail-smtp".to_string()],
+        active: vec!["yoast-seo".to_string(), "woocommerce".to_string()],
+        inactive: vec!["hello-dolly".to_string()],
+        blocklist: vec!["wp-file-manager".to_string()],
+    };
+    let wordpress = [
+        WordPressInventory {
+            site_id: "site-1".to_string(),
+            core: CoreInfo {
+                version: "6.7.1".to_string(),
+                update_version: None,
+                auto_update: "minor".to_string(),
+            },
+            plugins: vec![
+                plugin("query-monitor", "3.17.0", None, PluginState::MustUse),
+                plugin("wp-mail-smtp", "4.3.0", None, PluginState::MustUse),
+                plugin("yoast-seo", "24.1", Some("24.2"), PluginState::Active),
+                plugin("woocommerce", "9.5.1", None, PluginState::Active),
+                plugin("hello-dolly", "1.7.2", None, PluginState::Inactive),
+            ],
+            themes: vec![ThemeInfo {
+                slug: "twentytwentyfive".to_string(),
+                version: "1.0".to_string(),
+                update_version: None,
+                active: true,
+            }],
+            declared: declared.clone(),
+        },
+        WordPressInventory {
+            site_id: "site-2".to_string(),
+            core: CoreInfo {
+                version: "6.6.2".to_string(),
+                update_version: Some("6.7.1".to_string()),
+                auto_update: "minor".to_string(),
+            },
+            plugins: vec![
+                plugin("query-monitor", "3.16.4", Some("3.17.0"), PluginState::MustUse),
+                plugin("yoast-seo", "23.9", Some("24.2"), PluginState::Active),
+                plugin("woocommerce", "9.4.3", Some("9.5.1"), PluginState::Inactive),
+                plugin("hello-dolly", "1.7.2", None, PluginState::Active),
+                plugin("wp-file-manager", "6.0", Some("8.0.1"), PluginState::Active),
+            ],
+            themes: vec![
+                ThemeInfo {
+                    slug: "astra".to_string(),
+                    version: "4.8.6".to_string(),
+                    update_version: Some("4.8.10".to_string()),
+                    active: true,
+                },
+                ThemeInfo {
+                    slug: "twentytwentyfour".to_string(),
+                    version: "1.2".to_string(),
+                    update_version: Some("1.3".to_string()),
+                    active: false,
+                },
+            ],
+            declared,
+        },
+    ];
+
+    Fixture {
+        plane: PlaneData {
+            sites,
+            alerts,
+            pending_deployments,
+            groups,
+            calendar,
+            deployment_history,
+            permissions,
+        },
+        wordpress: wordpress.into_iter().map(|inventory| (inventory.site_id.clone(), inventory)).collect(),
+    }
+}
@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Operator notifications
+//!
+//! Every message is kept in a bounded history for the notification center.
+//! New ones also show as toasts until they expire, longer for more severe
+//! ones; a message repeating the newest one bumps its count instead.
+
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Notifications kept for the notification center
+const HISTORY_LIMIT: usize = 200;
+
+/// Toasts shown at once, newest first
+pub const MAX_TOASTS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Level {
+    /// How long the toast stays up
+    fn lifetime(self) -> Duration {
+        match self {
+            Level::Info | Level::Success => Duration::from_secs(4),
+            Level::Warning => Duration::from_secs(8),
+            Level::Error => Duration::from_secs(15),
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            Level::Info => "i",
+            Level::Success => "✓",
+            Level::Warning => "!",
+            Level::Error => "✗",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub level: Level,
+    pub message: String,
+    pub at: DateTime<Utc>,
+    /// Times the same message arrived in a row
+    pub count: u32,
+    expires: Instant,
+}
+
+#[derive(Debug, Default)]
+pub struct Notifications {
+    /// Oldest first
+    entries: VecDeque<Notification>,
+    /// Entries not yet seen in the notification center
+    unseen: usize,
+    /// Fixed time to stamp entries with; `None` follows the system clock
+    clock: Option<DateTime<Utc>>,
+}
+
+impl Notifications {
+    pub fn push(&mut self, level: Level, message: String) {
+        let expires = Instant::now() + level.lifetime();
+        let at = self.clock.unwrap_or_else(Utc::now);
+        if let Some(last) = self.entries.back_mut().filter(|n| n.level == level && n.message == message) {
+            last.count += 1;
+            last.at = at;
+            last.expires = expires;
+            return;
+        }
+        self.entries.push_back(Notification { level, message, at, count: 1, expires });
+        if self.entries.len() > HISTORY_LIMIT {
+            self.entries.pop_front();
+        }
+        self.unseen = (self.unseen + 1).min(self.entries.len());
+    }
+
+    /// Unexpired notifications, newest first
+    pub fn toasts(&self) -> impl Iterator<Item = &Notification> {
+        let now = Instant::now();
+        self.entries.iter().rev().filter(move |n| n.expires > now).take(MAX_TOASTS)
+    }
+
+    /// Hide every toast; the history keeps them
+    pub fn dismiss_toasts(&mut self) {
+        let now = Instant::now();
+        for entry in &mut self.entries {
+            entry.expires = entry.expires.min(now);
+        }
+    }
+
+    /// Everything kept, newest first
+    pub fn history(&self) -> impl Iterator<Item = &Notification> {
+        self.entries.iter().rev()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn unseen(&self) -> usize {
+        self.unseen
+    }
+
+    pub fn mark_seen(&mut self) {
+        self.unseen = 0;
+    }
+
+    /// Stamp every entry, kept and future, with `at`
+    #[cfg(test)]
+    pub fn freeze_clock(&mut self, at: DateTime<Utc>) {
+        self.clock = Some(at);
+        for entry in &mut self.entries {
+            entry.at = at;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.unseen = 0;
+    }
+}
@@ -0,0 +1,455 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Command palette (`:` or Ctrl-P)
+//!
+//! The first word names a verb and later words complete from the current
+//! sites, alerts, deployments, views or contexts. Suggestions are ranked by
+//! fuzzy match, but a line only runs if each argument is exact or matches
+//! just one thing, so "silence blog 1h" is enough to silence a site while a
+//! typo cannot act on the wrong one. Recent commands are kept across sessions.
+
+use anyhow::{anyhow, bail, Result};
+use std::path::{Path, PathBuf};
+
+use crate::app::{App, View};
+use crate::rollout::parse_duration;
+
+/// How many recent commands are kept
+const HISTORY_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Arg {
+    Site,
+    Alert,
+    Deployment,
+    View,
+    Context,
+    Duration,
+}
+
+impl Arg {
+    fn name(self) -> &'static str {
+        match self {
+            Arg::Site => "site",
+            Arg::Alert => "alert",
+            Arg::Deployment => "deployment",
+            Arg::View => "view",
+            Arg::Context => "context",
+            Arg::Duration => "duration",
+        }
+    }
+}
+
+struct Verb {
+    name: &'static str,
+    args: &'static [Arg],
+    description: &'static str,
+}
+
+const VERBS: &[Verb] = &[
+    Verb { name: "goto", args: &[Arg::View], description: "Open a view" },
+    Verb { name: "sync", args: &[Arg::Site], description: "Sync a site" },
+    Verb { name: "diff", args: &[Arg::Site], description: "Show a site's config diff" },
+    Verb { name: "silence", args: &[Arg::Site, Arg::Duration], description: "Silence a site's alerts" },
+    Verb { name: "acknowledge", args: &[Arg::Alert], description: "Acknowledge an alert" },
+    Verb { name: "dismiss", args: &[Arg::Alert], description: "Dismiss an alert" },
+    Verb { name: "approve", args: &[Arg::Deployment], description: "Approve a deployment" },
+    Verb { name: "reject", args: &[Arg::Deployment], description: "Reject a deployment" },
+    Verb { name: "pause", args: &[Arg::Deployment], description: "Pause or resume a rollout" },
+    Verb { name: "abort", args: &[Arg::Deployment], description: "Abort a rollout" },
+    Verb { name: "context", args: &[Arg::Context], description: "Switch control plane" },
+    Verb { name: "refresh", args: &[], description: "Refresh data" },
+    Verb { name: "help", args: &[], description: "Show key bindings" },
+    Verb { name: "quit", args: &[], description: "Quit" },
+];
+
+const VIEWS: [(&str, View); 8] = [
+    ("dashboard", View::Dashboard),
+    ("sites", View::SiteList),
+    ("deployments", View::Deployments),
+    ("history", View::History),
+    ("alerts", View::Alerts),
+    ("logs", View::Logs),
+    ("vulns", View::Vulnerabilities),
+    ("help", View::Help),
+];
+
+const DURATIONS: [&str; 6] = ["15m", "30m", "1h", "4h", "8h", "1d"];
+
+/// A parsed palette line with its arguments resolved to IDs
+#[derive(Debug, Clone)]
+pub enum Invocation {
+    Goto(View),
+    Sync { site_id: String },
+    Diff { site_id: String },
+    Silence { site_id: String, duration_secs: u64 },
+    Acknowledge { alert_id: String },
+    Dismiss { alert_id: String },
+    Approve { deployment_id: String },
+    Reject { deployment_id: String },
+    Pause { deployment_id: String },
+    Abort { deployment_id: String },
+    Context(String),
+    Refresh,
+    Help,
+    Quit,
+}
+
+/// Case-insensitive subsequence match, higher is better; runs of
+/// consecutive characters and matches at word starts score extra
+pub fn fuzzy_score(pattern: &str, candidate: &str) -> Option<i32> {
+    let chars: Vec<char> = candidate.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+    for p in pattern.chars().flat_map(char::to_lowercase) {
+        let found = (next..chars.len()).find(|&i| chars[i] == p)?;
+        score += 10;
+        if previous.is_some_and(|prev| prev + 1 == found) {
+            score += 15;
+        }
+        if found == 0 || !chars[found - 1].is_alphanumeric() {
+            score += 10;
+        }
+        score -= (found - next).min(10) as i32;
+        previous = Some(found);
+        next = found + 1;
+    }
+    Some(score - chars.len() as i32 / 4)
+}
+
+fn verb(word: &str) -> Option<&'static Verb> {
+    VERBS.iter().find(|v| v.name.eq_ignore_ascii_case(word))
+}
+
+/// Argument value the palette inserts, a detail column, and extra text to match on
+struct Candidate {
+    value: String,
+    detail: String,
+    aliases: Vec<String>,
+}
+
+impl Candidate {
+    fn haystack(&self) -> String {
+        std::iter::once(&self.value).chain(&self.aliases).cloned().collect::<Vec<_>>().join(" ")
+    }
+}
+
+fn candidates(arg: Arg, app: &App) -> Vec<Candidate> {
+    match arg {
+        Arg::Site => app.sites.iter()
+            .map(|s| Candidate {
+                value: s.domain.clone(),
+                detail: format!("{} ({})", s.id, s.environment),
+                aliases: vec![s.id.clone()],
+            })
+            .collect(),
+        Arg::Alert => app.alerts.iter()
+            .map(|a| Candidate { value: a.id.clone(), detail: a.message.clone(), aliases: vec![a.site_id.clone()] })
+            .collect(),
+        Arg::Deployment => app.pending_deployments.iter()
+            .map(|d| Candidate {
+                value: d.id.clone(),
+                detail: format!("{} ({:?})", d.change_type, d.status),
+                aliases: Vec::new(),
+            })
+            .collect(),
+        Arg::View => VIEWS.iter()
+            .filter(|(_, view)| app.can_view(*view))
+            .map(|(name, _)| Candidate { value: name.to_string(), detail: String::new(), aliases: Vec::new() })
+            .collect(),
+        Arg::Context => app.contexts.iter()
+            .map(|c| Candidate { value: c.name.clone(), detail: c.api_url.clone(), aliases: Vec::new() })
+            .collect(),
+        Arg::Duration => DURATIONS.iter()
+            .map(|d| Candidate { value: d.to_string(), detail: String::new(), aliases: Vec::new() })
+            .collect(),
+    }
+}
+
+/// The ID behind `word`: an exact value or alias first, else the only fuzzy match
+fn resolve(arg: Arg, word: &str, app: &App) -> Result<String> {
+    let candidates = candidates(arg, app);
+    let exact = candidates.iter().find(|c| {
+        c.value.eq_ignore_ascii_case(word) || c.aliases.iter().any(|a| a.eq_ignore_ascii_case(word))
+    });
+    let candidate = match exact {
+        Some(candidate) => candidate,
+        None => {
+            // The best of several fuzzy matches is a guess; guesses don't get to sync or abort
+            let matches: Vec<&Candidate> = candidates.iter()
+                .filter(|c| fuzzy_score(word, &c.haystack()).is_some())
+                .collect();
+            match matches[..] {
+                [] => bail!("no {} matches '{}'", arg.name(), word),
+                [candidate] => candidate,
+                _ => {
+                    let values: Vec<&str> = matches.iter().map(|c| c.value.as_str()).collect();
+                    bail!("'{}' matches more than one {}: {}", word, arg.name(), values.join(", "));
+                }
+            }
+        }
+    };
+    Ok(match arg {
+        // Sites are shown by domain but acted on by ID
+        Arg::Site => candidate.aliases[0].clone(),
+        _ => candidate.value.clone(),
+    })
+}
+
+/// One row under the input line
+pub struct Suggestion {
+    pub value: String,
+    pub detail: String,
+    /// A history entry replaces the whole line rather than the word being typed
+    pub whole_line: bool,
+}
+
+/// Recently run palette lines, newest first
+pub struct History {
+    pub entries: Vec<String>,
+    path: Option<PathBuf>,
+}
+
+impl History {
+    pub fn default_path() -> Option<PathBuf> {
+        directories::ProjectDirs::from("sh", "rhodium", "socp-tui")
+            .map(|dirs| dirs.data_dir().join("palette-history"))
+    }
+
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let entries = path.as_deref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .map(|text| text.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+        Self { entries, path }
+    }
+
+    pub fn record(&mut self, line: &str) {
+        self.entries.retain(|e| e != line);
+        self.entries.insert(0, line.to_string());
+        self.entries.truncate(HISTORY_LIMIT);
+        if let Some(path) = &self.path {
+            if let Err(e) = save(path, &self.entries) {
+                tracing::warn!("Could not save palette history to {}: {:#}", path.display(), e);
+            }
+        }
+    }
+}
+
+fn save(path: &Path, entries: &[String]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, entries.join("\n") + "\n")?;
+    Ok(())
+}
+
+/// State of the open palette
+#[derive(Debug, Default)]
+pub struct Palette {
+    pub input: String,
+    pub selected: usize,
+    /// Why the last Enter did not run anything
+    pub error: Option<String>,
+}
+
+impl Palette {
+    /// Finished words, and the position of the word being typed
+    fn words(&self) -> (Vec<&str>, usize) {
+        let words: Vec<&str> = self.input.split_whitespace().collect();
+        let typing = if self.input.is_empty() || self.input.ends_with(' ') { words.len() } else { words.len() - 1 };
+        (words, typing)
+    }
+
+    pub fn suggestions(&self, app: &App) -> Vec<Suggestion> {
+        let (words, typing) = self.words();
+        let partial = words.get(typing).copied().unwrap_or("");
+
+        if typing == 0 {
+            let mut suggestions: Vec<Suggestion> = Vec::new();
+            if partial.is_empty() {
+                suggestions.extend(app.palette_history.entries.iter().map(|line| Suggestion {
+                    value: line.clone(),
+                    detail: "recent".to_string(),
+                    whole_line: true,
+                }));
+            }
+            let mut verbs: Vec<(i32, &Verb)> = VERBS.iter()
+                .filter_map(|v| fuzzy_score(partial, v.name).map(|score| (score, v)))
+                .collect();
+            if !partial.is_empty() {
+                verbs.sort_by_key(|(score, _)| -score);
+            }
+            suggestions.extend(verbs.into_iter().map(|(_, v)| {
+                let args: Vec<String> = v.args.iter().map(|a| format!("<{}>", a.name())).collect();
+                Suggestion {
+                    value: v.name.to_string(),
+                    detail: format!("{} {}", args.join(" "), v.description).trim().to_string(),
+                    whole_line: false,
+                }
+            }));
+            return suggestions;
+        }
+
+        let Some(arg) = words.first().and_then(|w| verb(w)).and_then(|v| v.args.get(typing - 1)) else {
+            return Vec::new();
+        };
+        let mut matches: Vec<(i32, Candidate)> = candidates(*arg, app).into_iter()
+            .filter_map(|c| fuzzy_score(partial, &c.haystack()).map(|score| (score, c)))
+            .collect();
+        if !partial.is_empty() {
+            matches.sort_by_key(|(score, _)| -score);
+        }
+        matches.into_iter()
+            .map(|(_, c)| Suggestion { value: c.value, detail: c.detail, whole_line: false })
+            .collect()
+    }
+
+    /// Replace the word being typed with the selected suggestion
+    pub fn complete(&mut self, app: &App) {
+        let suggestions = self.suggestions(app);
+        let Some(suggestion) = suggestions.get(self.selected) else {
+            return;
+        };
+        if suggestion.whole_line {
+            self.input = suggestion.value.clone();
+        } else {
+            let (mut words, typing) = self.words();
+            words.truncate(typing);
+            words.push(&suggestion.value);
+            self.input = words.join(" ") + " ";
+        }
+        self.selected = 0;
+        self.error = None;
+    }
+
+    pub fn parse(&self, app: &App) -> Result<Invocation> {
+        let words: Vec<&str> = self.input.split_whitespace().collect();
+        let Some(first) = words.first() else {
+            bail!("type a command");
+        };
+        let verb = verb(first).ok_or_else(|| anyhow!("unknown command '{}'", first))?;
+        let given = &words[1..];
+        if given.len() < verb.args.len() {
+            let missing: Vec<String> = verb.args[given.len()..].iter().map(|a| format!("<{}>", a.name())).collect();
+            bail!("{} needs {}", verb.name, missing.join(" "));
+        }
+        if given.len() > verb.args.len() {
+            bail!("{} takes {} argument(s)", verb.name, verb.args.len());
+        }
+        let arg = |i: usize| resolve(verb.args[i], given[i], app);
+
+        Ok(match verb.name {
+            "goto" => {
+                let name = arg(0)?;
+                let view = VIEWS.iter().find(|(n, _)| *n == name).map(|(_, v)| *v).unwrap_or(View::Dashboard);
+                Invocation::Goto(view)
+            }
+            "sync" => Invocation::Sync { site_id: arg(0)? },
+            "diff" => Invocation::Diff { site_id: arg(0)? },
+            "silence" => Invocation::Silence {
+                site_id: arg(0)?,
+                duration_secs: parse_duration(given[1])
+                    .ok_or_else(|| anyhow!("'{}' is not a duration like 30m or 1h", given[1]))?,
+            },
+            "acknowledge" => Invocation::Acknowledge { alert_id: arg(0)? },
+            "dismiss" => Invocation::Dismiss { alert_id: arg(0)? },
+            "approve" => Invocation::Approve { deployment_id: arg(0)? },
+            "reject" => Invocation::Reject { deployment_id: arg(0)? },
+            "pause" => Invocation::Pause { deployment_id: arg(0)? },
+            "abort" => Invocation::Abort { deployment_id: arg(0)? },
+            "context" => Invocation::Context(arg(0)?),
+            "refresh" => Invocation::Refresh,
+            "help" => Invocation::Help,
+            _ => Invocation::Quit,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::app;
+
+    fn parse(app: &App, line: &str) -> Result<String, String> {
+        let palette = Palette { input: line.to_string(), ..Palette::default() };
+        palette.parse(app).map(|invocation| format!("{:?}", invocation)).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn fuzzy_scores_favour_runs_and_word_starts() {
+        assert_eq!(fuzzy_score("sync", "sync"), Some(94));
+        assert_eq!(fuzzy_score("SYN", "sync"), fuzzy_score("syn", "sync"));
+        assert!(fuzzy_score("", "sync").is_some());
+        assert_eq!(fuzzy_score("ysn", "sync"), None);
+        assert_eq!(fuzzy_score("xyz", "sync"), None);
+
+        // Pattern, then a candidate that should outrank the other
+        let cases = [
+            ("blog", "blog.example.com", "b-l-o-g.example.com"),
+            ("ex", "example.com", "shop.example.com"),
+            ("s2", "site-2", "site-12"),
+            ("ack", "acknowledge", "approve-check"),
+        ];
+        for (pattern, better, worse) in cases {
+            assert!(fuzzy_score(pattern, better) > fuzzy_score(pattern, worse), "{} in {} vs {}", pattern, better, worse);
+        }
+    }
+
+    #[test]
+    fn arguments_resolve_only_when_exact_or_unique() {
+        let app = app("palette-resolve");
+        let cases = [
+            (Arg::Site, "site-2", Ok("site-2")),
+            (Arg::Site, "BLOG.example.com", Ok("site-2")),
+            (Arg::Site, "blog", Ok("site-2")),
+            (Arg::Site, "shop", Ok("site-4")),
+            // "example.com" is exact even though every site contains it
+            (Arg::Site, "example.com", Ok("site-1")),
+            (
+                Arg::Site,
+                "example",
+                Err("'example' matches more than one site: example.com, blog.example.com, staging.example.com, shop.example.com"),
+            ),
+            (Arg::Site, "nosuch", Err("no site matches 'nosuch'")),
+            (Arg::Alert, "site-4", Ok("alert-3")),
+            (Arg::Alert, "alert", Err("'alert' matches more than one alert: alert-1, alert-2, alert-3")),
+            (Arg::Deployment, "002", Ok("deploy-002")),
+            (Arg::View, "vuln", Ok("vulns")),
+            (Arg::View, "dash", Ok("dashboard")),
+            (Arg::Duration, "1h", Ok("1h")),
+        ];
+        for (arg, word, expected) in cases {
+            let resolved = resolve(arg, word, &app).map_err(|e| e.to_string());
+            assert_eq!(resolved, expected.map(str::to_string).map_err(str::to_string), "{} {}", arg.name(), word);
+        }
+    }
+
+    #[test]
+    fn lines_parse_into_invocations() {
+        let app = app("palette-parse");
+        let cases = [
+            ("sync blog", Ok(r#"Sync { site_id: "site-2" }"#)),
+            ("SYNC site-1", Ok(r#"Sync { site_id: "site-1" }"#)),
+            ("silence shop 1h", Ok(r#"Silence { site_id: "site-4", duration_secs: 3600 }"#)),
+            // A bare number is seconds, as everywhere else durations are given
+            ("silence site-4 90", Ok(r#"Silence { site_id: "site-4", duration_secs: 90 }"#)),
+            ("goto vuln", Ok("Goto(Vulnerabilities)")),
+            ("approve 001", Ok(r#"Approve { deployment_id: "deploy-001" }"#)),
+            ("  refresh  ", Ok("Refresh")),
+            ("", Err("type a command")),
+            // Verbs are never guessed
+            ("snyc site-1", Err("unknown command 'snyc'")),
+            ("sil site-2 1h", Err("unknown command 'sil'")),
+            ("sync", Err("sync needs <site>")),
+            ("silence site-2", Err("silence needs <duration>")),
+            ("refresh now", Err("refresh takes 0 argument(s)")),
+            ("abort deploy", Err("'deploy' matches more than one deployment: deploy-001, deploy-002")),
+            ("silence site-2 1w", Err("'1w' is not a duration like 30m or 1h")),
+            ("silence site-2 99999999999999999999d", Err("'99999999999999999999d' is not a duration like 30m or 1h")),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(parse(&app, line), expected.map(str::to_string).map_err(str::to_string), "{:?}", line);
+        }
+    }
+}
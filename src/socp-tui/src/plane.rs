@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Data sources the app can run against
+//!
+//! A context's `api_url` picks one: `https://` (or `http://`) talks to the
+//! control plane API, `mock:` serves built-in demo data or a JSON fixture
+//! (`mock:fixtures/plane.json`), and `repo:<path>` reads site configs from a
+//! local checkout of the config repository, read-only.
+
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::api::ApiClient;
+use crate::app::{Alert, Approval, Deployment, Site};
+use crate::composer::{DeploymentRequest, SiteGroup};
+use crate::config::Context;
+use crate::history::DeploymentRecord;
+use crate::maintenance::ChangeCalendar;
+use crate::mock::MockPlane;
+use crate::rbac::Permissions;
+use crate::repo::RepoPlane;
+use crate::wordpress::WordPressInventory;
+
+/// Everything the app reads from and asks of a control plane
+#[async_trait]
+pub trait ControlPlane: Send + Sync {
+    /// Refuses every action; the app starts read-only
+    fn read_only(&self) -> bool {
+        false
+    }
+
+    /// Effective permissions of the authenticated operator
+    async fn get_permissions(&self) -> Result<Permissions>;
+    async fn get_sites(&self) -> Result<Vec<Site>>;
+    async fn get_alerts(&self) -> Result<Vec<Alert>>;
+    async fn get_pending_deployments(&self) -> Result<Vec<Deployment>>;
+    async fn get_groups(&self) -> Result<Vec<SiteGroup>>;
+    async fn get_change_calendar(&self) -> Result<ChangeCalendar>;
+    /// Most recent deployments first, at most `limit` entries
+    async fn get_deployment_history(&self, limit: usize) -> Result<Vec<DeploymentRecord>>;
+    async fn get_config_diff(&self, site_id: &str) -> Result<String>;
+    /// Installed WordPress core, plugins and themes; `None` for non-WordPress sites
+    async fn get_wordpress_inventory(&self, site_id: &str) -> Result<Option<WordPressInventory>>;
+
+    /// `override_reason` is recorded server-side when a window or freeze is bypassed
+    async fn sync_site(&self, site_id: &str, override_reason: Option<&str>) -> Result<()>;
+    /// Suppress alert notifications for a site; returns when the silence ends
+    async fn silence_site(&self, site_id: &str, duration_secs: u64) -> Result<chrono::DateTime<chrono::Utc>>;
+    async fn acknowledge_alert(&self, alert_id: &str) -> Result<()>;
+    async fn dismiss_alert(&self, alert_id: &str) -> Result<()>;
+    /// Submit a new deployment; rejected fields come back as `ApiError::Validation`
+    async fn create_deployment(&self, request: &DeploymentRequest) -> Result<Deployment>;
+    /// Diff the request would apply to its sites, without creating it; validated like `create_deployment`
+    async fn preview_deployment(&self, request: &DeploymentRequest) -> Result<String>;
+    /// Record our approval; the server rejects self-approval and duplicates as well
+    async fn approve_deployment(&self, deployment_id: &str, override_reason: Option<&str>) -> Result<Approval>;
+    async fn reject_deployment(&self, deployment_id: &str) -> Result<()>;
+    async fn pause_deployment(&self, deployment_id: &str) -> Result<()>;
+    async fn resume_deployment(&self, deployment_id: &str) -> Result<()>;
+    async fn abort_deployment(&self, deployment_id: &str) -> Result<()>;
+    /// Undo what an in-progress rollout has deployed so far
+    async fn rollback_deployment(&self, deployment_id: &str) -> Result<()>;
+    /// Schedule a new deployment restoring `config_hash` on the record's sites
+    async fn create_rollback(&self, record: &DeploymentRecord, config_hash: &str) -> Result<Deployment>;
+}
+
+/// Implementation named by a context's `api_url`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Backend {
+    Http,
+    /// Fixture file; built-in demo data when `None`
+    Mock(Option<PathBuf>),
+    /// Root of a config repository checkout
+    Repo(PathBuf),
+}
+
+impl Backend {
+    pub fn parse(api_url: &str) -> Result<Self> {
+        if let Some(path) = api_url.strip_prefix("mock:") {
+            return Ok(Backend::Mock((!path.is_empty()).then(|| PathBuf::from(path))));
+        }
+        if let Some(path) = api_url.strip_prefix("repo:") {
+            return Ok(Backend::Repo(PathBuf::from(if path.is_empty() { "." } else { path })));
+        }
+        match reqwest::Url::parse(api_url) {
+            Ok(url) if matches!(url.scheme(), "https" | "http") => Ok(Backend::Http),
+            Ok(url) => bail!("unsupported scheme '{}' (use https, http, mock or repo)", url.scheme()),
+            Err(e) => bail!("invalid URL '{}' ({})", api_url, e),
+        }
+    }
+}
+
+/// The data source for `context`
+pub fn connect(context: &Context, operator: &str) -> Result<Arc<dyn ControlPlane>> {
+    Ok(match Backend::parse(&context.api_url)? {
+        Backend::Http => Arc::new(ApiClient::new(context, operator)?),
+        Backend::Mock(None) => Arc::new(MockPlane::demo(operator)),
+        Backend::Mock(Some(path)) => Arc::new(MockPlane::load(&path, operator)?),
+        Backend::Repo(root) => Arc::new(RepoPlane::open(root)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{App, View};
+    use crate::config::Config;
+    use crate::fixtures::press;
+
+    #[test]
+    fn api_url_selects_the_backend() {
+        assert_eq!(Backend::parse("https://[::1]:8443").unwrap(), Backend::Http);
+        assert_eq!(Backend::parse("mock:").unwrap(), Backend::Mock(None));
+        assert_eq!(Backend::parse("mock:plane.json").unwrap(), Backend::Mock(Some("plane.json".into())));
+        assert_eq!(Backend::parse("repo:").unwrap(), Backend::Repo(".".into()));
+        assert!(Backend::parse("ftp://example.com").is_err());
+    }
+
+    #[tokio::test]
+    async fn mock_plane_keeps_the_effect_of_actions() {
+        let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/src/fixtures/plane.json");
+        let config = Config {
+            operator: Some("tester".to_string()),
+            audit_log: Some(std::env::temp_dir().join(format!("socp-tui-mock-{}.log", std::process::id()))),
+            ..Config::default()
+        };
+        let context = config.select_context(None, Some(format!("mock:{}", fixture))).unwrap();
+        let mut app = App::new(context, &config).await.unwrap();
+        assert_eq!(app.sites.len(), 4);
+
+        press(&mut app, "3").await;
+        let acknowledged = app.alerts[app.selected_alert].id.clone();
+        press(&mut app, "a esc r").await;
+        assert_eq!(app.view, View::Dashboard);
+        assert!(app.alerts.iter().any(|a| a.id == acknowledged && a.acknowledged));
+    }
+
+    #[tokio::test]
+    async fn repo_plane_reads_the_config_checkout() {
+        let repo = RepoPlane::open(concat!(env!("CARGO_MANIFEST_DIR"), "/../..").into()).unwrap();
+        assert!(repo.read_only());
+        let sites = repo.get_sites().await.unwrap();
+        let example = sites.iter().find(|s| s.id == "example-production").unwrap();
+        assert_eq!(example.domain, "example.com");
+        assert_eq!(example.environment, "production");
+        assert!(example.tags.contains(&"woocommerce".to_string()));
+        assert!(repo.sync_site(&example.id, None).await.is_err());
+    }
+}
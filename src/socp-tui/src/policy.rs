@@ -0,0 +1,176 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Confirmation policies for mutating actions
+//!
+//! Each `[[confirm]]` rule sets how an action is confirmed, optionally only
+//! for sites in some environments. User rules are added to the built-in ones
+//! and when several rules match, the strictest wins, so configuration can
+//! tighten a built-in rule but never loosen it. Actions no rule matches fall
+//! back to a per-action default.
+
+use serde::{Deserialize, Serialize};
+
+/// Mutating operator actions, named as in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    Sync,
+    Approve,
+    Reject,
+    Pause,
+    Resume,
+    Abort,
+    Rollback,
+    CreateDeployment,
+    Acknowledge,
+    Dismiss,
+    Silence,
+}
+
+impl Action {
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Sync => "sync",
+            Action::Approve => "approve",
+            Action::Reject => "reject",
+            Action::Pause => "pause",
+            Action::Resume => "resume",
+            Action::Abort => "abort",
+            Action::Rollback => "rollback",
+            Action::CreateDeployment => "create-deployment",
+            Action::Acknowledge => "acknowledge",
+            Action::Dismiss => "dismiss",
+            Action::Silence => "silence",
+        }
+    }
+
+    fn default_mode(self) -> ConfirmMode {
+        match self {
+            Action::Sync
+            | Action::Approve
+            | Action::Reject
+            | Action::Abort
+            | Action::Rollback
+            | Action::CreateDeployment => ConfirmMode::Prompt,
+            Action::Pause
+            | Action::Resume
+            | Action::Acknowledge
+            | Action::Dismiss
+            | Action::Silence => ConfirmMode::None,
+        }
+    }
+}
+
+/// Ordered from least to most strict
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfirmMode {
+    /// Run immediately
+    None,
+    /// Ask y/n
+    Prompt,
+    /// Operator must type the target (domain or ID)
+    Typed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfirmRule {
+    pub action: Action,
+    /// Environments the rule applies to; empty means all
+    #[serde(default)]
+    pub environments: Vec<String>,
+    pub mode: ConfirmMode,
+}
+
+impl ConfirmRule {
+    fn matches(&self, action: Action, environments: &[&str]) -> bool {
+        self.action == action
+            && (self.environments.is_empty()
+                || environments.iter().any(|e| self.environments.iter().any(|r| r == e)))
+    }
+}
+
+/// The user's rules; the built-in ones always apply as well
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ConfirmPolicy {
+    rules: Vec<ConfirmRule>,
+}
+
+/// Rules in force whatever the configuration says
+fn builtin_rules() -> Vec<ConfirmRule> {
+    vec![ConfirmRule {
+        action: Action::Sync,
+        environments: vec!["production".to_string()],
+        mode: ConfirmMode::Typed,
+    }]
+}
+
+impl ConfirmPolicy {
+    /// How to confirm `action` against sites in `environments`
+    pub fn mode(&self, action: Action, environments: &[&str]) -> ConfirmMode {
+        builtin_rules()
+            .iter()
+            .chain(&self.rules)
+            .filter(|r| r.matches(action, environments))
+            .map(|r| r.mode)
+            .max()
+            .unwrap_or_else(|| action.default_mode())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(action: Action, environments: &[&str], mode: ConfirmMode) -> ConfirmRule {
+        ConfirmRule { action, environments: environments.iter().map(|e| e.to_string()).collect(), mode }
+    }
+
+    #[test]
+    fn strictest_matching_rule_wins() {
+        let policy = ConfirmPolicy {
+            rules: vec![
+                rule(Action::Sync, &[], ConfirmMode::None),
+                rule(Action::Sync, &["production"], ConfirmMode::Typed),
+                rule(Action::Sync, &["staging"], ConfirmMode::Prompt),
+                rule(Action::Silence, &["production"], ConfirmMode::Prompt),
+            ],
+        };
+        let cases = [
+            (Action::Sync, &[][..], ConfirmMode::None),
+            (Action::Sync, &["development"][..], ConfirmMode::None),
+            (Action::Sync, &["staging"][..], ConfirmMode::Prompt),
+            (Action::Sync, &["staging", "production"][..], ConfirmMode::Typed),
+            (Action::Silence, &["production"][..], ConfirmMode::Prompt),
+            // No rule matches: the built-in default applies
+            (Action::Silence, &["staging"][..], ConfirmMode::None),
+            (Action::Abort, &["production"][..], ConfirmMode::Prompt),
+        ];
+        for (action, environments, expected) in cases {
+            assert_eq!(policy.mode(action, environments), expected, "{} on {:?}", action.name(), environments);
+        }
+    }
+
+    #[test]
+    fn default_policy_types_production_syncs() {
+        let policy = ConfirmPolicy::default();
+        assert_eq!(policy.mode(Action::Sync, &["production"]), ConfirmMode::Typed);
+        assert_eq!(policy.mode(Action::Sync, &["staging"]), ConfirmMode::Prompt);
+        assert_eq!(policy.mode(Action::Acknowledge, &["production"]), ConfirmMode::None);
+
+        let policy: ConfirmPolicy = serde_json::from_str(r#"[{ "action": "create-deployment", "mode": "typed" }]"#).unwrap();
+        assert_eq!(policy.mode(Action::CreateDeployment, &["staging"]), ConfirmMode::Typed);
+    }
+
+    #[test]
+    fn user_rules_add_to_the_builtin_ones() {
+        let policy: ConfirmPolicy = serde_json::from_str(r#"[{ "action": "abort", "mode": "typed" }]"#).unwrap();
+        assert_eq!(policy.mode(Action::Abort, &["staging"]), ConfirmMode::Typed);
+        assert_eq!(policy.mode(Action::Sync, &["production"]), ConfirmMode::Typed);
+
+        // A looser rule cannot undo the built-in one
+        let policy: ConfirmPolicy = serde_json::from_str(r#"[{ "action": "sync", "mode": "none" }]"#).unwrap();
+        assert_eq!(policy.mode(Action::Sync, &["production"]), ConfirmMode::Typed);
+        assert_eq!(policy.mode(Action::Sync, &["staging"]), ConfirmMode::None);
+    }
+}
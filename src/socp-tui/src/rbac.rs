@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Operator permissions as reported by the control plane
+//!
+//! The server is the authority and enforces RBAC on every call; the client
+//! only uses this to hide views and disable actions the operator cannot use.
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::Site;
+use crate::policy::Action;
+
+/// Actions granted on a set of sites
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Grant {
+    /// Action names as in [`Action::name`], or "*" for all
+    pub actions: Vec<String>,
+    /// Site IDs covered; empty together with `environments` means every site
+    #[serde(default)]
+    pub sites: Vec<String>,
+    #[serde(default)]
+    pub environments: Vec<String>,
+}
+
+impl Grant {
+    fn allows(&self, action: Action, site: &Site) -> bool {
+        let action_ok = self.actions.iter().any(|a| a == "*" || a == action.name());
+        let site_ok = (self.sites.is_empty() && self.environments.is_empty())
+            || self.sites.contains(&site.id)
+            || self.environments.contains(&site.environment);
+        action_ok && site_ok
+    }
+
+    fn allows_unscoped(&self, action: Action) -> bool {
+        self.sites.is_empty()
+            && self.environments.is_empty()
+            && self.actions.iter().any(|a| a == "*" || a == action.name())
+    }
+}
+
+/// `GET /me/permissions`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Permissions {
+    pub role: Option<String>,
+    /// Views the operator may open; `None` means all
+    pub views: Option<Vec<String>>,
+    /// `None` means unrestricted (server did not report grants)
+    pub grants: Option<Vec<Grant>>,
+}
+
+impl Permissions {
+    /// What to assume when the server could not be asked: every view, no actions
+    pub fn unverified() -> Self {
+        Self {
+            role: Some("unverified".to_string()),
+            views: None,
+            grants: Some(Vec::new()),
+        }
+    }
+
+    pub fn can_view(&self, view: &str) -> bool {
+        self.views.as_ref().is_none_or(|views| views.iter().any(|v| v == view))
+    }
+
+    /// Whether `action` is granted on every one of `sites`
+    pub fn allows(&self, action: Action, sites: &[&Site]) -> bool {
+        let Some(grants) = &self.grants else {
+            return true;
+        };
+        if sites.is_empty() {
+            return grants.iter().any(|g| g.allows_unscoped(action));
+        }
+        sites.iter().all(|site| grants.iter().any(|g| g.allows(action, site)))
+    }
+
+    /// Whether `action` is granted on at least some sites
+    pub fn allows_somewhere(&self, action: Action) -> bool {
+        self.grants.as_ref().is_none_or(|grants| {
+            grants.iter().any(|g| g.actions.iter().any(|a| a == "*" || a == action.name()))
+        })
+    }
+
+    /// Reason shown when `action` is not granted on one of `sites`
+    pub fn denied(&self, action: Action, sites: &[&Site]) -> Option<String> {
+        if self.allows(action, sites) {
+            return None;
+        }
+        let role = self.role.as_deref().unwrap_or("your role");
+        Some(match sites.iter().find(|s| !self.allows(action, &[**s])) {
+            Some(site) => format!("{} may not {} on {}", role, action.name(), site.domain),
+            None => format!("{} may not {}", role, action.name()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ApiClient;
+    use crate::app::PlaneData;
+    use crate::config::Config;
+    use crate::fixtures::{app, fixture, press, type_text};
+    use crate::maintenance::ChangeCalendar;
+    use crate::mock::MockPlane;
+    use std::sync::Arc;
+
+    fn grant(actions: &[&str], sites: &[&str], environments: &[&str]) -> Grant {
+        let strings = |values: &[&str]| values.iter().map(|v| v.to_string()).collect();
+        Grant { actions: strings(actions), sites: strings(sites), environments: strings(environments) }
+    }
+
+    fn operator() -> Permissions {
+        Permissions {
+            role: Some("operator".to_string()),
+            views: Some(vec!["sites".to_string(), "alerts".to_string()]),
+            grants: Some(vec![
+                grant(&["*"], &[], &["staging"]),
+                grant(&["sync"], &["site-2"], &[]),
+                grant(&["acknowledge"], &[], &[]),
+            ]),
+        }
+    }
+
+    #[test]
+    fn grants_are_scoped_by_site_and_environment() {
+        let sites = fixture().plane.sites;
+        let (example, blog, staging) = (&sites[0], &sites[1], &sites[2]);
+        let permissions = operator();
+        let cases = [
+            (Action::Sync, vec![staging], true),
+            (Action::Abort, vec![staging], true),
+            (Action::Sync, vec![blog], true),
+            (Action::Sync, vec![blog, staging], true),
+            (Action::Sync, vec![example], false),
+            (Action::Sync, vec![blog, example], false),
+            (Action::Acknowledge, vec![example], true),
+            // Unscoped actions need an unscoped grant
+            (Action::Acknowledge, vec![], true),
+            (Action::Sync, vec![], false),
+        ];
+        for (action, targets, expected) in cases {
+            assert_eq!(permissions.allows(action, &targets), expected, "{} on {:?}", action.name(), targets);
+        }
+        assert!(permissions.allows_somewhere(Action::Rollback));
+        assert_eq!(
+            permissions.denied(Action::Sync, &[blog, example]).as_deref(),
+            Some("operator may not sync on example.com")
+        );
+        assert_eq!(permissions.denied(Action::Sync, &[]).as_deref(), Some("operator may not sync"));
+        assert!(permissions.can_view("alerts") && !permissions.can_view("logs"));
+    }
+
+    #[test]
+    fn unreported_grants_are_unrestricted_and_unverified_ones_are_not() {
+        let site = &fixture().plane.sites[0];
+        let reported = Permissions::default();
+        assert!(reported.allows(Action::Rollback, &[site]) && reported.can_view("logs"));
+
+        let unverified = Permissions::unverified();
+        assert!(unverified.can_view("logs"));
+        assert!(!unverified.allows_somewhere(Action::Acknowledge));
+        assert_eq!(unverified.denied(Action::Sync, &[site]).as_deref(), Some("unverified may not sync on example.com"));
+    }
+
+    #[tokio::test]
+    async fn server_refusals_reach_the_operator() {
+        let mut app = app("forbidden");
+        let mut fixture = fixture();
+        fixture.plane.permissions = operator();
+        app.plane = Arc::new(MockPlane::new(fixture, "tester"));
+        // Windows are checked against the wall clock; keep them out of this
+        app.change_calendar = ChangeCalendar::default();
+        // Our copy still says unrestricted, as after a server-side role change
+        press(&mut app, "1 j j j s").await;
+        type_text(&mut app, "shop.example.com").await;
+        press(&mut app, "enter").await;
+        let latest = app.notifications.history().next().unwrap();
+        assert_eq!(latest.message, "Permission denied: operator may not sync on shop.example.com");
+
+        // The next refresh picks up the new grants and gates locally
+        press(&mut app, "esc r 1 j j j s").await;
+        assert!(app.confirmation.is_none());
+    }
+
+    #[tokio::test]
+    async fn unreachable_planes_grant_nothing() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let context = Config::default().select_context(None, Some(format!("http://127.0.0.1:{}", port))).unwrap();
+        let client = ApiClient::new(&context, "tester").unwrap();
+        let mut failures = Vec::new();
+        let data = PlaneData::fetch(&client, &mut failures).await;
+        assert_eq!(data.permissions.role.as_deref(), Some("unverified"));
+        assert!(failures.iter().any(|(what, _)| *what == "permissions"));
+        assert!(!data.permissions.allows_somewhere(Action::Sync));
+    }
+}
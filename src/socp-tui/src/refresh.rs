@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Fetching everything a refresh shows
+//!
+//! A [`RefreshJob`] owns what it needs to do all of a refresh's network I/O,
+//! so it can run on its own task, and hands back a [`Refresh`] that the app
+//! applies in one step. The periodic refresh runs in the background this way
+//! so a slow or unreachable plane cannot freeze the UI.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+
+use crate::app::{PlaneData, Site};
+use crate::dnssec::{DnssecValidator, ZoneReport};
+use crate::federation::FleetResults;
+use crate::plane::ControlPlane;
+use crate::wordpress::WordPressInventory;
+
+/// ID prefix and client of every plane sites may come from
+pub type Routes = Vec<(String, Arc<dyn ControlPlane>)>;
+
+pub struct RefreshJob {
+    pub plane: Arc<dyn ControlPlane>,
+    /// Set in the federated view, which fetches from its planes instead
+    pub fleet: Option<Pin<Box<dyn Future<Output = FleetResults> + Send>>>,
+    pub routes: Routes,
+    pub dnssec: Option<DnssecValidator>,
+    /// Fetch WordPress inventories for the vulnerability scan
+    pub scan: bool,
+}
+
+pub enum Fetched {
+    Plane(Box<Result<PlaneData>>),
+    Fleet(FleetResults),
+}
+
+pub struct Refresh {
+    pub fetched: Fetched,
+    /// Reports for the zones of the sites fetched
+    pub dnssec: HashMap<String, ZoneReport>,
+    pub inventories: Vec<(String, Result<Option<WordPressInventory>>)>,
+    /// Problems worth telling the operator that did not fail the refresh
+    pub warnings: Vec<String>,
+}
+
+impl RefreshJob {
+    pub async fn run(self) -> Refresh {
+        let mut warnings = Vec::new();
+        let (fetched, sites) = match self.fleet {
+            Some(fleet) => {
+                let results = fleet.await;
+                let sites = results.sites();
+                (Fetched::Fleet(results), sites)
+            }
+            None => {
+                let data = PlaneData::refresh(self.plane.as_ref(), &mut warnings).await;
+                let sites = data.as_ref().map(|d| d.sites.clone()).unwrap_or_default();
+                (Fetched::Plane(Box::new(data)), sites)
+            }
+        };
+        let dnssec = match &self.dnssec {
+            Some(validator) => check_zones(validator, &sites).await,
+            None => HashMap::new(),
+        };
+        let inventories = if self.scan {
+            fetch_inventories(&self.routes, &sites).await
+        } else {
+            Vec::new()
+        };
+        Refresh { fetched, dnssec, inventories, warnings }
+    }
+}
+
+/// Validate every zone the sites live in, concurrently
+pub async fn check_zones(validator: &DnssecValidator, sites: &[Site]) -> HashMap<String, ZoneReport> {
+    let mut zones: Vec<String> = sites.iter().map(|s| s.zone().to_string()).collect();
+    zones.sort_unstable();
+    zones.dedup();
+
+    let mut checks = JoinSet::new();
+    for zone in zones {
+        let validator = validator.clone();
+        checks.spawn(async move { validator.check_zone(&zone).await });
+    }
+    let mut reports = HashMap::new();
+    while let Some(report) = checks.join_next().await {
+        match report {
+            Ok(report) => {
+                reports.insert(report.zone.clone(), report);
+            }
+            Err(e) => tracing::warn!("DNSSEC check panicked: {}", e),
+        }
+    }
+    reports
+}
+
+/// Each site's WordPress inventory, asked of the plane serving it
+pub async fn fetch_inventories(
+    routes: &[(String, Arc<dyn ControlPlane>)],
+    sites: &[Site],
+) -> Vec<(String, Result<Option<WordPressInventory>>)> {
+    let mut inventories = Vec::new();
+    for site in sites {
+        let route = routes.iter().find_map(|(prefix, client)| Some((client, site.id.strip_prefix(prefix.as_str())?)));
+        if let Some((client, site_id)) = route {
+            inventories.push((site.id.clone(), client.get_wordpress_inventory(site_id).await));
+        }
+    }
+    inventories
+}
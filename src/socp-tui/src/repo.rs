@@ -0,0 +1,304 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Read-only control plane over a local checkout of the config repository
+//!
+//! Sites come from `config/sites/*.ncl` (or `sites/*.ncl` when pointed at the
+//! config directory itself), groups from `groups/*.ncl`, deployment history
+//! from the git log and diffs from uncommitted changes. Nothing is evaluated:
+//! only single-line top-level fields such as `id`, `domain`, `environment`
+//! and `tags` are read, so health is unknown and there are no alerts.
+
+use anyhow::{bail, Context as _, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use crate::app::{Alert, Approval, Deployment, DeploymentStatus, Site, SiteStatus};
+use crate::composer::{DeploymentRequest, SiteGroup};
+use crate::history::DeploymentRecord;
+use crate::maintenance::ChangeCalendar;
+use crate::plane::ControlPlane;
+use crate::rbac::Permissions;
+use crate::wordpress::WordPressInventory;
+
+/// Separates commits in `git log` output
+const RECORD_SEPARATOR: char = '\x1e';
+/// Separates fields of a commit header
+const FIELD_SEPARATOR: char = '\x1f';
+
+pub struct RepoPlane {
+    /// Directory holding `sites/` and `groups/`
+    config: PathBuf,
+    /// Inside a git work tree, so history and diffs are available
+    git: bool,
+}
+
+impl RepoPlane {
+    pub fn open(root: PathBuf) -> Result<Self> {
+        // git runs from the config directory, so paths must not depend on ours
+        let root = root.canonicalize().with_context(|| format!("opening repo {}", root.display()))?;
+        let config = [root.join("config"), root.clone()]
+            .into_iter()
+            .find(|dir| dir.join("sites").is_dir())
+            .with_context(|| format!("{}: no sites/ or config/sites/ directory", root.display()))?;
+        let git = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&config)
+            .args(["rev-parse", "--is-inside-work-tree"])
+            .output()
+            .is_ok_and(|output| output.status.success());
+        Ok(Self { config, git })
+    }
+
+    /// Site configs as (path, site), ordered by file name
+    fn scan_sites(&self) -> Result<Vec<(PathBuf, Site)>> {
+        let mut sites = Vec::new();
+        for path in ncl_files(&self.config.join("sites"))? {
+            let source = std::fs::read_to_string(&path)
+                .with_context(|| format!("reading {}", path.display()))?;
+            let fields = top_level_fields(&source);
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            let id = fields.get("id").map_or(stem, |v| unquote(v));
+            let hash: String = Sha256::digest(source.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect();
+            sites.push((path, Site {
+                domain: fields.get("domain").map_or_else(|| id.clone(), |v| unquote(v)),
+                id,
+                status: SiteStatus::Unknown,
+                last_sync: None,
+                config_hash: Some(format!("sha256:{}", &hash[..12])),
+                response_time_ms: None,
+                ssl_expires: None,
+                tags: fields.get("tags").map(|v| list(v)).unwrap_or_default(),
+                environment: fields.get("environment").map_or_else(|| "unknown".to_string(), |v| unquote(v)),
+                dns_zone: None,
+                origin: None,
+            }));
+        }
+        Ok(sites)
+    }
+
+    async fn git(&self, args: &[&str]) -> Result<String> {
+        let output = Command::new("git").arg("-C").arg(&self.config).args(args).output().await
+            .context("running git")?;
+        if !output.status.success() {
+            bail!("git {}: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn refuse<T>(&self, action: &str) -> Result<T> {
+        bail!("{}: the local repo at {} is read-only", action, self.config.display())
+    }
+}
+
+#[async_trait]
+impl ControlPlane for RepoPlane {
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn get_permissions(&self) -> Result<Permissions> {
+        Ok(Permissions::default())
+    }
+
+    async fn get_sites(&self) -> Result<Vec<Site>> {
+        Ok(self.scan_sites()?.into_iter().map(|(_, site)| site).collect())
+    }
+
+    async fn get_alerts(&self) -> Result<Vec<Alert>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_pending_deployments(&self) -> Result<Vec<Deployment>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_groups(&self) -> Result<Vec<SiteGroup>> {
+        let dir = self.config.join("groups");
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut groups = Vec::new();
+        for path in ncl_files(&dir)? {
+            let source = std::fs::read_to_string(&path)
+                .with_context(|| format!("reading {}", path.display()))?;
+            let fields = top_level_fields(&source);
+            groups.push(SiteGroup {
+                id: fields.get("id").map_or_else(
+                    || path.file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+                    |v| unquote(v),
+                ),
+                sites: fields.get("sites").map(|v| list(v)).unwrap_or_default(),
+            });
+        }
+        Ok(groups)
+    }
+
+    async fn get_change_calendar(&self) -> Result<ChangeCalendar> {
+        Ok(ChangeCalendar::default())
+    }
+
+    /// Commits touching the config, newest first
+    async fn get_deployment_history(&self, limit: usize) -> Result<Vec<DeploymentRecord>> {
+        if !self.git {
+            return Ok(Vec::new());
+        }
+        let format = format!("--format={}{}", RECORD_SEPARATOR, ["%h", "%p", "%an", "%aI", "%s"].join(&FIELD_SEPARATOR.to_string()));
+        let log = self.git(&["log", "-p", "--no-color", "--relative", &format!("-n{}", limit), &format, "--", "."]).await?;
+        let ids: HashMap<PathBuf, String> = self.scan_sites()?
+            .into_iter()
+            .filter_map(|(path, site)| Some((path.strip_prefix(&self.config).ok()?.to_path_buf(), site.id)))
+            .collect();
+
+        let mut history = Vec::new();
+        for record in log.split(RECORD_SEPARATOR).filter(|r| !r.is_empty()) {
+            let (header, diff) = record.split_once('\n').unwrap_or((record, ""));
+            let fields: Vec<&str> = header.split(FIELD_SEPARATOR).collect();
+            let [hash, parents, author, date, subject] = fields[..] else {
+                continue;
+            };
+            let Ok(at) = DateTime::parse_from_rfc3339(date) else {
+                continue;
+            };
+            let mut sites: Vec<String> = diff.lines()
+                .filter_map(|line| line.strip_prefix("+++ b/").or_else(|| line.strip_prefix("--- a/")))
+                .filter_map(|path| ids.get(Path::new(path)).cloned())
+                .collect();
+            sites.sort();
+            sites.dedup();
+            history.push(DeploymentRecord {
+                id: hash.to_string(),
+                operator: author.to_string(),
+                started_at: at.with_timezone(&Utc),
+                finished_at: Some(at.with_timezone(&Utc)),
+                change_type: subject.to_string(),
+                sites,
+                result: DeploymentStatus::Completed,
+                previous_config_hash: parents.split_whitespace().next().map(str::to_string),
+                config_hash: hash.to_string(),
+                diff: diff.trim_start().to_string(),
+            });
+        }
+        Ok(history)
+    }
+
+    /// Uncommitted changes to the site's config
+    async fn get_config_diff(&self, site_id: &str) -> Result<String> {
+        let sites = self.scan_sites()?;
+        let Some((path, _)) = sites.iter().find(|(_, site)| site.id == site_id) else {
+            bail!("no config for site {}", site_id);
+        };
+        if !self.git {
+            return Ok(format!("{} is not in a git checkout", path.display()));
+        }
+        let diff = self.git(&["diff", "--no-color", "HEAD", "--", &path.to_string_lossy()]).await?;
+        if diff.is_empty() {
+            return Ok(format!("No uncommitted changes to {}", path.display()));
+        }
+        Ok(diff)
+    }
+
+    async fn get_wordpress_inventory(&self, _site_id: &str) -> Result<Option<WordPressInventory>> {
+        Ok(None)
+    }
+
+    async fn sync_site(&self, _site_id: &str, _override_reason: Option<&str>) -> Result<()> {
+        self.refuse("sync")
+    }
+
+    async fn silence_site(&self, _site_id: &str, _duration_secs: u64) -> Result<DateTime<Utc>> {
+        self.refuse("silence")
+    }
+
+    async fn acknowledge_alert(&self, _alert_id: &str) -> Result<()> {
+        self.refuse("acknowledge")
+    }
+
+    async fn dismiss_alert(&self, _alert_id: &str) -> Result<()> {
+        self.refuse("dismiss")
+    }
+
+    async fn create_deployment(&self, _request: &DeploymentRequest) -> Result<Deployment> {
+        self.refuse("create-deployment")
+    }
+
+    async fn preview_deployment(&self, request: &DeploymentRequest) -> Result<String> {
+        match request.verbatim_preview() {
+            Some(preview) => Ok(preview),
+            None => bail!("{}: only the control plane can render named change types", request.change_type),
+        }
+    }
+
+    async fn approve_deployment(&self, _deployment_id: &str, _override_reason: Option<&str>) -> Result<Approval> {
+        self.refuse("approve")
+    }
+
+    async fn reject_deployment(&self, _deployment_id: &str) -> Result<()> {
+        self.refuse("reject")
+    }
+
+    async fn pause_deployment(&self, _deployment_id: &str) -> Result<()> {
+        self.refuse("pause")
+    }
+
+    async fn resume_deployment(&self, _deployment_id: &str) -> Result<()> {
+        self.refuse("resume")
+    }
+
+    async fn abort_deployment(&self, _deployment_id: &str) -> Result<()> {
+        self.refuse("abort")
+    }
+
+    async fn rollback_deployment(&self, _deployment_id: &str) -> Result<()> {
+        self.refuse("rollback")
+    }
+
+    async fn create_rollback(&self, _record: &DeploymentRecord, _config_hash: &str) -> Result<Deployment> {
+        self.refuse("rollback")
+    }
+}
+
+fn ncl_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("reading {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ncl"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// `name = value` lines of the outermost record, values as written
+fn top_level_fields(source: &str) -> HashMap<&str, &str> {
+    let mut fields = HashMap::new();
+    let mut depth = 0;
+    for line in source.lines() {
+        let code = line.split('#').next().unwrap_or_default();
+        if depth == 1 {
+            if let Some((name, value)) = code.split_once('=') {
+                let name = name.trim();
+                if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                    fields.insert(name, value.trim().trim_end_matches(',').trim_end());
+                }
+            }
+        }
+        depth += code.matches('{').count() as i32 - code.matches('}').count() as i32;
+    }
+    fields
+}
+
+/// `"text"` or an enum tag like `'production`
+fn unquote(value: &str) -> String {
+    value.trim().trim_start_matches('\'').trim_matches('"').to_string()
+}
+
+/// `["a", "b"]`
+fn list(value: &str) -> Vec<String> {
+    value.trim().trim_start_matches('[').trim_end_matches(']')
+        .split(',')
+        .map(unquote)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
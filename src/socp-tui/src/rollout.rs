@@ -0,0 +1,327 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Deployment rollout strategies and batch progress
+//!
+//! Mirrors the `deployment` block of group configs:
+//! `strategy`, `batch_size`, `pause_between` and `rollback_on_failure`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::app::{Site, SiteStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RolloutStrategy {
+    /// Batches advance automatically once healthy and `pause_between` has elapsed
+    #[default]
+    Rolling,
+    /// Every batch waits for an operator to resume
+    Batched,
+    /// A small canary batch waits for an operator, the rest rolls automatically
+    Canary,
+}
+
+impl RolloutStrategy {
+    const ALL: [RolloutStrategy; 3] = [RolloutStrategy::Rolling, RolloutStrategy::Batched, RolloutStrategy::Canary];
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|s| *s == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    pub fn prev(self) -> Self {
+        let i = Self::ALL.iter().position(|s| *s == self).unwrap_or(0);
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RolloutStrategy::Rolling => "rolling",
+            RolloutStrategy::Batched => "batched",
+            RolloutStrategy::Canary => "canary",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RolloutPolicy {
+    pub strategy: RolloutStrategy,
+    pub batch_size: usize,
+    /// Sites in the canary batch (canary strategy only)
+    pub canary_size: usize,
+    #[serde(rename = "pause_between", deserialize_with = "deserialize_duration")]
+    pub pause_between_secs: u64,
+    pub rollback_on_failure: bool,
+}
+
+impl Default for RolloutPolicy {
+    fn default() -> Self {
+        Self {
+            strategy: RolloutStrategy::Rolling,
+            batch_size: 1,
+            canary_size: 1,
+            pause_between_secs: 300,
+            rollback_on_failure: true,
+        }
+    }
+}
+
+impl RolloutPolicy {
+    /// Split target sites into batches according to the strategy
+    pub fn plan(&self, sites: &[String]) -> Vec<Batch> {
+        let batch_size = self.batch_size.max(1);
+        let mut groups: Vec<&[String]> = Vec::new();
+        let mut rest = sites;
+        if self.strategy == RolloutStrategy::Canary && !sites.is_empty() {
+            let (canary, remaining) = sites.split_at(self.canary_size.clamp(1, sites.len()));
+            groups.push(canary);
+            rest = remaining;
+        }
+        groups.extend(rest.chunks(batch_size));
+
+        groups
+            .into_iter()
+            .map(|group| Batch {
+                sites: group
+                    .iter()
+                    .map(|id| SiteRollout { site_id: id.clone(), status: SiteRolloutStatus::Pending })
+                    .collect(),
+                status: BatchStatus::Pending,
+                started_at: None,
+                completed_at: None,
+            })
+            .collect()
+    }
+
+    /// Whether the batch after `index` needs an operator to resume
+    pub fn gates_after(&self, index: usize) -> bool {
+        match self.strategy {
+            RolloutStrategy::Rolling => false,
+            RolloutStrategy::Batched => true,
+            RolloutStrategy::Canary => index == 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchStatus {
+    Pending,
+    InProgress,
+    /// Applied, waiting on health probes
+    Verifying,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SiteRolloutStatus {
+    Pending,
+    Deploying,
+    Succeeded,
+    Failed,
+    RolledBack,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SiteRollout {
+    pub site_id: String,
+    pub status: SiteRolloutStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Batch {
+    pub sites: Vec<SiteRollout>,
+    pub status: BatchStatus,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl Batch {
+    pub fn finished_sites(&self) -> usize {
+        self.sites
+            .iter()
+            .filter(|s| s.status != SiteRolloutStatus::Pending && s.status != SiteRolloutStatus::Deploying)
+            .count()
+    }
+
+    /// Sites in a deployed batch whose health probe now fails
+    pub fn unhealthy_sites(&self, sites: &[Site]) -> Vec<String> {
+        if !matches!(self.status, BatchStatus::Verifying | BatchStatus::Completed | BatchStatus::Failed) {
+            return Vec::new();
+        }
+        self.sites
+            .iter()
+            .filter(|s| {
+                s.status == SiteRolloutStatus::Failed
+                    || sites
+                        .iter()
+                        .any(|site| site.id == s.site_id && site.status == SiteStatus::Critical)
+            })
+            .map(|s| s.site_id.clone())
+            .collect()
+    }
+}
+
+/// Parse "30s", "5m", "1h" or a bare number of seconds
+pub fn parse_duration(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (number, unit) = value.split_at(value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len()));
+    let number: u64 = number.parse().ok()?;
+    let multiplier = match unit.trim() {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return None,
+    };
+    number.checked_mul(multiplier)
+}
+
+pub fn format_duration(secs: u64) -> String {
+    match secs {
+        s if s >= 3600 && s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s >= 60 && s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+fn deserialize_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Secs(u64),
+        Text(String),
+    }
+    match Raw::deserialize(deserializer)? {
+        Raw::Secs(secs) => Ok(secs),
+        Raw::Text(text) => parse_duration(&text)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid duration: {}", text))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use crate::app::{App, DeploymentStatus};
+    use crate::config::Config;
+    use crate::fixtures::{app, fixture, press};
+    use crate::mock::MockPlane;
+
+    fn sites(n: usize) -> Vec<String> {
+        (1..=n).map(|i| format!("site-{}", i)).collect()
+    }
+
+    fn batch_sizes(policy: &RolloutPolicy, n: usize) -> Vec<usize> {
+        policy.plan(&sites(n)).iter().map(|b| b.sites.len()).collect()
+    }
+
+    #[test]
+    fn plan_splits_sites_into_batches() {
+        let rolling = RolloutPolicy { batch_size: 2, ..RolloutPolicy::default() };
+        assert_eq!(batch_sizes(&rolling, 5), [2, 2, 1]);
+        assert!(batch_sizes(&rolling, 0).is_empty());
+
+        let zero = RolloutPolicy { batch_size: 0, ..RolloutPolicy::default() };
+        assert_eq!(batch_sizes(&zero, 3), [1, 1, 1]);
+
+        let canary = RolloutPolicy { strategy: RolloutStrategy::Canary, batch_size: 3, canary_size: 1, ..RolloutPolicy::default() };
+        assert_eq!(batch_sizes(&canary, 6), [1, 3, 2]);
+        assert!(batch_sizes(&canary, 0).is_empty());
+        let oversized = RolloutPolicy { canary_size: 10, ..canary.clone() };
+        assert_eq!(batch_sizes(&oversized, 2), [2]);
+
+        let plan = canary.plan(&sites(4));
+        assert_eq!(plan[0].sites[0].site_id, "site-1");
+        assert!(plan.iter().all(|b| b.status == BatchStatus::Pending));
+        assert!(plan.iter().flat_map(|b| &b.sites).all(|s| s.status == SiteRolloutStatus::Pending));
+        assert!(canary.gates_after(0) && !canary.gates_after(1));
+    }
+
+    #[test]
+    fn durations() {
+        let cases = [
+            ("30", Some(30)),
+            ("30s", Some(30)),
+            ("5m", Some(300)),
+            (" 1h ", Some(3600)),
+            ("2d", Some(172_800)),
+            ("5 m", Some(300)),
+            ("", None),
+            ("m", None),
+            ("5w", None),
+            ("-5m", None),
+            ("18446744073709551615", Some(u64::MAX)),
+            ("18446744073709551615m", None),
+            ("99999999999999999999", None),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(parse_duration(text), expected, "{:?}", text);
+        }
+        assert_eq!(format_duration(3600), "1h");
+        assert_eq!(format_duration(90), "90s");
+    }
+
+    /// Fixture data with site-1, the completed canary batch of deploy-002, failing its probes
+    fn unhealthy_canary() -> crate::mock::Fixture {
+        let mut data = fixture();
+        data.plane.sites[0].status = SiteStatus::Critical;
+        data
+    }
+
+    #[tokio::test]
+    async fn unhealthy_batch_is_rolled_back() {
+        let mut app = app("rollout");
+        app.plane = Arc::new(MockPlane::new(unhealthy_canary(), "tester"));
+        press(&mut app, "r").await;
+
+        let deployment = app.pending_deployments.iter().find(|d| d.id == "deploy-002").unwrap();
+        assert_eq!(deployment.status, DeploymentStatus::RolledBack);
+        let alert = app.alerts.iter().find(|a| a.id == "rollout-deploy-002").unwrap();
+        assert_eq!(alert.site_id, "site-1");
+        assert_eq!(alert.message, "Deployment deploy-002 rolled back: health probes failed on site-1");
+        assert_eq!(app.audit_entries.len(), 1);
+        assert_eq!((app.audit_entries[0].action.as_str(), app.audit_entries[0].target.as_str()), ("auto-rollback", "deploy-002"));
+
+        // The control plane now reports it rolled back; nothing is asked twice
+        press(&mut app, "r").await;
+        assert_eq!(app.pending_deployments.iter().find(|d| d.id == "deploy-002").unwrap().status, DeploymentStatus::RolledBack);
+        assert_eq!(app.audit_entries.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn unhealthy_rollouts_are_flagged_on_load_and_left_alone_when_asked() {
+        let context = app("rollout-load").context.clone();
+        let plane = Arc::new(MockPlane::new(unhealthy_canary(), "tester"));
+        let loaded = App::from_data(context, &Config::default(), plane, unhealthy_canary().plane).unwrap();
+        let alert = loaded.alerts.iter().find(|a| a.id == "rollout-deploy-002").expect("not flagged at startup");
+        assert!(alert.message.ends_with("; rolling back"), "{}", alert.message);
+
+        let mut manual = unhealthy_canary();
+        manual.plane.pending_deployments[1].rollout.rollback_on_failure = false;
+        let mut manual_app = app("rollout-manual");
+        manual_app.plane = Arc::new(MockPlane::new(manual, "tester"));
+        press(&mut manual_app, "r").await;
+        assert_eq!(manual_app.pending_deployments[1].status, DeploymentStatus::InProgress);
+        let alert = manual_app.alerts.iter().find(|a| a.id == "rollout-deploy-002").unwrap();
+        assert!(alert.message.ends_with("; abort or roll back manually"), "{}", alert.message);
+        let raised = alert.timestamp;
+        manual_app.clock = Some(raised + chrono::Duration::minutes(5));
+        press(&mut manual_app, "r").await;
+        let alert = manual_app.alerts.iter().find(|a| a.id == "rollout-deploy-002").unwrap();
+        assert_eq!(alert.timestamp, raised);
+        assert!(manual_app.audit_entries.is_empty());
+
+        let mut read_only = app("rollout-read-only");
+        read_only.read_only = true;
+        read_only.plane = Arc::new(MockPlane::new(unhealthy_canary(), "tester"));
+        press(&mut read_only, "r").await;
+        assert_eq!(read_only.pending_deployments[1].status, DeploymentStatus::InProgress);
+        let alert = read_only.alerts.iter().find(|a| a.id == "rollout-deploy-002").unwrap();
+        assert!(alert.message.ends_with("cannot roll it back"), "{}", alert.message);
+    }
+}
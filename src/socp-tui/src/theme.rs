@@ -0,0 +1,495 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Colour themes
+//!
+//! The UI asks for styles by role (ok, warning, selection, ...) rather than
+//! by colour. Built-in palettes can be extended with `[themes.<name>]` tables
+//! in the config, and colours are downgraded to what the terminal supports:
+//! truecolor, 256 colours, 16 colours, or none at all when `NO_COLOR` is set.
+
+use anyhow::{anyhow, bail, Result};
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const BUILTIN_THEMES: [&str; 4] = ["dark", "light", "high-contrast", "colorblind"];
+
+/// A user-defined theme; unset roles come from `base`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThemeSpec {
+    /// Theme to start from (default "dark")
+    pub base: Option<String>,
+    pub text: Option<String>,
+    pub muted: Option<String>,
+    pub selection: Option<String>,
+    pub selection_text: Option<String>,
+    pub highlight: Option<String>,
+    pub info: Option<String>,
+    pub accent: Option<String>,
+    pub ok: Option<String>,
+    pub warning: Option<String>,
+    pub error: Option<String>,
+    pub badge_text: Option<String>,
+    pub status_bar: Option<String>,
+    pub status_bar_text: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Palette {
+    text: Color,
+    muted: Color,
+    selection: Color,
+    /// `Reset` keeps each cell's own colour
+    selection_text: Color,
+    highlight: Color,
+    info: Color,
+    accent: Color,
+    ok: Color,
+    warning: Color,
+    error: Color,
+    badge_text: Color,
+    status_bar: Color,
+    status_bar_text: Color,
+}
+
+const DARK: Palette = Palette {
+    text: Color::White,
+    muted: Color::Gray,
+    selection: Color::DarkGray,
+    selection_text: Color::Reset,
+    highlight: Color::Cyan,
+    info: Color::Cyan,
+    accent: Color::Magenta,
+    ok: Color::Green,
+    warning: Color::Yellow,
+    error: Color::Red,
+    badge_text: Color::Black,
+    status_bar: Color::DarkGray,
+    status_bar_text: Color::White,
+};
+
+const LIGHT: Palette = Palette {
+    text: Color::Black,
+    muted: Color::Rgb(88, 88, 88),
+    selection: Color::Rgb(208, 208, 208),
+    selection_text: Color::Reset,
+    highlight: Color::Rgb(0, 95, 175),
+    info: Color::Rgb(0, 95, 175),
+    accent: Color::Rgb(135, 0, 135),
+    ok: Color::Rgb(0, 128, 0),
+    warning: Color::Rgb(175, 95, 0),
+    error: Color::Rgb(175, 0, 0),
+    badge_text: Color::White,
+    status_bar: Color::Rgb(208, 208, 208),
+    status_bar_text: Color::Black,
+};
+
+const HIGH_CONTRAST: Palette = Palette {
+    text: Color::White,
+    muted: Color::White,
+    selection: Color::White,
+    selection_text: Color::Black,
+    highlight: Color::LightYellow,
+    info: Color::LightCyan,
+    accent: Color::LightMagenta,
+    ok: Color::LightGreen,
+    warning: Color::LightYellow,
+    error: Color::LightRed,
+    badge_text: Color::Black,
+    status_bar: Color::White,
+    status_bar_text: Color::Black,
+};
+
+/// Okabe-Ito colours, distinguishable with the common colour-vision deficiencies
+const COLORBLIND: Palette = Palette {
+    text: Color::White,
+    muted: Color::Gray,
+    selection: Color::DarkGray,
+    selection_text: Color::Reset,
+    highlight: Color::Rgb(86, 180, 233),
+    info: Color::Rgb(86, 180, 233),
+    accent: Color::Rgb(204, 121, 167),
+    ok: Color::Rgb(0, 114, 178),
+    warning: Color::Rgb(240, 228, 66),
+    error: Color::Rgb(213, 94, 0),
+    badge_text: Color::Black,
+    status_bar: Color::DarkGray,
+    status_bar_text: Color::White,
+};
+
+fn builtin(name: &str) -> Option<Palette> {
+    match name {
+        "dark" => Some(DARK),
+        "light" => Some(LIGHT),
+        "high-contrast" => Some(HIGH_CONTRAST),
+        "colorblind" => Some(COLORBLIND),
+        _ => None,
+    }
+}
+
+/// Colours the terminal can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    Mono,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// From `NO_COLOR`, `COLORTERM` and `TERM`
+    pub fn detect() -> Self {
+        Self::from_env(|name| std::env::var(name).unwrap_or_default())
+    }
+
+    /// [`ColorDepth::detect`] with `var` standing in for the environment
+    fn from_env(var: impl Fn(&str) -> String) -> Self {
+        if !var("NO_COLOR").is_empty() {
+            return ColorDepth::Mono;
+        }
+        let term = var("TERM");
+        if term == "dumb" {
+            ColorDepth::Mono
+        } else if matches!(var("COLORTERM").as_str(), "truecolor" | "24bit") {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+}
+
+/// xterm's default RGB values for the 16 ANSI colours
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Levels of the 6x6x6 cube in the 256-colour palette
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).unsigned_abs();
+    d(r1, r2).pow(2) + d(g1, g2).pow(2) + d(b1, b2).pow(2)
+}
+
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI16[usize::from(index)].1,
+        16..=231 => {
+            let i = index - 16;
+            (CUBE[usize::from(i / 36)], CUBE[usize::from(i / 6 % 6)], CUBE[usize::from(i % 6)])
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+fn nearest_256(rgb: (u8, u8, u8)) -> Color {
+    let level = |c: u8| (0..6).min_by_key(|&i| CUBE[i].abs_diff(c)).unwrap_or(0);
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = 16 + 36 * r + 6 * g + b;
+    let gray = 232 + (0..24u8).min_by_key(|&i| distance(indexed_rgb(232 + i), rgb)).unwrap_or(0);
+    let best = [cube as u8, gray]
+        .into_iter()
+        .min_by_key(|&i| distance(indexed_rgb(i), rgb))
+        .unwrap_or(gray);
+    Color::Indexed(best)
+}
+
+fn nearest_16(rgb: (u8, u8, u8)) -> Color {
+    ANSI16.iter()
+        .min_by_key(|(_, ansi)| distance(*ansi, rgb))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+fn downgrade(color: Color, depth: ColorDepth) -> Color {
+    match (color, depth) {
+        (_, ColorDepth::Mono) => Color::Reset,
+        (Color::Rgb(r, g, b), ColorDepth::Ansi256) => nearest_256((r, g, b)),
+        (Color::Rgb(r, g, b), ColorDepth::Ansi16) => nearest_16((r, g, b)),
+        (Color::Indexed(i), ColorDepth::Ansi16) if i >= 16 => nearest_16(indexed_rgb(i)),
+        (color, _) => color,
+    }
+}
+
+fn parse_color(theme: &str, role: &str, value: &Option<String>, fallback: Color) -> Result<Color> {
+    match value {
+        Some(value) => value.parse().map_err(|_| {
+            anyhow!("themes.{}.{}: '{}' is not a colour name, #rrggbb or 0-255 index", theme, role, value)
+        }),
+        None => Ok(fallback),
+    }
+}
+
+fn resolve(name: &str, custom: &BTreeMap<String, ThemeSpec>, depth: usize) -> Result<Palette> {
+    if let Some(palette) = builtin(name) {
+        return Ok(palette);
+    }
+    let Some(spec) = custom.get(name) else {
+        let mut known: Vec<&str> = BUILTIN_THEMES.to_vec();
+        known.extend(custom.keys().map(String::as_str));
+        bail!("unknown theme '{}' (available: {})", name, known.join(", "));
+    };
+    if depth > custom.len() {
+        bail!("themes.{}: 'base' chain loops", name);
+    }
+    let base = spec.base.as_deref().unwrap_or("dark");
+    if builtin(base).is_none() && !custom.contains_key(base) {
+        bail!("themes.{}.base: unknown theme '{}'", name, base);
+    }
+    let base = resolve(base, custom, depth + 1)?;
+    let color = |role, value, fallback| parse_color(name, role, value, fallback);
+    Ok(Palette {
+        text: color("text", &spec.text, base.text)?,
+        muted: color("muted", &spec.muted, base.muted)?,
+        selection: color("selection", &spec.selection, base.selection)?,
+        selection_text: color("selection_text", &spec.selection_text, base.selection_text)?,
+        highlight: color("highlight", &spec.highlight, base.highlight)?,
+        info: color("info", &spec.info, base.info)?,
+        accent: color("accent", &spec.accent, base.accent)?,
+        ok: color("ok", &spec.ok, base.ok)?,
+        warning: color("warning", &spec.warning, base.warning)?,
+        error: color("error", &spec.error, base.error)?,
+        badge_text: color("badge_text", &spec.badge_text, base.badge_text)?,
+        status_bar: color("status_bar", &spec.status_bar, base.status_bar)?,
+        status_bar_text: color("status_bar_text", &spec.status_bar_text, base.status_bar_text)?,
+    })
+}
+
+/// A palette resolved for the terminal's colour depth
+#[derive(Debug, Clone)]
+pub struct Theme {
+    palette: Palette,
+    depth: ColorDepth,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(DARK, ColorDepth::detect())
+    }
+}
+
+impl Theme {
+    fn new(palette: Palette, depth: ColorDepth) -> Self {
+        let c = |color| downgrade(color, depth);
+        let palette = Palette {
+            text: c(palette.text),
+            muted: c(palette.muted),
+            selection: c(palette.selection),
+            selection_text: c(palette.selection_text),
+            highlight: c(palette.highlight),
+            info: c(palette.info),
+            accent: c(palette.accent),
+            ok: c(palette.ok),
+            warning: c(palette.warning),
+            error: c(palette.error),
+            badge_text: c(palette.badge_text),
+            status_bar: c(palette.status_bar),
+            status_bar_text: c(palette.status_bar_text),
+        };
+        Self { palette, depth }
+    }
+
+    /// Built-in or `[themes.<name>]` theme, for the detected colour depth
+    pub fn load(name: &str, custom: &BTreeMap<String, ThemeSpec>) -> Result<Self> {
+        Ok(Self::new(resolve(name, custom, 0)?, ColorDepth::detect()))
+    }
+
+    /// Checks every theme in the config, for `Config::validate`
+    pub fn check(custom: &BTreeMap<String, ThemeSpec>) -> Vec<String> {
+        custom.keys()
+            .filter_map(|name| resolve(name, custom, 0).err())
+            .map(|e| e.to_string())
+            .collect()
+    }
+
+    fn mono(&self) -> bool {
+        self.depth == ColorDepth::Mono
+    }
+
+    fn fg(&self, color: Color) -> Style {
+        Style::default().fg(color)
+    }
+
+    pub fn text(&self) -> Style {
+        self.fg(self.palette.text)
+    }
+
+    pub fn muted(&self) -> Style {
+        if self.mono() {
+            Style::default().add_modifier(Modifier::DIM)
+        } else {
+            self.fg(self.palette.muted)
+        }
+    }
+
+    pub fn highlight(&self) -> Style {
+        self.fg(self.palette.highlight).add_modifier(Modifier::BOLD)
+    }
+
+    pub fn info(&self) -> Style {
+        self.fg(self.palette.info)
+    }
+
+    pub fn accent(&self) -> Style {
+        self.fg(self.palette.accent)
+    }
+
+    pub fn ok(&self) -> Style {
+        self.fg(self.palette.ok)
+    }
+
+    pub fn warning(&self) -> Style {
+        self.fg(self.palette.warning)
+    }
+
+    /// Bold without colour so failures still stand out in monochrome
+    pub fn error(&self) -> Style {
+        if self.mono() {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            self.fg(self.palette.error)
+        }
+    }
+
+    /// Selected table row or list entry
+    pub fn selected(&self) -> Style {
+        if self.mono() {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default().bg(self.palette.selection).fg(self.palette.selection_text)
+        }
+    }
+
+    /// Label drawn on a solid block of `tone`'s colour, e.g. `badge(theme.warning())`
+    pub fn badge(&self, tone: Style) -> Style {
+        let style = if self.mono() {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default().fg(self.palette.badge_text).bg(tone.fg.unwrap_or(self.palette.info))
+        };
+        style.add_modifier(Modifier::BOLD)
+    }
+
+    pub fn status_bar(&self) -> Style {
+        if self.mono() {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default().bg(self.palette.status_bar).fg(self.palette.status_bar_text)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colours_map_to_their_nearest_neighbour() {
+        let cases = [
+            ((0, 0, 0), Color::Indexed(16), Color::Black),
+            ((255, 255, 255), Color::Indexed(231), Color::White),
+            ((255, 0, 0), Color::Indexed(196), Color::LightRed),
+            ((200, 10, 10), Color::Indexed(160), Color::Red),
+            ((0, 95, 135), Color::Indexed(24), Color::Cyan),
+            // Greys land on the grey ramp rather than the cube
+            ((128, 128, 128), Color::Indexed(244), Color::DarkGray),
+            ((100, 100, 100), Color::Indexed(241), Color::DarkGray),
+            ((240, 240, 240), Color::Indexed(255), Color::Gray),
+        ];
+        for (rgb, ansi256, ansi16) in cases {
+            assert_eq!(nearest_256(rgb), ansi256, "{:?} in 256 colours", rgb);
+            assert_eq!(nearest_16(rgb), ansi16, "{:?} in 16 colours", rgb);
+        }
+    }
+
+    #[test]
+    fn colours_downgrade_to_the_terminal_depth() {
+        let cases = [
+            (Color::Rgb(255, 0, 0), ColorDepth::TrueColor, Color::Rgb(255, 0, 0)),
+            (Color::Rgb(255, 0, 0), ColorDepth::Ansi256, Color::Indexed(196)),
+            (Color::Rgb(255, 0, 0), ColorDepth::Ansi16, Color::LightRed),
+            (Color::Rgb(255, 0, 0), ColorDepth::Mono, Color::Reset),
+            (Color::Indexed(196), ColorDepth::Ansi256, Color::Indexed(196)),
+            (Color::Indexed(196), ColorDepth::Ansi16, Color::LightRed),
+            (Color::Indexed(9), ColorDepth::Ansi16, Color::Indexed(9)),
+            (Color::Blue, ColorDepth::Ansi16, Color::Blue),
+            (Color::Blue, ColorDepth::Mono, Color::Reset),
+            (Color::Reset, ColorDepth::Ansi256, Color::Reset),
+        ];
+        for (color, depth, expected) in cases {
+            assert_eq!(downgrade(color, depth), expected, "{:?} at {:?}", color, depth);
+        }
+    }
+
+    #[test]
+    fn depth_follows_the_environment() {
+        let cases = [
+            (("1", "xterm-256color", "truecolor"), ColorDepth::Mono),
+            (("", "xterm-256color", "truecolor"), ColorDepth::TrueColor),
+            (("", "xterm", "24bit"), ColorDepth::TrueColor),
+            (("", "dumb", "truecolor"), ColorDepth::Mono),
+            (("", "screen-256color", ""), ColorDepth::Ansi256),
+            (("", "xterm", ""), ColorDepth::Ansi16),
+            (("", "", ""), ColorDepth::Ansi16),
+        ];
+        for ((no_color, term, colorterm), expected) in cases {
+            let depth = ColorDepth::from_env(|name| match name {
+                "NO_COLOR" => no_color,
+                "TERM" => term,
+                "COLORTERM" => colorterm,
+                _ => "",
+            }.to_string());
+            assert_eq!(depth, expected, "NO_COLOR={:?} TERM={:?} COLORTERM={:?}", no_color, term, colorterm);
+        }
+    }
+
+    #[test]
+    fn base_chains_resolve_and_loops_are_reported() {
+        let spec = |base: &str| ThemeSpec { base: Some(base.to_string()), ..ThemeSpec::default() };
+        let themes = |specs: &[(&str, ThemeSpec)]| -> BTreeMap<String, ThemeSpec> {
+            specs.iter().map(|(name, spec)| (name.to_string(), spec.clone())).collect()
+        };
+
+        let chained = themes(&[
+            ("mine", spec("brand")),
+            ("brand", ThemeSpec { ok: Some("#00ff00".to_string()), ..spec("light") }),
+        ]);
+        let palette = resolve("mine", &chained, 0).unwrap();
+        assert_eq!(palette.ok, Color::Rgb(0, 255, 0));
+        assert_eq!(palette.text, LIGHT.text);
+        assert!(Theme::check(&chained).is_empty());
+
+        // Every theme on or leading into a loop is reported, naming one on the loop
+        let cases = [
+            (themes(&[("a", spec("a"))]), 1, "'base' chain loops"),
+            (themes(&[("a", spec("b")), ("b", spec("a"))]), 2, "'base' chain loops"),
+            (themes(&[("a", spec("b")), ("b", spec("c")), ("c", spec("a")), ("d", spec("a"))]), 4, "'base' chain loops"),
+            (themes(&[("a", spec("b")), ("b", spec("b")), ("c", spec("dark"))]), 2, "'base' chain loops"),
+            (themes(&[("a", spec("missing"))]), 1, "themes.a.base: unknown theme 'missing'"),
+        ];
+        for (custom, count, message) in cases {
+            let problems = Theme::check(&custom);
+            assert_eq!(problems.len(), count, "{:?}", problems);
+            assert!(problems.iter().all(|p| p.ends_with(message)), "{:?}", problems);
+        }
+    }
+}
@@ -3,12 +3,35 @@
 
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Tabs, Wrap},
+    widgets::{Block, Borders, Cell, Clear, Gauge, Paragraph, Row, Sparkline, Table, Tabs, Wrap},
 };
 
-use crate::app::{App, SiteStatus, View};
+use crate::app::{AlertSeverity, App, ConfirmInput, DeploymentStatus, HitAreas, SiteStatus, View};
+use crate::composer::{ChangeMode, ComposerField, TargetMode};
+use crate::dnssec::algorithm_name;
+use crate::federation::Fleet;
+use crate::keymap::{show_sequence, Command, Mode};
+use crate::layout::{Breakpoint, Panel};
+use crate::maintenance::{timeline, SLOTS_PER_DAY};
+use crate::notify::{Level, Notification};
+use crate::policy::Action;
+use crate::rollout::{format_duration, BatchStatus, RolloutStrategy, SiteRolloutStatus};
+use crate::theme::Theme;
+use crate::wordpress::{Divergence, PluginState};
+
+/// Certificates expiring within this many days appear on the dashboard
+const CERT_WARN_DAYS: i64 = 30;
+/// ...and are shown as critical within this many
+const CERT_CRITICAL_DAYS: i64 = 7;
+
+/// Popups shrink with the screen down to this size, then fill it
+const MIN_POPUP_WIDTH: u16 = 60;
+const MIN_POPUP_HEIGHT: u16 = 10;
+const TAB_MENU_WIDTH: u16 = 24;
+const TOAST_WIDTH: u16 = 44;
 
 pub fn draw(frame: &mut Frame, app: &App) {
+    *app.hit_areas.borrow_mut() = HitAreas::default();
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -26,109 +49,356 @@ pub fn draw(frame: &mut Frame, app: &App) {
     if app.show_popup {
         draw_popup(frame, app);
     }
+    if app.context_picker.is_some() {
+        draw_context_picker(frame, app);
+    }
+    if app.tab_menu.is_some() {
+        draw_tab_menu(frame, app);
+    }
+    if app.notification_center.is_some() {
+        draw_notification_center(frame, app);
+    }
+    if app.palette.is_some() {
+        draw_palette(frame, app);
+    }
+    draw_toasts(frame, app);
+    if app.confirmation.is_some() {
+        draw_confirmation(frame, app);
+    }
 }
 
 fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
-    let titles = vec!["Dashboard", "Sites", "Deployments", "Alerts", "Logs", "Help"];
-    let selected = match app.view {
-        View::Dashboard => 0,
-        View::SiteList | View::SiteDetail => 1,
-        View::Deployments => 2,
-        View::Alerts => 3,
-        View::Logs => 4,
-        View::Help => 5,
-        View::Secrets => 1,
-    };
+    let tabs = app.tabs();
+    let selected = tabs.iter().position(|view| *view == app.view.tab()).unwrap_or(0);
+    let titles: Vec<&str> = tabs.iter().map(|view| view.title()).collect();
+    let narrow = Breakpoint::of(area.width) == Breakpoint::Narrow;
+
+    let mut title = vec![Span::raw(" SOCP ")];
+    match &app.fleet {
+        Some(fleet) => {
+            title.push(Span::styled(" FEDERATED ", app.theme.badge(app.theme.accent())));
+            title.push(Span::styled(format!(" {} ", fleet.names().join(", ")), app.theme.accent()));
+        }
+        None => {
+            title.push(Span::styled(format!(" {} ", app.context.name), app.theme.badge(app.theme.info())));
+            if !narrow {
+                title.push(Span::styled(format!(" {} ", app.context.api_url), app.theme.info()));
+            }
+        }
+    }
+    if let Some(role) = app.permissions.role.as_ref().filter(|_| !narrow) {
+        title.push(Span::styled(format!("{} ({}) ", app.operator, role), app.theme.muted()));
+    }
+    if app.read_only {
+        title.push(Span::styled(" READ-ONLY ", app.theme.badge(app.theme.warning())));
+    }
+    let mut crumbs = Vec::new();
+    for (i, crumb) in app.breadcrumbs().into_iter().enumerate() {
+        if i > 0 {
+            crumbs.push(Span::styled(" › ", app.theme.muted()));
+        }
+        crumbs.push(Span::raw(crumb));
+    }
+    crumbs.push(Span::raw(" "));
+    let block = Block::default()
+        .borders(Borders::BOTTOM)
+        .title(Line::from(title))
+        .title(Line::from(crumbs).right_aligned());
+    let row = block.inner(area);
+
+    // Tabs pad each title by one column either side and divide them with one
+    let widths: Vec<u16> = titles.iter().map(|title| title.chars().count() as u16 + 2).collect();
+    let total = widths.iter().sum::<u16>() + widths.len().saturating_sub(1) as u16;
+    let mut hits = app.hit_areas.borrow_mut();
+    if total > row.width {
+        // Too narrow for every tab: show the current one and open the rest as a menu
+        let key = app.keymap.key_for(Mode::Global, Command::TabMenu);
+        let label = Line::from(vec![
+            Span::styled(format!(" ≡ {} ", app.view.tab().title()), app.theme.highlight()),
+            Span::styled(format!(" {}/{}", selected + 1, tabs.len()), app.theme.muted()),
+            Span::styled(key.map(|k| format!("  [{}] views", k)).unwrap_or_default(), app.theme.muted()),
+        ]);
+        hits.menu = Some(Rect { width: label.width() as u16, height: 1, ..row }.intersection(row));
+        frame.render_widget(Paragraph::new(label).block(block).style(app.theme.text()), area);
+        return;
+    }
 
-    let tabs = Tabs::new(titles)
-        .block(Block::default().borders(Borders::BOTTOM).title(" SOCP "))
+    let mut x = row.x;
+    for (view, width) in tabs.into_iter().zip(widths) {
+        hits.tabs.push((Rect::new(x, row.y, width, 1).intersection(row), view));
+        x = x.saturating_add(width + 1);
+    }
+    let widget = Tabs::new(titles)
+        .block(block)
         .select(selected)
-        .style(Style::default().fg(Color::White))
-        .highlight_style(Style::default().fg(Color::Cyan).bold());
+        .style(app.theme.text())
+        .highlight_style(app.theme.highlight());
+    frame.render_widget(widget, area);
+}
 
-    frame.render_widget(tabs, area);
+/// The table columns `keep` marks, for dropping columns on narrow screens
+fn shown<T, const N: usize>(columns: [T; N], keep: &[bool; N]) -> Vec<T> {
+    columns.into_iter().zip(keep).filter(|(_, keep)| **keep).map(|(column, _)| column).collect()
+}
+
+/// Record where a bordered list draws its items, for mouse selection
+fn record_rows(app: &App, area: Rect, header: u16) {
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    let rows = Rect {
+        y: inner.y + header.min(inner.height),
+        height: inner.height.saturating_sub(header),
+        ..inner
+    };
+    app.hit_areas.borrow_mut().rows = Some(rows);
 }
 
 fn draw_content(frame: &mut Frame, app: &App, area: Rect) {
     match app.view {
         View::Dashboard => draw_dashboard(frame, app, area),
+        View::SiteList | View::SiteDetail if split_pane(app, area) => {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .split(area);
+            draw_site_list(frame, app, chunks[0]);
+            draw_site_detail(frame, app, chunks[1]);
+        }
         View::SiteList => draw_site_list(frame, app, area),
         View::SiteDetail => draw_site_detail(frame, app, area),
+        View::WordPress => draw_wordpress(frame, app, area),
         View::Deployments => draw_deployments(frame, app, area),
+        View::Composer => draw_composer(frame, app, area),
+        View::History => draw_history(frame, app, area),
         View::Alerts => draw_alerts(frame, app, area),
-        View::Help => draw_help(frame, area),
+        View::Logs => draw_audit_log(frame, app, area),
+        View::Vulnerabilities => draw_vulnerabilities(frame, app, area),
+        View::Help => draw_help(frame, app, area),
         _ => {}
     }
 }
 
+/// Site list and detail side by side when the terminal is wide enough
+fn split_pane(app: &App, area: Rect) -> bool {
+    app.layout.split_min_width > 0 && area.width >= app.layout.split_min_width
+}
+
 fn draw_dashboard(frame: &mut Frame, app: &App, area: Rect) {
-    let chunks = Layout::default()
+    let columns = &app.layout.dashboard;
+    let column_areas = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .constraints(columns.iter().map(|_| Constraint::Ratio(1, columns.len() as u32)))
         .split(area);
 
-    let left_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(7), Constraint::Min(0)])
-        .split(chunks[0]);
-
-    // Summary stats
-    let stats_text = format!(
-        "\n  Sites:  {} total\n  \x1b[32m●\x1b[0m Healthy: {}   \x1b[33m●\x1b[0m Warning: {}   \x1b[31m●\x1b[0m Critical: {}\n\n  Pending: {} deployments   Alerts: {}",
-        app.sites.len(),
-        app.healthy_sites(),
-        app.warning_sites(),
-        app.critical_sites(),
-        app.pending_deployments.len(),
-        app.unacknowledged_alerts(),
-    );
-
-    let stats = Paragraph::new(stats_text)
-        .block(Block::default().borders(Borders::ALL).title(" Overview "));
-    frame.render_widget(stats, left_chunks[0]);
+    for (panels, column_area) in columns.iter().zip(column_areas.iter()) {
+        let areas = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(panels.iter().map(|p| p.height().map_or(Constraint::Fill(1), Constraint::Length)))
+            .split(*column_area);
+        for (panel, panel_area) in panels.iter().zip(areas.iter()) {
+            draw_panel(frame, app, *panel, *panel_area);
+        }
+    }
+}
 
-    // Recent activity
-    let activity = Paragraph::new("\n  Coming soon...")
-        .block(Block::default().borders(Borders::ALL).title(" Recent Activity "));
-    frame.render_widget(activity, left_chunks[1]);
+fn draw_panel(frame: &mut Frame, app: &App, panel: Panel, area: Rect) {
+    let block = Block::default().borders(Borders::ALL).title(format!(" {} ", panel.title()));
+    let lines = match panel {
+        Panel::Overview => {
+            // Short labels when the panel is too narrow for the long ones
+            let [healthy, warning, critical, deployments] = if area.width < 50 {
+                ["ok", "warn", "crit", ""]
+            } else {
+                ["Healthy:", "Warning:", "Critical:", " deployments"]
+            };
+            vec![
+                Line::raw(""),
+                Line::raw(format!("  Sites:  {} total", app.sites.len())),
+                Line::from(vec![
+                    Span::styled("  ●", app.theme.ok()),
+                    Span::raw(format!(" {} {}   ", healthy, app.healthy_sites())),
+                    Span::styled("●", app.theme.warning()),
+                    Span::raw(format!(" {} {}   ", warning, app.warning_sites())),
+                    Span::styled("●", app.theme.error()),
+                    Span::raw(format!(" {} {}", critical, app.critical_sites())),
+                ]),
+                Line::raw(""),
+                Line::raw(format!(
+                    "  Pending: {}{}   Alerts: {}",
+                    app.pending_deployments.len(),
+                    deployments,
+                    app.unacknowledged_alerts(),
+                )),
+            ]
+        }
+        Panel::NeedsAttention => {
+            let attention: Vec<Line> = app.sites.iter()
+                .filter(|s| s.status != SiteStatus::Healthy)
+                .take(10)
+                .map(|s| Line::raw(format!("  {} - {:?}", s.domain, s.status)))
+                .collect();
+            if attention.is_empty() {
+                vec![Line::raw(""), Line::raw("  All sites healthy!")]
+            } else {
+                std::iter::once(Line::raw("")).chain(attention).collect()
+            }
+        }
+        Panel::Activity => {
+            if let Some(fleet) = &app.fleet {
+                draw_planes(frame, fleet, &app.theme, area);
+                return;
+            }
+            let mut lines = vec![Line::raw("")];
+            let recent = app.audit_entries.iter().rev().take(area.height.saturating_sub(3) as usize);
+            for entry in recent {
+                let style = if entry.succeeded() { app.theme.text() } else { app.theme.error() };
+                lines.push(Line::from(vec![
+                    Span::styled(format!("  {} ", entry.timestamp.format("%m-%d %H:%M")), app.theme.muted()),
+                    Span::styled(format!("{} {} {}", entry.operator, entry.action, entry.target), style),
+                ]));
+            }
+            if lines.len() == 1 {
+                lines.push(Line::raw("  No recorded actions"));
+            }
+            lines
+        }
+        Panel::ExpiringCerts => {
+            let now = app.now();
+            let mut expiring: Vec<_> = app.sites.iter()
+                .filter_map(|s| s.ssl_expires.map(|at| (at, s)))
+                .filter(|(at, _)| *at - now < chrono::Duration::days(CERT_WARN_DAYS))
+                .collect();
+            expiring.sort_by_key(|(at, _)| *at);
+            let mut lines = vec![Line::raw("")];
+            for (at, site) in expiring.into_iter().take(10) {
+                let days = (at - now).num_days();
+                let (text, style) = if at < now {
+                    ("expired".to_string(), app.theme.error())
+                } else if days < CERT_CRITICAL_DAYS {
+                    (format!("{}d", days), app.theme.error())
+                } else {
+                    (format!("{}d", days), app.theme.warning())
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(format!("  {:>8} ", text), style),
+                    Span::raw(site.domain.clone()),
+                ]));
+            }
+            if lines.len() == 1 {
+                lines.push(Line::raw(format!("  None within {} days", CERT_WARN_DAYS)));
+            }
+            lines
+        }
+        Panel::PendingDeployments => {
+            let mut lines = vec![Line::raw("")];
+            for d in &app.pending_deployments {
+                let (done, total) = d.progress();
+                lines.push(Line::from(vec![
+                    Span::raw(format!("  {} {} ", d.id, d.change_type)),
+                    Span::styled(format!("{:?}", d.status), deployment_status_style(&app.theme, d.status)),
+                    Span::styled(format!(" {}/{}", done, total), app.theme.muted()),
+                ]));
+            }
+            if lines.len() == 1 {
+                lines.push(Line::raw("  No pending deployments"));
+            }
+            lines
+        }
+        Panel::AlertHistogram => {
+            draw_alert_histogram(frame, app, block, area);
+            return;
+        }
+        Panel::Notifications => {
+            let mut lines = vec![Line::raw("")];
+            let recent = app.notifications.history().take(area.height.saturating_sub(3) as usize);
+            lines.extend(recent.map(|n| notification_line(app, n)));
+            if lines.len() == 1 {
+                lines.push(Line::raw("  Nothing yet"));
+            }
+            lines
+        }
+    };
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
 
-    // Sites needing attention
-    let attention_sites: Vec<&_> = app.sites.iter()
-        .filter(|s| s.status != SiteStatus::Healthy)
-        .take(10)
-        .collect();
+/// Alerts per hour over the last day, oldest on the left
+fn draw_alert_histogram(frame: &mut Frame, app: &App, block: Block, area: Rect) {
+    let now = app.now();
+    let mut buckets = [0u64; 24];
+    for alert in &app.alerts {
+        let hours = (now - alert.timestamp).num_hours();
+        if (0..24).contains(&hours) {
+            buckets[23 - hours as usize] += 1;
+        }
+    }
+    let count = |severity| app.alerts.iter().filter(|a| a.severity == severity).count();
 
-    let attention_text = if attention_sites.is_empty() {
-        "\n  All sites healthy!".to_string()
-    } else {
-        attention_sites.iter()
-            .map(|s| format!("  {} - {:?}", s.domain, s.status))
-            .collect::<Vec<_>>()
-            .join("\n")
-    };
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(inner);
+    let summary = Line::from(vec![
+        Span::styled(format!(" Critical {}", count(AlertSeverity::Critical)), app.theme.error()),
+        Span::styled(format!("  Warning {}", count(AlertSeverity::Warning)), app.theme.warning()),
+        Span::styled(format!("  Info {}", count(AlertSeverity::Info)), app.theme.info()),
+    ]);
+    frame.render_widget(Paragraph::new(summary), chunks[0]);
+    frame.render_widget(Sparkline::default().data(buckets).style(app.theme.accent()), chunks[1]);
+}
 
-    let attention = Paragraph::new(format!("\n{}", attention_text))
-        .block(Block::default().borders(Borders::ALL).title(" Needs Attention "));
-    frame.render_widget(attention, chunks[1]);
+fn draw_planes(frame: &mut Frame, fleet: &Fleet, theme: &Theme, area: Rect) {
+    let mut lines = vec![Line::raw("")];
+    for plane in &fleet.planes {
+        let (icon, style) = match &plane.error {
+            None => ("●", theme.ok()),
+            Some(_) => ("○", theme.error()),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {} ", icon), style),
+            Span::raw(format!("{:<14} {:>3} sites  ", plane.context.name, plane.site_count())),
+            Span::styled(plane.context.api_url.clone(), theme.muted()),
+        ]));
+        if let Some(error) = &plane.error {
+            let note = match plane.last_success {
+                Some(at) if plane.is_stale() => format!("      stale since {}: {}", at.format("%H:%M:%S"), error),
+                _ => format!("      unavailable: {}", error),
+            };
+            lines.push(Line::styled(note, theme.error()));
+        }
+    }
+    let para = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(" Control Planes "))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(para, area);
 }
 
 fn draw_site_list(frame: &mut Frame, app: &App, area: Rect) {
-    let header = Row::new(vec![
-        Cell::from("Status"),
+    let breakpoint = Breakpoint::of(area.width);
+    let narrow = breakpoint == Breakpoint::Narrow;
+    let federated = app.fleet.is_some();
+    let mut header = vec![
+        Cell::from(if narrow { "" } else { "Status" }),
         Cell::from("Domain"),
-        Cell::from("Environment"),
+        Cell::from(if narrow { "Env" } else { "Environment" }),
         Cell::from("Last Sync"),
-        Cell::from("Response"),
-    ]).style(Style::default().bold());
+    ];
+    if !narrow {
+        header.push(Cell::from("Response"));
+    }
+    if federated {
+        header.push(Cell::from("Plane"));
+    }
+    let header = Row::new(header).style(Style::default().bold());
 
     let rows: Vec<Row> = app.sites.iter().enumerate().map(|(i, site)| {
         let status_style = match site.status {
-            SiteStatus::Healthy => Style::default().fg(Color::Green),
-            SiteStatus::Warning => Style::default().fg(Color::Yellow),
-            SiteStatus::Critical => Style::default().fg(Color::Red),
-            SiteStatus::Drifted => Style::default().fg(Color::Magenta),
-            SiteStatus::Syncing => Style::default().fg(Color::Cyan),
-            SiteStatus::Unknown => Style::default().fg(Color::Gray),
+            SiteStatus::Healthy => app.theme.ok(),
+            SiteStatus::Warning => app.theme.warning(),
+            SiteStatus::Critical => app.theme.error(),
+            SiteStatus::Drifted => app.theme.accent(),
+            SiteStatus::Syncing => app.theme.info(),
+            SiteStatus::Unknown => app.theme.muted(),
         };
 
         let status_icon = match site.status {
@@ -141,7 +411,7 @@ fn draw_site_list(frame: &mut Frame, app: &App, area: Rect) {
         };
 
         let last_sync = site.last_sync
-            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .map(|t| t.format(breakpoint.time_format()).to_string())
             .unwrap_or_else(|| "Never".to_string());
 
         let response = site.response_time_ms
@@ -149,31 +419,63 @@ fn draw_site_list(frame: &mut Frame, app: &App, area: Rect) {
             .unwrap_or_else(|| "-".to_string());
 
         let row_style = if i == app.selected_site {
-            Style::default().bg(Color::DarkGray)
+            app.theme.selected()
         } else {
             Style::default()
         };
 
-        Row::new(vec![
+        let mut cells = vec![
             Cell::from(status_icon).style(status_style),
             Cell::from(site.domain.clone()),
             Cell::from(site.environment.clone()),
             Cell::from(last_sync),
-            Cell::from(response),
-        ]).style(row_style)
+        ];
+        if !narrow {
+            cells.push(Cell::from(response));
+        }
+        if let Some(origin) = &site.origin {
+            let stale = app.fleet.as_ref().is_some_and(|f| f.is_stale(&site.id));
+            let style = if stale { app.theme.error() } else { app.theme.info() };
+            cells.push(Cell::from(if stale { format!("{} (stale)", origin) } else { origin.clone() }).style(style));
+        }
+        Row::new(cells).style(row_style)
     }).collect();
 
-    let table = Table::new(rows, [
-        Constraint::Length(8),
-        Constraint::Min(30),
-        Constraint::Length(12),
-        Constraint::Length(18),
-        Constraint::Length(10),
-    ])
+    let mut widths = match breakpoint {
+        Breakpoint::Narrow => vec![
+            Constraint::Length(1),
+            Constraint::Min(16),
+            Constraint::Length(10),
+            Constraint::Length(11),
+        ],
+        Breakpoint::Medium => vec![
+            Constraint::Length(6),
+            Constraint::Min(24),
+            Constraint::Length(11),
+            Constraint::Length(11),
+            Constraint::Length(8),
+        ],
+        Breakpoint::Wide => vec![
+            Constraint::Length(8),
+            Constraint::Min(30),
+            Constraint::Length(12),
+            Constraint::Length(18),
+            Constraint::Length(10),
+        ],
+    };
+    if federated {
+        widths.push(Constraint::Length(if narrow { 12 } else { 18 }));
+    }
+    let table = Table::new(rows, widths)
     .header(header)
-    .block(Block::default().borders(Borders::ALL).title(" Sites (j/k to navigate, Enter to select) "));
+    .block(Block::default().borders(Borders::ALL).title(format!(" Sites ({}) ", hints(app, Mode::Sites, &[
+        (Command::Down, "down"),
+        (Command::Up, "up"),
+        (Command::Open, "open"),
+    ], ", "))));
 
     frame.render_widget(table, area);
+    record_rows(app, area, 1);
 }
 
 fn draw_site_detail(frame: &mut Frame, app: &App, area: Rect) {
@@ -187,6 +489,28 @@ fn draw_site_detail(frame: &mut Frame, app: &App, area: Rect) {
         }
     };
 
+    let dnssec_text = match app.dnssec_report(site) {
+        Some(report) => {
+            let keys = report.dnskeys.iter()
+                .map(|k| format!("{} {} {}", k.key_tag, algorithm_name(k.algorithm), if k.is_sep() { "KSK" } else { "ZSK" }))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let ds = report.ds.iter()
+                .map(|d| format!("{} {} digest {}", d.key_tag, algorithm_name(d.algorithm), d.digest_type))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "{} [{}]\n  DNSKEY:      {}\n  DS:          {}",
+                report.summary(),
+                report.zone,
+                if keys.is_empty() { "-".to_string() } else { keys },
+                if ds.is_empty() { "-".to_string() } else { ds },
+            )
+        }
+        None if app.dnssec.is_some() => "Not checked".to_string(),
+        None => "Disabled".to_string(),
+    };
+
     let detail_text = format!(
         r#"
   Domain:      {}
@@ -199,8 +523,9 @@ fn draw_site_detail(frame: &mut Frame, app: &App, area: Rect) {
 
   Response:    {}
   SSL Expires: {}
+  DNSSEC:      {}
 
-  [s] Sync  [c] Config Diff  [b] Back
+  {}
 "#,
         site.domain,
         site.status,
@@ -210,6 +535,14 @@ fn draw_site_detail(frame: &mut Frame, app: &App, area: Rect) {
         site.config_hash.as_deref().unwrap_or("N/A"),
         site.response_time_ms.map(|t| format!("{}ms", t)).unwrap_or_else(|| "N/A".to_string()),
         site.ssl_expires.map(|t| t.format("%Y-%m-%d").to_string()).unwrap_or_else(|| "N/A".to_string()),
+        dnssec_text,
+        hints(app, Mode::SiteDetail, &[
+            (Command::Sync, if app.can(Action::Sync, std::slice::from_ref(&site.id)) { "Sync" } else { "" }),
+            (Command::Diff, "Config Diff"),
+            (Command::WordPress, "WordPress"),
+            (Command::Dnssec, "Validate DNSSEC"),
+            (Command::Back, "Back"),
+        ], "  "),
     );
 
     let detail = Paragraph::new(detail_text)
@@ -217,83 +550,872 @@ fn draw_site_detail(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(detail, area);
 }
 
-fn draw_deployments(frame: &mut Frame, app: &App, area: Rect) {
-    let text = if app.pending_deployments.is_empty() {
-        "\n  No pending deployments".to_string()
+fn draw_wordpress(frame: &mut Frame, app: &App, area: Rect) {
+    let site = app.sites.get(app.selected_site);
+    let inventory = site.and_then(|s| app.wp_inventory.get(&s.id));
+    let (site, inventory) = match (site, inventory) {
+        (Some(site), Some(inventory)) => (site, inventory),
+        _ => {
+            let para = Paragraph::new("\n  No WordPress inventory for this site")
+                .block(Block::default().borders(Borders::ALL).title(" WordPress "));
+            frame.render_widget(para, area);
+            return;
+        }
+    };
+
+    let divergences = inventory.divergences();
+    let missing: Vec<&Divergence> = divergences.iter()
+        .filter(|d| matches!(d, Divergence::Missing(_)))
+        .collect();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(4),
+            Constraint::Min(5),
+            Constraint::Length(inventory.themes.len() as u16 + 3),
+            Constraint::Length(missing.len() as u16 + if missing.is_empty() { 0 } else { 2 }),
+        ])
+        .split(area);
+
+    // Core
+    let core = &inventory.core;
+    let core_text = format!(
+        "  Version: {}   Update: {}   Auto-update: {}",
+        core.version,
+        core.update_version.as_deref().unwrap_or("up to date"),
+        core.auto_update,
+    );
+    let core_style = if core.update_version.is_some() {
+        app.theme.warning()
     } else {
-        app.pending_deployments.iter()
-            .map(|d| format!("  {} - {} - {:?}", d.id, d.change_type, d.status))
+        Style::default()
+    };
+    let core_para = Paragraph::new(format!("\n{}", core_text))
+        .style(core_style)
+        .block(Block::default().borders(Borders::ALL).title(format!(" {} - WordPress Core ", site.domain)));
+    frame.render_widget(core_para, chunks[0]);
+
+    // Plugins
+    let header = Row::new(vec!["Plugin", "State", "Version", "Update", "Declared"])
+        .style(Style::default().bold());
+    let rows: Vec<Row> = inventory.plugins.iter().skip(app.scroll_offset).map(|p| {
+        let divergence = divergences.iter().find(|d| d.slug() == p.slug);
+        let style = match divergence {
+            Some(d) if d.is_violation() => app.theme.error(),
+            Some(_) => app.theme.warning(),
+            None => Style::default(),
+        };
+        let state = match p.state {
+            PluginState::Active => "active",
+            PluginState::Inactive => "inactive",
+            PluginState::MustUse => "must-use",
+            PluginState::DropIn => "drop-in",
+        };
+        Row::new(vec![
+            Cell::from(p.slug.clone()),
+            Cell::from(state),
+            Cell::from(p.version.clone()),
+            Cell::from(p.update_version.clone().unwrap_or_else(|| "-".to_string())),
+            Cell::from(divergence.map(|d| d.describe()).unwrap_or_else(|| "ok".to_string())),
+        ]).style(style)
+    }).collect();
+
+    let plugins = Table::new(rows, [
+        Constraint::Length(22),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Min(20),
+    ])
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).title(" Plugins (j/k scroll, r refresh, b back) "));
+    frame.render_widget(plugins, chunks[1]);
+
+    // Themes
+    let rows: Vec<Row> = inventory.themes.iter().map(|t| {
+        let style = if t.update_version.is_some() {
+            app.theme.warning()
+        } else {
+            Style::default()
+        };
+        Row::new(vec![
+            Cell::from(t.slug.clone()),
+            Cell::from(if t.active { "active" } else { "inactive" }),
+            Cell::from(t.version.clone()),
+            Cell::from(t.update_version.clone().unwrap_or_else(|| "-".to_string())),
+        ]).style(style)
+    }).collect();
+
+    let themes = Table::new(rows, [
+        Constraint::Length(22),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(10),
+    ])
+    .header(Row::new(vec!["Theme", "State", "Version", "Update"]).style(Style::default().bold()))
+    .block(Block::default().borders(Borders::ALL).title(" Themes "));
+    frame.render_widget(themes, chunks[2]);
+
+    if !missing.is_empty() {
+        let text = missing.iter()
+            .map(|d| format!("  {}", d.describe()))
             .collect::<Vec<_>>()
-            .join("\n")
+            .join("\n");
+        let para = Paragraph::new(text)
+            .style(app.theme.warning())
+            .block(Block::default().borders(Borders::ALL).title(" Declared but Missing "));
+        frame.render_widget(para, chunks[3]);
+    }
+}
+
+fn draw_deployments(frame: &mut Frame, app: &App, area: Rect) {
+    if app.pending_deployments.is_empty() {
+        let para = Paragraph::new("\n  No pending deployments")
+            .block(Block::default().borders(Borders::ALL).title(" Deployments "));
+        frame.render_widget(para, area);
+        return;
+    }
+
+    let calendar = &app.change_calendar;
+    let calendar_height = if calendar.is_empty() {
+        0
+    } else {
+        (calendar.windows.len() + calendar.freezes.len()) as u16 + 4
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(app.pending_deployments.len() as u16 + 3),
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(calendar_height),
+        ])
+        .split(area);
+    if !calendar.is_empty() {
+        draw_change_calendar(frame, app, chunks[3]);
+    }
+
+    // Narrow screens drop strategy and schedule; the detail below shows both
+    let breakpoint = Breakpoint::of(area.width);
+    let narrow = breakpoint == Breakpoint::Narrow;
+    let keep = [true, true, !narrow, true, !narrow, true, true];
+    let header = Row::new(shown(["ID", "Change", "Strategy", "Progress", "Scheduled", "Approvals", "Status"], &keep))
+        .style(Style::default().bold());
+    let rows: Vec<Row> = app.pending_deployments.iter().enumerate().map(|(i, d)| {
+        let (done, total) = d.progress();
+        let scheduled = d.scheduled
+            .map(|t| t.format(breakpoint.time_format()).to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let row_style = if i == app.selected_deployment {
+            app.theme.selected()
+        } else {
+            Style::default()
+        };
+        Row::new(shown([
+            Cell::from(d.id.clone()),
+            Cell::from(d.change_type.clone()),
+            Cell::from(d.rollout.strategy.label()),
+            Cell::from(format!("{}/{}", done, total)),
+            Cell::from(scheduled),
+            Cell::from(format!(
+                "{}/{}",
+                d.approvals.iter().filter(|a| a.operator != d.created_by).count(),
+                app.required_approvals(d)
+            )),
+            Cell::from(format!("{:?}", d.status)).style(deployment_status_style(&app.theme, d.status)),
+        ], &keep)).style(row_style)
+    }).collect();
+
+    // Federated IDs carry their plane as a prefix
+    let id_width = if app.fleet.is_some() { 22 } else { 12 };
+    let table = Table::new(rows, shown([
+        Constraint::Length(id_width),
+        Constraint::Min(if narrow { 12 } else { 20 }),
+        Constraint::Length(9),
+        Constraint::Length(9),
+        Constraint::Length(if breakpoint == Breakpoint::Wide { 17 } else { 11 }),
+        Constraint::Length(9),
+        Constraint::Length(11),
+    ], &keep))
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).title(format!(" Deployments ({}) ", deployment_hints(app))));
+    frame.render_widget(table, chunks[0]);
+    record_rows(app, chunks[0], 1);
+
+    let Some(deployment) = app.pending_deployments.get(app.selected_deployment) else {
+        return;
     };
 
-    let para = Paragraph::new(format!("\n{}", text))
-        .block(Block::default().borders(Borders::ALL).title(" Pending Deployments "));
+    let (done, total) = deployment.progress();
+    let ratio = if total == 0 { 0.0 } else { done as f64 / total as f64 };
+    let gauge = Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title(format!(" {} ", deployment.id)))
+        .gauge_style(deployment_status_style(&app.theme, deployment.status))
+        .ratio(ratio)
+        .label(format!("{}/{} sites", done, total));
+    frame.render_widget(gauge, chunks[1]);
+
+    let policy = &deployment.rollout;
+    let mut lines = vec![
+        Line::from(format!(
+            "  Strategy: {:?}   Batch size: {}   Pause between: {}   Rollback on failure: {}",
+            policy.strategy,
+            policy.batch_size,
+            format_duration(policy.pause_between_secs),
+            if policy.rollback_on_failure { "yes" } else { "no" },
+        )),
+    ];
+    let outstanding = app.approvals_outstanding(deployment);
+    let approvers = deployment.approvers();
+    let approval_text = format!(
+        "  Created by: {}   Approved by: {}",
+        if deployment.created_by.is_empty() { "?" } else { &deployment.created_by },
+        if approvers.is_empty() { "nobody yet".to_string() } else { approvers.join(", ") },
+    );
+    if outstanding > 0 {
+        lines.push(Line::from(vec![
+            Span::raw(approval_text),
+            Span::styled(
+                format!("   Awaiting {} more approval(s)", outstanding),
+                app.theme.warning(),
+            ),
+        ]));
+    } else {
+        lines.push(Line::from(approval_text));
+    }
+    lines.push(Line::from(""));
+    let current = deployment.current_batch();
+    for (i, batch) in deployment.batches.iter().enumerate() {
+        let marker = if Some(i) == current && deployment.is_active() { "▶" } else { " " };
+        let gate = if policy.gates_after(i) && i + 1 < deployment.batches.len() {
+            "  (operator resume required)"
+        } else {
+            ""
+        };
+        lines.push(Line::from(vec![
+            Span::raw(format!("  {} Batch {} ", marker, i + 1)),
+            Span::styled(format!("{:?}", batch.status), batch_status_style(&app.theme, batch.status)),
+            Span::raw(format!("  {}/{}{}", batch.finished_sites(), batch.sites.len(), gate)),
+        ]));
+        for site in &batch.sites {
+            let domain = app.sites.iter()
+                .find(|s| s.id == site.site_id)
+                .map(|s| s.domain.as_str())
+                .unwrap_or(&site.site_id);
+            let style = match site.status {
+                SiteRolloutStatus::Pending => app.theme.muted(),
+                SiteRolloutStatus::Deploying => app.theme.info(),
+                SiteRolloutStatus::Succeeded => app.theme.ok(),
+                SiteRolloutStatus::Failed => app.theme.error(),
+                SiteRolloutStatus::RolledBack => app.theme.accent(),
+            };
+            lines.push(Line::from(vec![
+                Span::raw(format!("        {:<30} ", domain)),
+                Span::styled(format!("{:?}", site.status), style),
+            ]));
+        }
+    }
+
+    let detail = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(" Rollout "));
+    frame.render_widget(detail, chunks[2]);
+}
+
+fn draw_composer(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(composer) = &app.composer else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(6),
+            Constraint::Length(5),
+            Constraint::Length(4),
+            Constraint::Length(5),
+            Constraint::Length(3),
+        ])
+        .split(area);
+
+    let focused = |field: ComposerField| {
+        if composer.focus == field {
+            app.theme.info()
+        } else {
+            Style::default()
+        }
+    };
+    let cursor = |field: ComposerField| if composer.focus == field { "_" } else { "" };
+    let error_line = |field: ComposerField| match composer.error_for(field) {
+        Some(message) => Line::styled(format!("  ✗ {}", message), app.theme.error()),
+        None => Line::from(""),
+    };
+
+    // Targets
+    let targets = composer.resolve_targets(&app.sites, &app.groups);
+    let mut lines = vec![Line::from(format!(
+        "  Target by: ◀ {} ▶   ({} site(s) matched)",
+        composer.target_mode.label(),
+        targets.len()
+    ))];
+    if composer.target_mode == TargetMode::Selection {
+        for (i, site) in app.sites.iter().enumerate() {
+            let mark = if composer.selected_sites.contains(&site.id) { "[x]" } else { "[ ]" };
+            let style = if i == composer.cursor && composer.focus == ComposerField::Targets {
+                app.theme.selected()
+            } else {
+                Style::default()
+            };
+            lines.push(Line::styled(
+                format!("    {} {:<30} {}", mark, site.domain, site.environment),
+                style,
+            ));
+        }
+    } else {
+        let hint = match composer.target_mode {
+            TargetMode::Group => app.groups.iter().map(|g| g.id.as_str()).collect::<Vec<_>>().join(", "),
+            _ => String::new(),
+        };
+        lines.push(Line::from(format!(
+            "  {}: {}{}   {}",
+            composer.target_mode.label(),
+            composer.target_value,
+            cursor(ComposerField::Targets),
+            hint
+        )));
+        let domains: Vec<&str> = app.sites.iter()
+            .filter(|s| targets.contains(&s.id))
+            .map(|s| s.domain.as_str())
+            .collect();
+        lines.push(Line::from(format!("  → {}", domains.join(", "))));
+    }
+    lines.push(error_line(ComposerField::Targets));
+    let target_block = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(focused(ComposerField::Targets))
+            .title(" Targets (←/→ mode, ↑/↓ + Space select) "),
+    );
+    frame.render_widget(target_block, chunks[0]);
+
+    // Change set
+    let (label, value) = match composer.change_mode {
+        ChangeMode::Named => ("change type", &composer.change_type),
+        ChangeMode::Diff => ("diff file", &composer.diff_path),
+    };
+    let change = Paragraph::new(vec![
+        Line::from(format!("  Change set: ◀ {} ▶", composer.change_mode.label())),
+        Line::from(format!("  {}: {}{}", label, value, cursor(ComposerField::ChangeSet))),
+        error_line(ComposerField::ChangeSet),
+    ])
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(focused(ComposerField::ChangeSet))
+            .title(" Change Set (←/→ mode) "),
+    );
+    frame.render_widget(change, chunks[1]);
+
+    // Schedule
+    let schedule = Paragraph::new(vec![
+        Line::from(format!("  {}{}", composer.schedule, cursor(ComposerField::Schedule))),
+        error_line(ComposerField::Schedule),
+    ])
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(focused(ComposerField::Schedule))
+            .title(" Schedule (now, +6h, YYYY-MM-DD HH:MM UTC) "),
+    );
+    frame.render_widget(schedule, chunks[2]);
+
+    // Rollout
+    let policy = match composer.error_for(ComposerField::Rollout) {
+        Some(message) => Err(message.to_string()),
+        None => composer.rollout_policy(),
+    };
+    let status = match policy {
+        Ok(policy) => Line::from(format!(
+            "  → batches of {}{}, pause {}, {}",
+            policy.batch_size,
+            if policy.strategy == RolloutStrategy::Canary { format!(" after a canary of {}", policy.canary_size) } else { String::new() },
+            format_duration(policy.pause_between_secs),
+            if policy.rollback_on_failure { "roll back on failure" } else { "no rollback on failure" }
+        )),
+        Err(message) => Line::styled(format!("  ✗ {}", message), app.theme.error()),
+    };
+    let rollout = Paragraph::new(vec![
+        Line::from(format!("  Strategy: ◀ {} ▶", composer.strategy.label())),
+        Line::from(format!("  options: {}{}", composer.rollout, cursor(ComposerField::Rollout))),
+        status,
+    ])
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(focused(ComposerField::Rollout))
+            .title(" Rollout (←/→ strategy; batch_size=N canary_size=N pause_between=10m rollback_on_failure=false) "),
+    );
+    frame.render_widget(rollout, chunks[3]);
+
+    let help = Paragraph::new("  [Tab] next field  [Ctrl+D] preview diff  [Ctrl+S] submit  [Esc] cancel")
+        .block(Block::default().borders(Borders::ALL).title(" New Deployment "));
+    frame.render_widget(help, chunks[4]);
+}
+
+fn draw_history(frame: &mut Frame, app: &App, area: Rect) {
+    if app.deployment_history.is_empty() {
+        let para = Paragraph::new("\n  No deployment history")
+            .block(Block::default().borders(Borders::ALL).title(" Deployment History "));
+        frame.render_widget(para, area);
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+
+    let breakpoint = Breakpoint::of(area.width);
+    let narrow = breakpoint == Breakpoint::Narrow;
+    let keep = [true, true, !narrow, true, true, !narrow, true];
+    let header = Row::new(shown(["ID", "When", "Operator", "Change", "Sites", "Duration", "Result"], &keep))
+        .style(Style::default().bold());
+    let rows: Vec<Row> = app.deployment_history.iter().enumerate().map(|(i, r)| {
+        let duration = r.duration()
+            .map(|d| format!("{}m", d.num_minutes()))
+            .unwrap_or_else(|| "-".to_string());
+        let row_style = if i == app.selected_history {
+            app.theme.selected()
+        } else {
+            Style::default()
+        };
+        Row::new(shown([
+            Cell::from(r.id.clone()),
+            Cell::from(r.started_at.format(breakpoint.time_format()).to_string()),
+            Cell::from(r.operator.clone()),
+            Cell::from(r.change_type.clone()),
+            Cell::from(r.sites.len().to_string()),
+            Cell::from(duration),
+            Cell::from(format!("{:?}", r.result)).style(deployment_status_style(&app.theme, r.result)),
+        ], &keep)).style(row_style)
+    }).collect();
+
+    let can_roll_back = app.deployment_history.get(app.selected_history)
+        .is_some_and(|r| app.can(Action::Rollback, &r.sites));
+    let title = format!(" Deployment History ({}) ", hints(app, Mode::History, &[
+        (Command::Diff, "diff"),
+        (Command::Rollback, if can_roll_back { "rollback" } else { "" }),
+    ], ", "));
+    let table = Table::new(rows, shown([
+        Constraint::Length(14),
+        Constraint::Length(if breakpoint == Breakpoint::Wide { 17 } else { 11 }),
+        Constraint::Length(12),
+        Constraint::Min(if narrow { 12 } else { 18 }),
+        Constraint::Length(6),
+        Constraint::Length(9),
+        Constraint::Length(11),
+    ], &keep))
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).title(title));
+    frame.render_widget(table, chunks[0]);
+    record_rows(app, chunks[0], 1);
+
+    if let Some(record) = app.deployment_history.get(app.selected_history) {
+        let title = format!(
+            " {} → {} ",
+            record.previous_config_hash.as_deref().unwrap_or("?"),
+            record.config_hash
+        );
+        let diff = Paragraph::new(record.diff.clone())
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(diff, chunks[1]);
+    }
+}
+
+/// Seven-day strip of maintenance windows, freezes and scheduled deployments
+fn draw_change_calendar(frame: &mut Frame, app: &App, area: Rect) {
+    const DAYS: u32 = 7;
+    let now = app.now();
+    let calendar = &app.change_calendar;
+    let label_width = 28;
+
+    let mut day_header = format!("  {:<label_width$}", "");
+    for day in 0..DAYS {
+        let date = now + chrono::Duration::days(day as i64);
+        day_header.push_str(&format!("{:<width$}", date.format("%a %d"), width = SLOTS_PER_DAY as usize));
+    }
+    let mut lines = vec![Line::styled(day_header, Style::default().bold())];
+
+    for window in &calendar.windows {
+        lines.push(Line::from(vec![
+            Span::raw(format!("  {:<label_width$}", format!("{} ({})", window.name, window.scope.describe()))),
+            Span::styled(timeline(now, DAYS, |t| window.contains(t)), app.theme.ok()),
+        ]));
+    }
+    for freeze in &calendar.freezes {
+        lines.push(Line::from(vec![
+            Span::raw(format!("  {:<label_width$}", format!("{} ({})", freeze.name, freeze.scope.describe()))),
+            Span::styled(timeline(now, DAYS, |t| freeze.contains(t)), app.theme.error()),
+        ]));
+    }
+
+    let slot = chrono::Duration::hours((24 / SLOTS_PER_DAY) as i64);
+    let scheduled = timeline(now, DAYS, |t| {
+        app.pending_deployments.iter()
+            .filter_map(|d| d.scheduled)
+            .any(|s| s >= t - slot / 2 && s < t + slot / 2)
+    }).replace('█', "▲");
+    lines.push(Line::from(vec![
+        Span::raw(format!("  {:<label_width$}", "scheduled deployments")),
+        Span::styled(scheduled, app.theme.info()),
+    ]));
+
+    let para = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(" Change Calendar (UTC, 3h slots; green = window, red = freeze) "));
     frame.render_widget(para, area);
 }
 
+/// Key hints for the actions the operator may take on the selected deployment
+fn deployment_hints(app: &App) -> String {
+    let sites = app.pending_deployments.get(app.selected_deployment)
+        .map(|d| d.sites.clone())
+        .unwrap_or_default();
+    let label = |allowed: bool, label| if allowed { label } else { "" };
+    hints(app, Mode::Deployments, &[
+        (Command::NewDeployment, label(app.permissions.allows_somewhere(Action::CreateDeployment), "new")),
+        (Command::Approve, label(app.can(Action::Approve, &sites), "approve")),
+        (Command::Reject, label(app.can(Action::Reject, &sites), "reject")),
+        (Command::Pause, label(app.can(Action::Pause, &sites), "pause/resume")),
+        (Command::Abort, label(app.can(Action::Abort, &sites), "abort")),
+    ], ", ")
+}
+
+/// "[key] label" for each command bound in `mode`; empty labels and unbound commands are left out
+fn hints(app: &App, mode: Mode, commands: &[(Command, &str)], separator: &str) -> String {
+    commands.iter()
+        .filter(|(_, label)| !label.is_empty())
+        .filter_map(|(command, label)| {
+            app.keymap.key_for(mode, *command).map(|key| format!("[{}] {}", key, label))
+        })
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn deployment_status_style(theme: &Theme, status: DeploymentStatus) -> Style {
+    match status {
+        DeploymentStatus::Pending => theme.muted(),
+        DeploymentStatus::InProgress => theme.info(),
+        DeploymentStatus::Paused => theme.warning(),
+        DeploymentStatus::Completed => theme.ok(),
+        DeploymentStatus::Failed | DeploymentStatus::Aborted | DeploymentStatus::Rejected => {
+            theme.error()
+        }
+        DeploymentStatus::RolledBack => theme.accent(),
+    }
+}
+
+fn batch_status_style(theme: &Theme, status: BatchStatus) -> Style {
+    match status {
+        BatchStatus::Pending => theme.muted(),
+        BatchStatus::InProgress | BatchStatus::Verifying => theme.info(),
+        BatchStatus::Completed => theme.ok(),
+        BatchStatus::Failed => theme.error(),
+    }
+}
+
 fn draw_alerts(frame: &mut Frame, app: &App, area: Rect) {
-    let text = if app.alerts.is_empty() {
-        "\n  No alerts".to_string()
+    if app.alerts.is_empty() {
+        let para = Paragraph::new("\n  No alerts")
+            .block(Block::default().borders(Borders::ALL).title(" Alerts "));
+        frame.render_widget(para, area);
+        return;
+    }
+
+    let lines: Vec<Line> = app.alerts.iter().enumerate().map(|(i, a)| {
+        let ack = if a.acknowledged { "✓" } else { " " };
+        let mut text = format!("  [{}] {:?} - {} - {}", ack, a.severity, a.site_id, a.message);
+        if app.is_silenced(&a.site_id) {
+            text.push_str("  (silenced)");
+        }
+        let style = if i == app.selected_alert {
+            app.theme.selected()
+        } else if a.acknowledged || app.is_silenced(&a.site_id) {
+            app.theme.muted()
+        } else {
+            Style::default()
+        };
+        Line::styled(text, style)
+    }).collect();
+
+    let para = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(format!(" Alerts ({}) ", alert_hints(app))));
+    frame.render_widget(para, area);
+    record_rows(app, area, 0);
+}
+
+/// Key hints for the actions the operator may take on the selected alert
+fn alert_hints(app: &App) -> String {
+    let sites: Vec<String> = app.alerts.get(app.selected_alert).map(|a| a.site_id.clone()).into_iter().collect();
+    let label = |action, label| if app.can(action, &sites) { label } else { "" };
+    hints(app, Mode::Alerts, &[
+        (Command::Acknowledge, label(Action::Acknowledge, "Acknowledge")),
+        (Command::Dismiss, label(Action::Dismiss, "Dismiss")),
+        (Command::Silence, label(Action::Silence, "Silence site 1h")),
+    ], ", ")
+}
+
+fn draw_audit_log(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(log) = &app.audit else {
+        let para = Paragraph::new("\n  Audit log disabled (no data directory; set audit_log in config.toml)")
+            .block(Block::default().borders(Borders::ALL).title(" Audit Log "));
+        frame.render_widget(para, area);
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(area);
+
+    let (summary, style) = match &app.audit_verification {
+        Some(v) if v.is_intact() => (v.summary(), app.theme.ok()),
+        Some(v) => (v.summary(), app.theme.error().bold()),
+        None => ("Not verified".to_string(), app.theme.muted()),
+    };
+    let status = Paragraph::new(Line::styled(format!(" {}", summary), style))
+        .block(Block::default().borders(Borders::ALL).title(format!(" {} ", log.path().display())));
+    frame.render_widget(status, chunks[0]);
+
+    let breakpoint = Breakpoint::of(area.width);
+    let narrow = breakpoint == Breakpoint::Narrow;
+    let keep = [!narrow, true, true, !narrow, true, true, true];
+    let when = if breakpoint == Breakpoint::Wide { "%Y-%m-%d %H:%M:%S" } else { breakpoint.time_format() };
+    let header = Row::new(shown(["#", "When", "Operator", "Context", "Action", "Target", "Result"], &keep))
+        .style(Style::default().bold());
+    // Newest first
+    let rows: Vec<Row> = app.audit_entries.iter().rev().skip(app.scroll_offset).map(|e| {
+        let result_style = if e.succeeded() {
+            app.theme.ok()
+        } else {
+            app.theme.error()
+        };
+        Row::new(shown([
+            Cell::from(e.seq.to_string()),
+            Cell::from(e.timestamp.format(when).to_string()),
+            Cell::from(e.operator.clone()),
+            Cell::from(e.context.clone()),
+            Cell::from(e.action.clone()),
+            Cell::from(e.target.clone()),
+            Cell::from(e.result.clone()).style(result_style),
+        ], &keep))
+    }).collect();
+
+    let table = Table::new(rows, shown([
+        Constraint::Length(6),
+        Constraint::Length(if breakpoint == Breakpoint::Wide { 20 } else { 11 }),
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Length(if narrow { 10 } else { 18 }),
+        Constraint::Length(if narrow { 16 } else { 24 }),
+        Constraint::Min(10),
+    ], &keep))
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).title(format!(" Audit Log ({}) ", hints(app, Mode::Logs, &[(Command::VerifyAudit, "verify chain")], ", "))));
+    frame.render_widget(table, chunks[1]);
+}
+
+fn draw_vulnerabilities(frame: &mut Frame, app: &App, area: Rect) {
+    if app.vuln_db.is_none() {
+        let para = Paragraph::new("\n  No vulnerability database configured (set vuln_db in config.toml)")
+            .block(Block::default().borders(Borders::ALL).title(" Vulnerabilities "));
+        frame.render_widget(para, area);
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(area);
+
+    let cursor = if app.editing_vuln_query { "_" } else { "" };
+    let query_text = if app.vuln_query.is_empty() && !app.editing_vuln_query {
+        format!(" All findings ({})", hints(app, Mode::Vulnerabilities, &[(Command::Search, "query by CVE or ID")], ", "))
     } else {
-        app.alerts.iter()
-            .map(|a| {
-                let ack = if a.acknowledged { "✓" } else { " " };
-                format!("  [{}] {:?} - {} - {}", ack, a.severity, a.site_id, a.message)
-            })
-            .collect::<Vec<_>>()
-            .join("\n")
+        let affected: Vec<&str> = app.sites_affected_by(&app.vuln_query).iter()
+            .map(|s| s.domain.as_str())
+            .collect();
+        format!(
+            " {}{}   Affected sites: {}",
+            app.vuln_query,
+            cursor,
+            if affected.is_empty() { "none".to_string() } else { affected.join(", ") },
+        )
     };
+    let query = Paragraph::new(query_text)
+        .block(Block::default().borders(Borders::ALL).title(" Which sites have... "));
+    frame.render_widget(query, chunks[0]);
 
-    let para = Paragraph::new(format!("\n{}", text))
-        .block(Block::default().borders(Borders::ALL).title(" Alerts ([a] Acknowledge, [d] Dismiss) "));
-    frame.render_widget(para, area);
+    let findings = app.filtered_findings();
+    let header = Row::new(vec!["ID", "Site", "Component", "Installed", "Fixed In", "Summary"])
+        .style(Style::default().bold());
+    let rows: Vec<Row> = findings.iter().skip(app.scroll_offset).map(|f| {
+        let domain = app.sites.iter()
+            .find(|s| s.id == f.site_id)
+            .map(|s| s.domain.clone())
+            .unwrap_or_else(|| f.site_id.clone());
+        let style = match f.severity.as_deref() {
+            Some("low") | Some("medium") => app.theme.warning(),
+            _ => app.theme.error(),
+        };
+        Row::new(vec![
+            Cell::from(f.vuln_id.clone()),
+            Cell::from(domain),
+            Cell::from(f.component_label()),
+            Cell::from(f.installed.clone()),
+            Cell::from(f.fixed.clone().unwrap_or_else(|| "-".to_string())),
+            Cell::from(f.summary.clone()),
+        ]).style(style)
+    }).collect();
+
+    let table = Table::new(rows, [
+        Constraint::Length(16),
+        Constraint::Length(22),
+        Constraint::Length(24),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Min(20),
+    ])
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).title(format!(
+        " Vulnerabilities ({} findings, {}) ",
+        findings.len(),
+        hints(app, Mode::Vulnerabilities, &[(Command::ClearSearch, "clear"), (Command::Refresh, "rescan")], ", "),
+    )));
+    frame.render_widget(table, chunks[1]);
 }
 
-fn draw_help(frame: &mut Frame, area: Rect) {
-    let help_text = r#"
-  SOCP - Site Operations Control Plane
-
-  NAVIGATION
-    Tab / 1-5     Switch views
-    j / ↓         Move down
-    k / ↑         Move up
-    Enter         Select / Open
-    Esc / b       Back / Close
-    Ctrl+Q        Quit
-
-  DASHBOARD
-    s             Sites view
-    d             Deployments view
-    a             Alerts view
-    l             Logs view
-    r             Refresh data
-
-  SITES
-    s             Sync selected site
-    c             Show config diff
-    /             Search/filter
-
-  DEPLOYMENTS
-    a             Approve deployment
-    r             Reject deployment
-
-  ALERTS
-    a             Acknowledge alert
-    d             Dismiss alert
-"#;
-
-    let para = Paragraph::new(help_text)
+fn draw_help(frame: &mut Frame, app: &App, area: Rect) {
+    let sections = app.keymap.help();
+    let width = sections.iter()
+        .flat_map(|(_, rows)| rows.iter().map(|(keys, _)| keys.chars().count()))
+        .max()
+        .unwrap_or(0) + 2;
+
+    let mut lines = vec![Line::raw(""), Line::raw("  SOCP - Site Operations Control Plane")];
+    for (title, rows) in sections {
+        lines.push(Line::raw(""));
+        lines.push(Line::raw(format!("  {}", title)));
+        for (keys, description) in rows {
+            lines.push(Line::raw(format!("    {:<width$}{}", keys, description, width = width)));
+        }
+    }
+    lines.extend([
+        Line::raw(""),
+        Line::raw("  Keys are set per mode under [keys.<mode>] in config.toml; \"g s\" binds"),
+        Line::raw("  a chord. Prompts and text fields use Enter to confirm and Esc to cancel."),
+        Line::raw(""),
+        Line::raw("  Mutating actions ask for confirmation per the [[confirm]] rules in"),
+        Line::raw("  config.toml; production syncs require typing the domain. Start with"),
+        Line::raw("  --read-only (or read_only = true) to disable them entirely."),
+    ]);
+
+    let para = Paragraph::new(lines)
         .block(Block::default().borders(Borders::ALL).title(" Help "))
         .wrap(Wrap { trim: false });
     frame.render_widget(para, area);
 }
 
 fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
-    let status = app.status_message.as_deref().unwrap_or("Ready");
-    let para = Paragraph::new(format!(" {} ", status))
-        .style(Style::default().bg(Color::DarkGray).fg(Color::White));
-    frame.render_widget(para, area);
+    let status = if !app.pending_keys.is_empty() {
+        format!("{} …", show_sequence(&app.pending_keys))
+    } else if let Some(latest) = app.notifications.history().next() {
+        latest.message.clone()
+    } else {
+        "Ready".to_string()
+    };
+    let key = app.keymap.key_for(Mode::Global, Command::Notifications)
+        .map(|k| format!(" [{}]", k))
+        .unwrap_or_default();
+    let unseen = match app.notifications.unseen() {
+        0 => format!("notifications{} ", key),
+        n => format!("{} new{} ", n, key),
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(unseen.chars().count() as u16)])
+        .split(area);
+    frame.render_widget(Paragraph::new(format!(" {} ", status)).style(app.theme.status_bar()), chunks[0]);
+    frame.render_widget(Paragraph::new(unseen).style(app.theme.status_bar()), chunks[1]);
+}
+
+fn level_style(theme: &Theme, level: Level) -> Style {
+    match level {
+        Level::Info => theme.info(),
+        Level::Success => theme.ok(),
+        Level::Warning => theme.warning(),
+        Level::Error => theme.error(),
+    }
+}
+
+/// Unexpired notifications stacked in the top-right corner, newest on top
+fn draw_toasts(frame: &mut Frame, app: &App) {
+    let screen = frame.area();
+    let width = TOAST_WIDTH.min(screen.width);
+    // Below the header
+    let mut y = screen.y + 3;
+    for toast in app.notifications.toasts() {
+        let inner_width = width.saturating_sub(2).max(1) as usize;
+        let lines = toast.message.chars().count().div_ceil(inner_width).clamp(1, 3) as u16;
+        let area = Rect::new(screen.right().saturating_sub(width + 1), y, width, lines + 2).intersection(screen);
+        if area.height < 3 {
+            break;
+        }
+        let mut title = format!(" {} {} ", toast.level.icon(), toast.at.format("%H:%M:%S"));
+        if toast.count > 1 {
+            title.push_str(&format!("×{} ", toast.count));
+        }
+        let style = level_style(&app.theme, toast.level);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(toast.message.clone())
+                .block(Block::default().borders(Borders::ALL).border_style(style).title(title))
+                .wrap(Wrap { trim: true }),
+            area,
+        );
+        y = area.bottom();
+    }
+}
+
+fn notification_line<'a>(app: &App, notification: &Notification) -> Line<'a> {
+    let mut spans = vec![
+        Span::styled(format!(" {} ", notification.at.format("%H:%M:%S")), app.theme.muted()),
+        Span::styled(format!("{} ", notification.level.icon()), level_style(&app.theme, notification.level)),
+        Span::raw(notification.message.clone()),
+    ];
+    if notification.count > 1 {
+        spans.push(Span::styled(format!(" ×{}", notification.count), app.theme.muted()));
+    }
+    Line::from(spans)
+}
+
+fn draw_notification_center(frame: &mut Frame, app: &App) {
+    let Some(offset) = app.notification_center else {
+        return;
+    };
+    let area = centered_rect(70, 70, frame.area());
+    frame.render_widget(Clear, area);
+
+    let mut lines: Vec<Line> = app.notifications.history()
+        .skip(offset)
+        .map(|n| notification_line(app, n))
+        .collect();
+    if lines.is_empty() {
+        lines.push(Line::raw("  No notifications"));
+    }
+    let popup = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(" Notifications ([c] clear, [Esc] close) "));
+    frame.render_widget(popup, area);
 }
 
 fn draw_popup(frame: &mut Frame, app: &App) {
@@ -303,26 +1425,309 @@ fn draw_popup(frame: &mut Frame, app: &App) {
 
     let popup = Paragraph::new(app.popup_content.clone())
         .block(Block::default().borders(Borders::ALL).title(" Config Diff (Esc to close) "))
+        .wrap(Wrap { trim: false })
+        .scroll((app.popup_scroll, 0));
+    frame.render_widget(popup, area);
+}
+
+fn draw_confirmation(frame: &mut Frame, app: &App) {
+    let Some(confirmation) = &app.confirmation else {
+        return;
+    };
+    let area = centered_rect(60, 30, frame.area());
+
+    frame.render_widget(Clear, area);
+
+    let text = match &confirmation.input {
+        ConfirmInput::Reason(reason) => format!(
+            "\n  {}\n\n  Reason: {}_\n\n  [Enter] Override   [Esc] Cancel",
+            confirmation.prompt, reason
+        ),
+        ConfirmInput::Typed { entered, .. } => format!(
+            "\n  {}\n\n  > {}_\n\n  [Enter] Confirm   [Esc] Cancel",
+            confirmation.prompt, entered
+        ),
+        ConfirmInput::YesNo => format!("\n  {}\n\n  [y] Confirm   [n] Cancel", confirmation.prompt),
+    };
+    let popup = Paragraph::new(text)
+        .style(app.theme.warning())
+        .block(Block::default().borders(Borders::ALL).title(" Confirm "))
         .wrap(Wrap { trim: false });
     frame.render_widget(popup, area);
 }
 
+fn draw_context_picker(frame: &mut Frame, app: &App) {
+    let Some(selected) = app.context_picker else {
+        return;
+    };
+    let area = centered_rect(60, 40, frame.area());
+    frame.render_widget(Clear, area);
+
+    let mut lines: Vec<Line> = app.contexts.iter().enumerate().map(|(i, context)| {
+        let current = if app.fleet.is_none() && context.name == app.context.name { "●" } else { " " };
+        let read_only = if context.read_only { "  (read-only)" } else { "" };
+        let style = if i == selected {
+            app.theme.selected()
+        } else {
+            Style::default()
+        };
+        Line::styled(format!("  {} {:<16} {}{}", current, context.name, context.api_url, read_only), style)
+    }).collect();
+    if app.contexts.len() > 1 {
+        let current = if app.fleet.is_some() { "●" } else { " " };
+        let style = if selected == app.contexts.len() {
+            app.theme.selected()
+        } else {
+            Style::default()
+        };
+        lines.push(Line::styled(format!("  {} {:<16} all planes, read-only", current, "(federated)"), style));
+    }
+
+    let popup = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(" Switch Control Plane ([Enter] connect, [Esc] cancel) "));
+    frame.render_widget(popup, area);
+}
+
+fn draw_tab_menu(frame: &mut Frame, app: &App) {
+    let Some(selected) = app.tab_menu else {
+        return;
+    };
+    let tabs = app.tabs();
+    let screen = frame.area();
+    let width = TAB_MENU_WIDTH.min(screen.width);
+    let height = (tabs.len() as u16 + 2).min(screen.height);
+    let area = Rect::new(
+        screen.x + (screen.width - width) / 2,
+        screen.y + (screen.height - height) / 2,
+        width,
+        height,
+    );
+    frame.render_widget(Clear, area);
+
+    let lines: Vec<Line> = tabs.iter().enumerate().map(|(i, view)| {
+        let current = if *view == app.view.tab() { "●" } else { " " };
+        let style = if i == selected { app.theme.selected() } else { Style::default() };
+        Line::styled(format!(" {} {}", current, view.title()), style)
+    }).collect();
+    let popup = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(" Views "));
+    frame.render_widget(popup, area);
+}
+
+fn draw_palette(frame: &mut Frame, app: &App) {
+    let Some(palette) = &app.palette else {
+        return;
+    };
+    let area = centered_rect(70, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let mut lines = vec![Line::from(vec![
+        Span::styled(" : ", app.theme.highlight()),
+        Span::raw(format!("{}_", palette.input)),
+    ])];
+    lines.push(match &palette.error {
+        Some(error) => Line::styled(format!("   {}", error), app.theme.error()),
+        None => Line::raw(""),
+    });
+
+    // Keep the selection in view
+    let visible = area.height.saturating_sub(4) as usize;
+    let suggestions = palette.suggestions(app);
+    let skip = (palette.selected + 1).saturating_sub(visible);
+    let width = suggestions.iter().map(|s| s.value.chars().count()).max().unwrap_or(0) + 2;
+    for (i, suggestion) in suggestions.iter().enumerate().skip(skip).take(visible) {
+        let style = if i == palette.selected { app.theme.selected() } else { Style::default() };
+        lines.push(Line::from(vec![
+            Span::raw(format!("   {:<width$}", suggestion.value, width = width)),
+            Span::styled(suggestion.detail.clone(), app.theme.muted()),
+        ]).style(style));
+    }
+
+    let popup = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(" Command ([Tab] complete, [Enter] run, [Esc] cancel) "));
+    frame.render_widget(popup, area);
+}
+
+/// `percent_x` by `percent_y` of `area`, but never smaller than
+/// `MIN_POPUP_WIDTH` by `MIN_POPUP_HEIGHT` where the screen allows
 fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
-        ])
-        .split(area);
+    let scale = |length: u16, percent: u16| (u32::from(length) * u32::from(percent) / 100) as u16;
+    let width = scale(area.width, percent_x).max(MIN_POPUP_WIDTH).min(area.width);
+    let height = scale(area.height, percent_y).max(MIN_POPUP_HEIGHT).min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
 
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{app, assert_snapshot, press, render, settle, type_text};
+
+    /// Sizes the layouts are checked at: an 80x24 SSH session, a laptop, a wide monitor
+    const SIZES: [(u16, u16); 3] = [(80, 24), (120, 40), (200, 50)];
+
+    /// Size each view is pinned at
+    const VIEW_SIZE: (u16, u16) = (100, 30);
+
+    fn assert_sizes(name: &str, app: &App) {
+        for size in SIZES {
+            assert_snapshot(&format!("{}_{}x{}", name, size.0, size.1), &render(app, size));
+        }
+    }
+
+    /// Snapshot of the screen after pressing `keys` on a fresh fixture app
+    async fn assert_view(name: &str, keys: &str, view: View) {
+        let mut app = app(name);
+        press(&mut app, keys).await;
+        settle(&mut app);
+        assert_eq!(app.view, view, "after '{}'", keys);
+        // The audit log lives under a per-process temp path
+        let path = format!("{} ", app.audit.as_ref().unwrap().path().display());
+        let screen = render(&app, VIEW_SIZE).replace(&path, &format!("{:─<1$}", "audit.log ", path.chars().count()));
+        assert_snapshot(&format!("view_{}", name), &screen);
+    }
+
+    #[tokio::test]
+    async fn dashboard_at_each_size() {
+        let app = app("dashboard");
+        assert_sizes("dashboard", &app);
+    }
+
+    #[tokio::test]
+    async fn site_list_drops_columns_when_narrow() {
+        let mut app = app("sites");
+        press(&mut app, "1 j").await;
+        assert_sizes("sites", &app);
+    }
+
+    #[tokio::test]
+    async fn site_list_splits_with_detail_when_wide() {
+        let mut app = app("split");
+        press(&mut app, "1").await;
+        let wide = render(&app, (200, 50));
+        assert!(wide.contains("Sites (") && wide.contains("example.com"));
+        assert_snapshot("sites_split_200x50", &wide);
+    }
+
+    #[tokio::test]
+    async fn alerts_at_each_size() {
+        let mut app = app("alerts");
+        press(&mut app, "3").await;
+        assert_sizes("alerts", &app);
+    }
+
+    #[tokio::test]
+    async fn each_view() {
+        assert_view("dashboard", "", View::Dashboard).await;
+        assert_view("site_list", "1 j j", View::SiteList).await;
+        assert_view("site_detail", "1 j enter", View::SiteDetail).await;
+        assert_view("site_detail_unsynced", "1 j j j enter", View::SiteDetail).await;
+        assert_view("wordpress", "1 enter w", View::WordPress).await;
+        assert_view("deployments", "2 j", View::Deployments).await;
+        assert_view("composer", "2 n", View::Composer).await;
+        assert_view("history", "h", View::History).await;
+        assert_view("alerts", "3 j", View::Alerts).await;
+        assert_view("logs", "4", View::Logs).await;
+        assert_view("vulnerabilities", "5 r", View::Vulnerabilities).await;
+        assert_view("help", "?", View::Help).await;
+    }
+
+    #[test]
+    fn dashboard_with_every_panel() {
+        let mut app = app("panels");
+        app.layout.dashboard = vec![
+            vec![Panel::Overview, Panel::AlertHistogram, Panel::Notifications],
+            vec![Panel::NeedsAttention, Panel::ExpiringCerts],
+            vec![Panel::PendingDeployments, Panel::Activity],
+        ];
+        assert_snapshot("dashboard_panels_120x40", &render(&app, (120, 40)));
+    }
+
+    #[tokio::test]
+    async fn overlays() {
+        let mut app = app("overlays");
+        press(&mut app, "N").await;
+        assert_snapshot("overlay_notifications", &render(&app, VIEW_SIZE));
+        press(&mut app, "esc C").await;
+        assert_snapshot("overlay_context_picker", &render(&app, VIEW_SIZE));
+        press(&mut app, "esc :").await;
+        type_text(&mut app, "goto al").await;
+        assert_snapshot("overlay_palette", &render(&app, VIEW_SIZE));
+    }
+
+    #[tokio::test]
+    async fn acknowledging_an_alert_updates_the_table() {
+        let mut app = app("acknowledge");
+        press(&mut app, "3").await;
+        let before = render(&app, VIEW_SIZE);
+        press(&mut app, "a").await;
+        let after = render(&app, VIEW_SIZE);
+        assert_ne!(before, after);
+        assert!(app.alerts[app.selected_alert].acknowledged);
+    }
+
+    #[tokio::test]
+    async fn back_and_forward_restore_the_selection() {
+        let mut app = app("history_nav");
+        press(&mut app, "1 j j enter").await;
+        assert_eq!((app.view, app.selected_site), (View::SiteDetail, 2));
+        press(&mut app, "esc").await;
+        assert_eq!((app.view, app.selected_site), (View::SiteList, 2));
+        press(&mut app, "esc").await;
+        assert_eq!(app.view, View::Dashboard);
+        press(&mut app, "alt+right alt+right").await;
+        assert_eq!((app.view, app.selected_site), (View::SiteDetail, 2));
+        assert!(render(&app, VIEW_SIZE).contains("staging.example.com"));
+    }
+
+    #[tokio::test]
+    async fn tab_cycles_through_the_tabs() {
+        let mut app = app("tabs");
+        let tabs = app.tabs();
+        let mut seen = vec![app.view];
+        for _ in 1..tabs.len() {
+            press(&mut app, "tab").await;
+            seen.push(app.view);
+        }
+        assert_eq!(seen, tabs);
+        press(&mut app, "tab").await;
+        assert_eq!(app.view, View::Dashboard);
+        press(&mut app, "backtab").await;
+        assert_eq!(app.view, *tabs.last().unwrap());
+    }
+
+    #[tokio::test]
+    async fn tabs_collapse_into_a_menu() {
+        let mut app = app("menu");
+        let size = (60, 20);
+        assert_snapshot("tabs_collapsed_60x20", &render(&app, size));
+        press(&mut app, "f2").await;
+        assert_snapshot("tab_menu_60x20", &render(&app, size));
+    }
+
+    #[tokio::test]
+    async fn toasts_stack_below_the_header() {
+        let mut app = app("toasts");
+        app.notifications.push(Level::Error, "Sync failed for example.com: connection reset by peer".to_string());
+        app.notifications.push(Level::Warning, "Permission denied".to_string());
+        app.notifications.push(Level::Warning, "Permission denied".to_string());
+        let screen = render(&app, (80, 24));
+        assert!(screen.contains("×2"), "{}", screen);
+        assert!(screen.contains("Permission denied") && screen.contains("reset by peer"), "{}", screen);
+        assert!(screen.lines().last().unwrap().contains("2 new [N]"), "{}", screen);
+    }
+
+    #[tokio::test]
+    async fn popups_stay_readable_on_small_screens() {
+        let mut app = app("popups");
+        press(&mut app, "1 enter c").await;
+        assert_snapshot("diff_popup_80x24", &render(&app, (80, 24)));
+        press(&mut app, "esc s").await;
+        assert_snapshot("confirmation_80x24", &render(&app, (80, 24)));
+        assert_snapshot("confirmation_60x16", &render(&app, (60, 16)));
+    }
 }
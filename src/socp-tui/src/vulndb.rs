@@ -0,0 +1,312 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! Offline vulnerability matching for WordPress core, plugins and themes
+//!
+//! Loads a local JSON export in a WPScan/OSV-like shape and matches it
+//! against site inventories without contacting any external service.
+//!
+//! ```json
+//! { "vulnerabilities": [ {
+//!     "id": "CVE-2020-25213", "summary": "Unauthenticated RCE", "severity": "critical",
+//!     "affected": [ { "type": "plugin", "slug": "wp-file-manager", "fixed": "6.9" } ]
+//! } ] }
+//! ```
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::app::{Alert, AlertSeverity};
+use crate::wordpress::WordPressInventory;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ComponentType {
+    Core,
+    Plugin,
+    Theme,
+}
+
+/// A version range affected by a vulnerability
+#[derive(Debug, Clone, Deserialize)]
+pub struct Affected {
+    #[serde(rename = "type")]
+    pub component: ComponentType,
+    /// Plugin or theme slug; ignored for core
+    #[serde(default)]
+    pub slug: String,
+    /// First affected version (inclusive)
+    pub introduced: Option<String>,
+    /// First fixed version (exclusive upper bound)
+    pub fixed: Option<String>,
+    /// Last affected version (inclusive upper bound) when no fix exists
+    pub last_affected: Option<String>,
+}
+
+impl Affected {
+    pub fn contains(&self, version: &str) -> bool {
+        if let Some(introduced) = &self.introduced {
+            if compare_versions(version, introduced) == Ordering::Less {
+                return false;
+            }
+        }
+        if let Some(fixed) = &self.fixed {
+            return compare_versions(version, fixed) == Ordering::Less;
+        }
+        if let Some(last) = &self.last_affected {
+            return compare_versions(version, last) != Ordering::Greater;
+        }
+        true
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Vulnerability {
+    pub id: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub summary: String,
+    #[serde(default)]
+    pub severity: Option<String>,
+    pub affected: Vec<Affected>,
+}
+
+impl Vulnerability {
+    /// Whether `query` names this vulnerability by ID or alias
+    pub fn is_named(&self, query: &str) -> bool {
+        self.id.eq_ignore_ascii_case(query) || self.aliases.iter().any(|a| a.eq_ignore_ascii_case(query))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum DatabaseFile {
+    Wrapped { vulnerabilities: Vec<Vulnerability> },
+    Bare(Vec<Vulnerability>),
+}
+
+/// A vulnerable component installed on a site
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub vuln_id: String,
+    pub site_id: String,
+    pub component: ComponentType,
+    pub slug: String,
+    pub installed: String,
+    pub fixed: Option<String>,
+    pub summary: String,
+    pub severity: Option<String>,
+}
+
+impl Finding {
+    pub fn component_label(&self) -> String {
+        match self.component {
+            ComponentType::Core => "core".to_string(),
+            ComponentType::Plugin => format!("plugin {}", self.slug),
+            ComponentType::Theme => format!("theme {}", self.slug),
+        }
+    }
+
+    pub fn alert_id(&self) -> String {
+        format!("vuln-{}-{}-{}", self.vuln_id, self.site_id, self.slug)
+    }
+
+    /// Alert dated `first_seen`, when this finding first turned up
+    pub fn to_alert(&self, first_seen: DateTime<Utc>) -> Alert {
+        let fix = match &self.fixed {
+            Some(v) => format!(", fixed in {}", v),
+            None => ", no fix available".to_string(),
+        };
+        Alert {
+            id: self.alert_id(),
+            site_id: self.site_id.clone(),
+            severity: AlertSeverity::Critical,
+            message: format!("{}: {} {} is vulnerable{}", self.vuln_id, self.component_label(), self.installed, fix),
+            timestamp: first_seen,
+            acknowledged: false,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct VulnDatabase {
+    pub vulnerabilities: Vec<Vulnerability>,
+}
+
+impl VulnDatabase {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("reading vulnerability database {}", path.display()))?;
+        let file: DatabaseFile = serde_json::from_str(&contents)
+            .with_context(|| format!("parsing vulnerability database {}", path.display()))?;
+        let vulnerabilities = match file {
+            DatabaseFile::Wrapped { vulnerabilities } => vulnerabilities,
+            DatabaseFile::Bare(vulnerabilities) => vulnerabilities,
+        };
+        Ok(Self { vulnerabilities })
+    }
+
+    /// All findings for one site's inventory
+    pub fn scan(&self, inventory: &WordPressInventory) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for vuln in &self.vulnerabilities {
+            for affected in &vuln.affected {
+                let installed = match affected.component {
+                    ComponentType::Core => Some(inventory.core.version.as_str()),
+                    ComponentType::Plugin => inventory.plugin(&affected.slug).map(|p| p.version.as_str()),
+                    ComponentType::Theme => inventory.themes.iter()
+                        .find(|t| t.slug == affected.slug)
+                        .map(|t| t.version.as_str()),
+                };
+                if let Some(version) = installed.filter(|v| affected.contains(v)) {
+                    findings.push(Finding {
+                        vuln_id: vuln.id.clone(),
+                        site_id: inventory.site_id.clone(),
+                        component: affected.component,
+                        slug: affected.slug.clone(),
+                        installed: version.to_string(),
+                        fixed: affected.fixed.clone(),
+                        summary: vuln.summary.clone(),
+                        severity: vuln.severity.clone(),
+                    });
+                }
+            }
+        }
+        findings
+    }
+
+    /// Findings across the fleet, keyed by site ID
+    pub fn scan_fleet<'a>(
+        &self,
+        inventories: impl IntoIterator<Item = &'a WordPressInventory>,
+    ) -> HashMap<String, Vec<Finding>> {
+        inventories
+            .into_iter()
+            .map(|inv| (inv.site_id.clone(), self.scan(inv)))
+            .filter(|(_, findings)| !findings.is_empty())
+            .collect()
+    }
+}
+
+/// Compare dotted version strings numerically where possible ("6.10" > "6.9")
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let split = |v: &str| -> Vec<String> {
+        v.split(['.', '-', '+']).map(str::to_string).collect()
+    };
+    let (pa, pb) = (split(a), split(b));
+    for i in 0..pa.len().max(pb.len()) {
+        let x = pa.get(i).map(String::as_str).unwrap_or("0");
+        let y = pb.get(i).map(String::as_str).unwrap_or("0");
+        let ord = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            // Pre-release tags sort before the release they precede
+            (Ok(_), Err(_)) => Ordering::Greater,
+            (Err(_), Ok(_)) => Ordering::Less,
+            (Err(_), Err(_)) => x.cmp(y),
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{app, press};
+
+    fn range(introduced: Option<&str>, fixed: Option<&str>, last_affected: Option<&str>) -> Affected {
+        Affected {
+            component: ComponentType::Plugin,
+            slug: "plugin".to_string(),
+            introduced: introduced.map(str::to_string),
+            fixed: fixed.map(str::to_string),
+            last_affected: last_affected.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn versions_compare_numerically() {
+        let cases = [
+            ("6.10", "6.9", Ordering::Greater),
+            ("6.9", "6.9.0", Ordering::Equal),
+            ("6.6.2", "6.6.3", Ordering::Less),
+            ("1.0-beta1", "1.0", Ordering::Less),
+            ("1.0-beta2", "1.0-beta1", Ordering::Greater),
+            ("10", "9.99", Ordering::Greater),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(compare_versions(a, b), expected, "{} vs {}", a, b);
+            assert_eq!(compare_versions(b, a), expected.reverse(), "{} vs {}", b, a);
+        }
+    }
+
+    #[test]
+    fn affected_ranges() {
+        let cases = [
+            (range(None, Some("6.9"), None), "6.0", true),
+            (range(None, Some("6.9"), None), "6.9", false),
+            (range(None, Some("6.9"), None), "6.10", false),
+            (range(Some("6.6"), Some("6.6.3"), None), "6.5.9", false),
+            (range(Some("6.6"), Some("6.6.3"), None), "6.6", true),
+            (range(Some("6.6"), Some("6.6.3"), None), "6.6.2", true),
+            (range(None, None, Some("2.1")), "2.1", true),
+            (range(None, None, Some("2.1")), "2.1.1", false),
+            // Fixed wins over last_affected
+            (range(None, Some("3.0"), Some("2.1")), "2.5", true),
+            (range(Some("1.0"), None, None), "99", true),
+            (range(None, None, None), "0.1", true),
+        ];
+        for (affected, version, expected) in cases {
+            assert_eq!(affected.contains(version), expected, "{} in {:?}", version, affected);
+        }
+    }
+
+    #[tokio::test]
+    async fn fleet_query_finds_affected_sites() {
+        let mut app = app("vuln_query");
+        press(&mut app, "5 r").await;
+        let affected = |query: &str| -> Vec<String> {
+            app.sites_affected_by(query).iter().map(|s| s.id.clone()).collect()
+        };
+        assert_eq!(affected("CVE-2020-25213"), ["site-2"]);
+        // By alias, in any case
+        assert_eq!(affected("wpscan-2024-1180"), ["site-2"]);
+        assert!(affected("CVE-1999-0001").is_empty());
+
+        app.vuln_query = "CVE-2024-10924".to_string();
+        let findings = app.filtered_findings();
+        assert_eq!(findings.len(), 1);
+        assert_eq!((findings[0].component, findings[0].installed.as_str()), (ComponentType::Core, "6.6.2"));
+    }
+
+    #[tokio::test]
+    async fn acknowledged_findings_stay_acknowledged_after_a_refresh() {
+        let mut app = app("vuln_ack");
+        press(&mut app, "5 r 3").await;
+        let id = "vuln-CVE-2020-25213-site-2-wp-file-manager";
+        app.selected_alert = app.alerts.iter().position(|a| a.id == id).unwrap();
+        press(&mut app, "a").await;
+        press(&mut app, "esc r").await;
+        let alert = app.alerts.iter().find(|a| a.id == id).unwrap();
+        assert!(alert.acknowledged);
+        assert!(app.alerts.iter().any(|a| a.is_local() && !a.acknowledged));
+    }
+
+    #[tokio::test]
+    async fn findings_keep_the_time_they_were_first_seen() {
+        let mut app = app("vuln_age");
+        press(&mut app, "5 r").await;
+        let id = "vuln-CVE-2020-25213-site-2-wp-file-manager";
+        let first = app.alerts.iter().find(|a| a.id == id).unwrap().timestamp;
+        assert_eq!(first, app.now());
+
+        app.clock = Some(first + chrono::Duration::hours(2));
+        press(&mut app, "r").await;
+        assert_eq!(app.alerts.iter().find(|a| a.id == id).unwrap().timestamp, first);
+    }
+}
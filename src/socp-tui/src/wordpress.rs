@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: AGPL-3.0-or-later
+//! WordPress inventory: installed core, plugins and themes per site
+//!
+//! The control plane reports what is actually installed alongside the
+//! plugin lists declared in the site's Nickel config, so drift between the
+//! two can be highlighted without a round-trip to the config repository.
+
+use serde::{Deserialize, Serialize};
+
+/// Runtime state of an installed plugin (mirrors `PluginState` in wordpress.ncl)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginState {
+    Active,
+    Inactive,
+    MustUse,
+    DropIn,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoreInfo {
+    pub version: String,
+    pub update_version: Option<String>,
+    /// `auto_update_core` policy: disabled, minor or major
+    pub auto_update: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginInfo {
+    pub slug: String,
+    pub version: String,
+    pub update_version: Option<String>,
+    pub state: PluginState,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeInfo {
+    pub slug: String,
+    pub version: String,
+    pub update_version: Option<String>,
+    pub active: bool,
+}
+
+/// Plugin lists as declared in `wordpress.plugins`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeclaredPlugins {
+    pub must_use: Vec<String>,
+    pub active: Vec<String>,
+    #[serde(alias = "disabled")]
+    pub inactive: Vec<String>,
+    pub blocklist: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WordPressInventory {
+    pub site_id: String,
+    pub core: CoreInfo,
+    pub plugins: Vec<PluginInfo>,
+    pub themes: Vec<ThemeInfo>,
+    pub declared: DeclaredPlugins,
+}
+
+/// A difference between declared and installed plugins
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Divergence {
+    /// Declared inactive/disabled but running
+    DisabledButActive(String),
+    /// Declared active but installed inactive
+    ExpectedActive(String),
+    /// Declared but not installed at all
+    Missing(String),
+    /// Installed despite being on the blocklist
+    Blocklisted(String),
+    /// Installed but not mentioned in any declared list
+    Undeclared(String),
+}
+
+impl Divergence {
+    pub fn slug(&self) -> &str {
+        match self {
+            Divergence::DisabledButActive(s)
+            | Divergence::ExpectedActive(s)
+            | Divergence::Missing(s)
+            | Divergence::Blocklisted(s)
+            | Divergence::Undeclared(s) => s,
+        }
+    }
+
+    /// Whether this divergence is a policy violation rather than drift
+    pub fn is_violation(&self) -> bool {
+        matches!(self, Divergence::DisabledButActive(_) | Divergence::Blocklisted(_))
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Divergence::DisabledButActive(s) => format!("{} is declared disabled but active", s),
+            Divergence::ExpectedActive(s) => format!("{} is declared active but inactive", s),
+            Divergence::Missing(s) => format!("{} is declared but not installed", s),
+            Divergence::Blocklisted(s) => format!("{} is blocklisted but installed", s),
+            Divergence::Undeclared(s) => format!("{} is installed but not declared", s),
+        }
+    }
+}
+
+impl WordPressInventory {
+    pub fn plugin(&self, slug: &str) -> Option<&PluginInfo> {
+        self.plugins.iter().find(|p| p.slug == slug)
+    }
+
+    pub fn updates_available(&self) -> usize {
+        usize::from(self.core.update_version.is_some())
+            + self.plugins.iter().filter(|p| p.update_version.is_some()).count()
+            + self.themes.iter().filter(|t| t.update_version.is_some()).count()
+    }
+
+    /// Compare installed plugins with the declared lists
+    pub fn divergences(&self) -> Vec<Divergence> {
+        let declared = &self.declared;
+        let mut out = Vec::new();
+
+        for slug in &declared.must_use {
+            match self.plugin(slug) {
+                None => out.push(Divergence::Missing(slug.clone())),
+                Some(p) if p.state == PluginState::Inactive => {
+                    out.push(Divergence::ExpectedActive(slug.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+        for slug in &declared.active {
+            match self.plugin(slug) {
+                None => out.push(Divergence::Missing(slug.clone())),
+                Some(p) if p.state == PluginState::Inactive => {
+                    out.push(Divergence::ExpectedActive(slug.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+        for slug in &declared.inactive {
+            if let Some(p) = self.plugin(slug) {
+                if matches!(p.state, PluginState::Active | PluginState::MustUse) {
+                    out.push(Divergence::DisabledButActive(slug.clone()));
+                }
+            }
+        }
+        for plugin in &self.plugins {
+            let slug = &plugin.slug;
+            if declared.blocklist.contains(slug) {
+                out.push(Divergence::Blocklisted(slug.clone()));
+            } else if plugin.state != PluginState::DropIn
+                && !declared.must_use.contains(slug)
+                && !declared.active.contains(slug)
+                && !declared.inactive.contains(slug)
+            {
+                out.push(Divergence::Undeclared(slug.clone()));
+            }
+        }
+        out
+    }
+}